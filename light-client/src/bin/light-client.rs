@@ -0,0 +1,437 @@
+//! Command-line light client.
+//!
+//! `init` fetches the block at a trusted height from the primary and records
+//! it in the light store, together with the configuration of the client.
+//! `sync` then verifies the blocks of the primary up to its highest block, or
+//! to a target height, cross-checking them with the witnesses. `status` and
+//! `inspect` report the contents of the light store.
+//!
+//! The configuration and the light store are kept in the home directory:
+//! `<home>/config.json` and `<home>/store` respectively.
+//!
+//! Endpoints are given as `file://<dir>` URLs, which are served by a
+//! `DirectoryIo`, or, when built with the `rpc-client` feature, as the
+//! `http://<host>:<port>` addresses of the RPC endpoints of full nodes, which
+//! are served by an `RpcIo`.
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use tendermint::block::CommitSig;
+
+#[cfg(feature = "rpc-client")]
+use tendermint_light_client::components::io::RpcIo;
+
+use tendermint_light_client::{
+    components::{
+        clock::SystemClock,
+        io::{DirectoryIo, Provider},
+        verifier::ProdVerifier,
+    },
+    fork_detector::ProdForkDetector,
+    light_client::{LightClient, Options},
+    store::{file::FileStore, memory::MemoryStore, LightStore},
+    supervisor::{Instance, Supervisor},
+    types::{
+        ChainId, Hash, Height, LightBlock, PeerId, Status, TrustOptions, TrustThresholdFraction,
+    },
+};
+
+const USAGE: &str = "\
+Usage: light-client <command> --home <dir> [options]
+
+Commands:
+    init       Initialize the light client from a trusted block
+                   --chain-id <id> --height <height> --hash <hash>
+                   --primary <endpoint> --witness <endpoint>...
+                   [--trusting-period <seconds>] [--trust-threshold <n>/<d>]
+    sync       Verify the highest block of the primary
+                   [--height <height>]   verify the block at this height instead
+    status     Print the highest trusted and verified heights
+    inspect    Print the light block at the given height
+                   --height <height>
+
+Endpoints are given as file://<dir> URLs, or as http://<host>:<port> RPC
+addresses when built with the rpc-client feature.";
+
+/// Default trusting period: two weeks, well within the usual unbonding period
+const DEFAULT_TRUSTING_PERIOD: Duration = Duration::from_secs(14 * 24 * 60 * 60);
+
+/// Timeout of the requests to RPC endpoints
+#[cfg(feature = "rpc-client")]
+const RPC_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[cfg(feature = "rpc-client")]
+const SUPPORTED_ENDPOINTS: &str = "only file:// and http:// endpoints are supported";
+#[cfg(not(feature = "rpc-client"))]
+const SUPPORTED_ENDPOINTS: &str = "only file:// endpoints are supported";
+
+type Result<T> = std::result::Result<T, Box<dyn Error + Send + Sync>>;
+
+/// Configuration of the light client, written by `init`
+#[derive(Serialize, Deserialize)]
+struct Config {
+    chain_id: String,
+    /// Trusting period, in seconds
+    trusting_period: u64,
+    trust_threshold: TrustThresholdFraction,
+    primary: String,
+    witnesses: Vec<String>,
+}
+
+impl Config {
+    fn path(home: &Path) -> PathBuf {
+        home.join("config.json")
+    }
+
+    fn load(home: &Path) -> Result<Self> {
+        let path = Self::path(home);
+        let json = fs::read_to_string(&path).map_err(|e| {
+            format!(
+                "cannot read {}: {} (has the light client been initialized?)",
+                path.display(),
+                e
+            )
+        })?;
+        Ok(serde_json::from_str(&json)?)
+    }
+
+    fn save(&self, home: &Path) -> Result<()> {
+        fs::write(Self::path(home), serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    fn options(&self) -> Result<Options> {
+        Ok(Options {
+            chain_id: self.chain_id.parse()?,
+            trust_threshold: self.trust_threshold,
+            trusting_period: Duration::from_secs(self.trusting_period),
+            clock_drift: Options::DEFAULT_CLOCK_DRIFT,
+        })
+    }
+}
+
+/// Command-line flags, each of which may be given several times
+struct Flags(BTreeMap<String, Vec<String>>);
+
+impl Flags {
+    fn parse(args: &[String]) -> Result<Self> {
+        let mut flags = BTreeMap::<String, Vec<String>>::new();
+        let mut args = args.iter();
+
+        while let Some(arg) = args.next() {
+            let name = arg
+                .strip_prefix("--")
+                .ok_or_else(|| format!("unexpected argument: {}", arg))?;
+            let value = args
+                .next()
+                .ok_or_else(|| format!("missing value for --{}", name))?;
+            flags
+                .entry(name.to_string())
+                .or_default()
+                .push(value.clone());
+        }
+
+        Ok(Self(flags))
+    }
+
+    fn optional(&mut self, name: &str) -> Result<Option<String>> {
+        match self.0.remove(name) {
+            None => Ok(None),
+            Some(mut values) if values.len() == 1 => Ok(values.pop()),
+            Some(_) => Err(format!("--{} given more than once", name).into()),
+        }
+    }
+
+    fn required(&mut self, name: &str) -> Result<String> {
+        self.optional(name)?
+            .ok_or_else(|| format!("missing --{}", name).into())
+    }
+
+    fn all(&mut self, name: &str) -> Vec<String> {
+        self.0.remove(name).unwrap_or_default()
+    }
+
+    /// Fail on any flag which the command did not consume
+    fn finish(self) -> Result<()> {
+        match self.0.keys().next() {
+            Some(name) => Err(format!("unexpected flag: --{}", name).into()),
+            None => Ok(()),
+        }
+    }
+}
+
+fn parse_height(height: &str) -> Result<Height> {
+    height
+        .parse()
+        .map_err(|e| format!("invalid height {}: {}", height, e).into())
+}
+
+fn parse_trust_threshold(threshold: &str) -> Result<TrustThresholdFraction> {
+    let (numerator, denominator) = match threshold.find('/') {
+        Some(i) => (&threshold[..i], &threshold[i + 1..]),
+        None => return Err(format!("invalid trust threshold: {}", threshold).into()),
+    };
+    Ok(TrustThresholdFraction::new(
+        numerator.parse()?,
+        denominator.parse()?,
+    )?)
+}
+
+/// Build the provider for the given endpoint
+fn provider(endpoint: &str) -> Result<Provider> {
+    let peer_id = PeerId::new(endpoint);
+    if let Some(dir) = endpoint.strip_prefix("file://") {
+        return Ok(Provider::new(peer_id, Box::new(DirectoryIo::new(dir))));
+    }
+
+    #[cfg(feature = "rpc-client")]
+    {
+        if endpoint.starts_with("http://") {
+            let io = RpcIo::new(peer_id.clone(), endpoint, Some(RPC_TIMEOUT))?;
+            return Ok(Provider::new(peer_id, Box::new(io)));
+        }
+    }
+
+    Err(format!("unsupported endpoint {}: {}", endpoint, SUPPORTED_ENDPOINTS).into())
+}
+
+fn light_client(endpoint: &str, options: Options) -> Result<LightClient> {
+    Ok(LightClient::new(
+        provider(endpoint)?,
+        options,
+        Box::new(ProdVerifier::default()),
+        Box::new(SystemClock),
+    ))
+}
+
+fn open_store(home: &Path) -> Result<FileStore> {
+    Ok(FileStore::open(home.join("store"))?)
+}
+
+fn init(home: &Path, mut flags: Flags) -> Result<()> {
+    let chain_id: ChainId = flags.required("chain-id")?.parse()?;
+    let height = parse_height(&flags.required("height")?)?;
+    let hash: Hash = flags.required("hash")?.parse()?;
+    let primary = flags.required("primary")?;
+    let witnesses = flags.all("witness");
+    let trusting_period = match flags.optional("trusting-period")? {
+        Some(seconds) => Duration::from_secs(seconds.parse()?),
+        None => DEFAULT_TRUSTING_PERIOD,
+    };
+    let trust_threshold = match flags.optional("trust-threshold")? {
+        Some(threshold) => parse_trust_threshold(&threshold)?,
+        None => TrustThresholdFraction::default(),
+    };
+    flags.finish()?;
+
+    if witnesses.is_empty() {
+        return Err("at least one --witness is required for fork detection".into());
+    }
+    for endpoint in &witnesses {
+        provider(endpoint)?;
+    }
+
+    let trust_options = TrustOptions {
+        chain_id,
+        period: trusting_period,
+        height,
+        hash,
+        trust_threshold,
+    };
+
+    fs::create_dir_all(home)?;
+    let mut light_store = open_store(home)?;
+    let trusted_block = light_client(&primary, Options::from(&trust_options))?
+        .initialize(&trust_options, &mut light_store)?;
+
+    Config {
+        chain_id: trust_options.chain_id.to_string(),
+        trusting_period: trusting_period.as_secs(),
+        trust_threshold,
+        primary,
+        witnesses,
+    }
+    .save(home)?;
+
+    println!(
+        "initialized at height {} with hash {}",
+        trusted_block.height(),
+        trusted_block.signed_header.header.hash()
+    );
+    Ok(())
+}
+
+fn sync(home: &Path, mut flags: Flags) -> Result<()> {
+    let target = match flags.optional("height")? {
+        Some(height) => Some(parse_height(&height)?),
+        None => None,
+    };
+    flags.finish()?;
+
+    let config = Config::load(home)?;
+    let options = config.options()?;
+    let light_store = open_store(home)?;
+
+    if light_store.highest(Status::Trusted).is_none() {
+        return Err("no trusted block in the light store".into());
+    }
+
+    // The witnesses are only queried for fork detection. The light store
+    // in `home` stays with the primary, including a witness which replaces
+    // a faulty primary, so that everything it verifies is persisted.
+    let witnesses = config
+        .witnesses
+        .iter()
+        .map(|endpoint| {
+            Ok(Instance::new(
+                light_client(endpoint, options.clone())?,
+                Box::new(MemoryStore::new()),
+            ))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let primary = Instance::new(
+        light_client(&config.primary, options)?,
+        Box::new(light_store),
+    );
+    let mut supervisor = Supervisor::new(primary, witnesses, Box::new(ProdForkDetector::new()));
+
+    let verified_block = match target {
+        Some(height) => supervisor.verify_to_target(height)?,
+        None => supervisor.verify_to_highest()?,
+    };
+
+    println!(
+        "verified height {} with hash {}",
+        verified_block.height(),
+        verified_block.signed_header.header.hash()
+    );
+    Ok(())
+}
+
+fn status(home: &Path, flags: Flags) -> Result<()> {
+    flags.finish()?;
+
+    let config = Config::load(home)?;
+    let light_store = open_store(home)?;
+
+    let describe = |light_block: Option<LightBlock>| match light_block {
+        Some(light_block) => format!(
+            "{} ({})",
+            light_block.height(),
+            light_block.signed_header.header.hash()
+        ),
+        None => "none".to_string(),
+    };
+
+    println!("chain id: {}", config.chain_id);
+    println!(
+        "highest trusted: {}",
+        describe(light_store.highest(Status::Trusted))
+    );
+    println!(
+        "highest verified: {}",
+        describe(light_store.highest(Status::Verified))
+    );
+    Ok(())
+}
+
+fn inspect(home: &Path, mut flags: Flags) -> Result<()> {
+    let height = parse_height(&flags.required("height")?)?;
+    flags.finish()?;
+
+    let light_store = open_store(home)?;
+    let (light_block, status) = light_store
+        .get_non_failed(height)
+        .or_else(|| {
+            light_store
+                .get(height, Status::Failed)
+                .map(|lb| (lb, Status::Failed))
+        })
+        .ok_or_else(|| format!("no light block at height {} in the light store", height))?;
+
+    let header = &light_block.signed_header.header;
+    let commit = &light_block.signed_header.commit;
+    let signed = commit
+        .signatures
+        .iter()
+        .filter(|sig| matches!(sig, CommitSig::BlockIDFlagCommit { .. }))
+        .count();
+
+    println!("height: {}", header.height);
+    println!("status: {}", status_name(&status));
+    println!("chain id: {}", header.chain_id);
+    println!("time: {}", header.time);
+    println!("hash: {}", header.hash());
+    match &header.last_block_id {
+        Some(last_block_id) => println!("last block id: {}", last_block_id),
+        None => println!("last block id: none"),
+    }
+    println!("validators hash: {}", header.validators_hash);
+    println!("next validators hash: {}", header.next_validators_hash);
+    println!("app hash: {}", hex_upper(&header.app_hash));
+    println!(
+        "commit: round {}, block id {}, {} of {} signatures",
+        commit.round,
+        commit.block_id,
+        signed,
+        commit.signatures.len()
+    );
+    println!(
+        "validators (total power {}):",
+        light_block.validators.total_voting_power()
+    );
+    for validator in light_block.validators.validators() {
+        println!("    {} power {}", validator.address, validator.power);
+    }
+    Ok(())
+}
+
+fn status_name(status: &Status) -> &'static str {
+    match status {
+        Status::Failed => "failed",
+        Status::Unverified => "unverified",
+        Status::Verified => "verified",
+        Status::Trusted => "trusted",
+    }
+}
+
+fn hex_upper(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02X}", b)).collect()
+}
+
+fn run(args: &[String]) -> Result<()> {
+    let (command, args) = args.split_first().ok_or("missing command")?;
+    let mut flags = Flags::parse(args)?;
+    let home = PathBuf::from(flags.required("home")?);
+
+    match command.as_str() {
+        "init" => init(&home, flags),
+        "sync" => sync(&home, flags),
+        "status" => status(&home, flags),
+        "inspect" => inspect(&home, flags),
+        _ => Err(format!("unknown command: {}", command).into()),
+    }
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    if args.is_empty() || args[0] == "help" || args[0] == "--help" {
+        println!("{}", USAGE);
+        return;
+    }
+
+    if let Err(e) = run(&args) {
+        eprintln!("error: {}", e);
+        eprintln!("run `light-client help` for usage");
+        process::exit(1);
+    }
+}
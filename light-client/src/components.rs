@@ -0,0 +1,5 @@
+//! Components used by the light client.
+
+pub mod clock;
+pub mod io;
+pub mod verifier;
@@ -0,0 +1,105 @@
+//! Provides an interface and default implementations of the `Clock` component
+
+use std::fmt;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+extern crate prusti_contracts;
+use prusti_contracts::*;
+
+use tendermint::Time;
+
+/// Abstracts over the current time.
+pub trait Clock: Send + Sync {
+    /// Get the current time.
+    fn now(&self) -> Time;
+}
+
+/// Provides the current wall clock time.
+#[derive(Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl fmt::Debug for SystemClock {
+    #[trusted]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SystemClock").finish()
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Time {
+        Time::now()
+    }
+}
+
+/// A clock which always returns the time it was last set to.
+///
+/// Clones share the same underlying time, so that a test can hand a clone
+/// to the light client and later move the time forward from the outside,
+/// eg. to simulate the expiry of the trusting period of a stored block.
+#[derive(Clone)]
+pub struct FixedClock {
+    now: Arc<RwLock<Time>>,
+}
+
+impl FixedClock {
+    /// Constructs a new clock set to the given time
+    pub fn new(now: Time) -> Self {
+        Self {
+            now: Arc::new(RwLock::new(now)),
+        }
+    }
+
+    /// Set the time returned by this clock and all its clones
+    pub fn set(&self, now: Time) {
+        *self.now.write().expect("clock lock poisoned") = now;
+    }
+
+    /// Move the time of this clock and all its clones forward by the given duration
+    ///
+    /// ## Panics
+    /// If the time moves past year 9999.
+    pub fn advance(&self, duration: Duration) {
+        let mut now = self.now.write().expect("clock lock poisoned");
+        *now = now
+            .checked_add(duration)
+            .expect("clock moved past year 9999");
+    }
+}
+
+impl fmt::Debug for FixedClock {
+    #[trusted]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("FixedClock").field(&self.now()).finish()
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> Time {
+        *self.now.read().expect("clock lock poisoned")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_clock_is_shared_between_clones() {
+        let clock = FixedClock::new(Time::unix_epoch());
+        let other = clock.clone();
+
+        assert_eq!(other.now(), Time::unix_epoch());
+
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(
+            other.now(),
+            Time::unix_epoch()
+                .checked_add(Duration::from_secs(10))
+                .unwrap()
+        );
+
+        other.set(Time::unix_epoch());
+        assert_eq!(clock.now(), Time::unix_epoch());
+    }
+}
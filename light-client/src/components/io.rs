@@ -0,0 +1,210 @@
+//! Provides an interface and a default implementation of the `Io` component
+
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use tendermint::proto::Protobuf;
+use thiserror::Error;
+
+extern crate prusti_contracts;
+use prusti_contracts::*;
+
+use crate::types::{Height, LightBlock, PeerId};
+
+#[cfg(feature = "rpc-client")]
+mod rpc;
+#[cfg(feature = "rpc-client")]
+pub use rpc::RpcIo;
+
+/// Type for selecting either a specific height or the latest one
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum AtHeight {
+    /// A specific height
+    At(Height),
+    /// The latest height
+    Highest,
+}
+
+impl fmt::Debug for AtHeight {
+    #[trusted]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AtHeight::At(height) => f.debug_tuple("At").field(height).finish(),
+            AtHeight::Highest => f.write_str("Highest"),
+        }
+    }
+}
+
+impl From<Height> for AtHeight {
+    fn from(height: Height) -> Self {
+        AtHeight::At(height)
+    }
+}
+
+/// I/O errors
+#[derive(Clone, Error, PartialEq)]
+pub enum IoError {
+    /// The peer does not have a light block at the requested height
+    #[error("no light block at height {0:?}")]
+    NotFound(AtHeight),
+
+    /// The peer returned a light block at another height than the requested one
+    #[error("invalid height: {0}")]
+    InvalidHeight(String),
+
+    /// The request to the peer timed out
+    #[error("request to peer {0} timed out after {1:?}")]
+    Timeout(PeerId, Duration),
+
+    /// Any other error reported by the RPC layer
+    #[error("rpc error: {0}")]
+    Rpc(String),
+
+    /// The peer returned a light block which could not be decoded
+    #[error("invalid light block returned by peer: {0}")]
+    InvalidResponse(String),
+}
+
+impl fmt::Debug for IoError {
+    #[trusted]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "IoError({})", self)
+    }
+}
+
+impl IoError {
+    /// Whether this error means that the request timed out
+    pub fn is_timeout(&self) -> bool {
+        matches!(self, IoError::Timeout(..))
+    }
+}
+
+/// Interface for fetching light blocks from a full node, typically via the RPC client.
+pub trait Io: Send + Sync {
+    /// Fetch a light block at the given height from the peer
+    fn fetch_light_block(&self, height: AtHeight) -> Result<LightBlock, IoError>;
+}
+
+/// A full node the light client fetches light blocks from, be it the primary or a witness.
+pub struct Provider {
+    /// The identifier of the full node
+    pub peer_id: PeerId,
+    /// The I/O component used to fetch light blocks from the full node
+    pub io: Box<dyn Io>,
+}
+
+impl Provider {
+    /// Constructs a new provider
+    pub fn new(peer_id: PeerId, io: Box<dyn Io>) -> Self {
+        Self { peer_id, io }
+    }
+
+    /// Fetch a light block at the given height from this provider
+    pub fn fetch_light_block(&self, height: AtHeight) -> Result<LightBlock, IoError> {
+        self.io.fetch_light_block(height)
+    }
+}
+
+impl fmt::Debug for Provider {
+    #[trusted]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Provider")
+            .field("peer_id", &self.peer_id)
+            .finish()
+    }
+}
+
+impl<F: Send + Sync> Io for F
+where
+    F: Fn(AtHeight) -> Result<LightBlock, IoError>,
+{
+    fn fetch_light_block(&self, height: AtHeight) -> Result<LightBlock, IoError> {
+        self(height)
+    }
+}
+
+/// Serves the light blocks stored in a directory, one protobuf-encoded
+/// `LightBlock` per `<height>.pb` file, as written by `store::file::FileStore`.
+///
+/// This stands in for a full node in tests and when replaying fixtures,
+/// the highest height being the greatest one found in the directory.
+#[derive(Clone)]
+pub struct DirectoryIo {
+    dir: PathBuf,
+}
+
+impl DirectoryIo {
+    /// Constructs a new `Io` serving the light blocks stored in the given directory
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// The greatest height of the light blocks in the directory, if any
+    fn highest_height(&self) -> Result<Option<Height>, IoError> {
+        let entries = fs::read_dir(&self.dir).map_err(|e| {
+            IoError::InvalidResponse(format!("cannot read {}: {}", self.dir.display(), e))
+        })?;
+
+        let highest = entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let path = entry.path();
+                if path.extension()? != "pb" {
+                    return None;
+                }
+                path.file_stem()?.to_str()?.parse::<Height>().ok()
+            })
+            .max();
+
+        Ok(highest)
+    }
+}
+
+impl fmt::Debug for DirectoryIo {
+    #[trusted]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DirectoryIo")
+            .field("dir", &self.dir)
+            .finish()
+    }
+}
+
+impl Io for DirectoryIo {
+    fn fetch_light_block(&self, height: AtHeight) -> Result<LightBlock, IoError> {
+        let at = match height {
+            AtHeight::At(at) => at,
+            AtHeight::Highest => self.highest_height()?.ok_or(IoError::NotFound(height))?,
+        };
+
+        let path = self.dir.join(format!("{}.pb", at));
+        let bytes = match fs::read(&path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err(IoError::NotFound(height))
+            }
+            Err(e) => {
+                return Err(IoError::InvalidResponse(format!(
+                    "cannot read {}: {}",
+                    path.display(),
+                    e
+                )))
+            }
+        };
+
+        let light_block = LightBlock::decode_vec(&bytes).map_err(|e| {
+            IoError::InvalidResponse(format!("cannot decode {}: {}", path.display(), e))
+        })?;
+
+        if light_block.height() != at {
+            return Err(IoError::InvalidHeight(format!(
+                "requested {}, got {}",
+                at,
+                light_block.height()
+            )));
+        }
+
+        Ok(light_block)
+    }
+}
@@ -0,0 +1,441 @@
+//! Fetches light blocks from the RPC endpoint of a full node
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::time::Duration;
+
+use hyper::{body, Client, Uri};
+use serde::{de::DeserializeOwned, Deserialize};
+use tendermint::proto::crypto::{public_key::Sum, PublicKey as RawPublicKey};
+use tendermint::proto::serializers;
+use tendermint::proto::types::{SignedHeader as RawSignedHeader, Validator as RawValidator};
+use tendermint::validator;
+
+extern crate prusti_contracts;
+use prusti_contracts::*;
+
+use crate::{
+    errors::ErrorKind,
+    types::{Height, LightBlock, PeerId, SignedHeader, ValidatorSet},
+    utils::block_on,
+};
+
+use super::{AtHeight, Io, IoError};
+
+/// Number of validators requested per page of the `/validators` endpoint,
+/// the maximum allowed by Tendermint
+const VALIDATORS_PER_PAGE: usize = 100;
+
+/// Maximum number of validators in a validator set, beyond which a peer's
+/// `/validators` response is rejected
+///
+/// <https://github.com/tendermint/tendermint/blob/v0.34.0/types/vote_set.go#L17-L21>
+const MAX_VALIDATORS: usize = 10_000;
+
+/// Fetches the signed header of a light block from the `/commit` endpoint of a
+/// full node, and its validator set from the `/validators` endpoint.
+#[derive(Clone)]
+pub struct RpcIo {
+    peer_id: PeerId,
+    address: String,
+    timeout: Option<Duration>,
+}
+
+impl RpcIo {
+    /// Constructs a new `Io` talking to the `http://` RPC endpoint at the given
+    /// address, failing any request which does not complete within `timeout`.
+    pub fn new(peer_id: PeerId, address: &str, timeout: Option<Duration>) -> Result<Self, IoError> {
+        let uri: Uri = address
+            .parse()
+            .map_err(|e| IoError::Rpc(format!("invalid address {}: {}", address, e)))?;
+        if uri.scheme_str() != Some("http") {
+            return Err(IoError::Rpc(format!(
+                "invalid address {}: only http:// endpoints are supported",
+                address
+            )));
+        }
+
+        Ok(Self {
+            peer_id,
+            address: address.trim_end_matches('/').to_string(),
+            timeout,
+        })
+    }
+
+    fn fetch_validators(&self, height: Height) -> Result<Vec<validator::Info>, IoError> {
+        let mut validators = Vec::new();
+        let mut total = None;
+
+        for page in 1.. {
+            let result: ValidatorsResult = self.call(&format!(
+                "validators?height={}&page={}&per_page={}",
+                height, page, VALIDATORS_PER_PAGE
+            ))?;
+
+            // The number of pages is bounded by the total announced on the
+            // first one, which must not change while paging.
+            let total = *total.get_or_insert(result.total);
+            if result.total != total {
+                return Err(IoError::InvalidResponse(format!(
+                    "total number of validators changed from {} to {} on page {}",
+                    total, result.total, page
+                )));
+            }
+            if total > MAX_VALIDATORS {
+                return Err(IoError::InvalidResponse(format!(
+                    "{} validators exceed the maximum of {}",
+                    total, MAX_VALIDATORS
+                )));
+            }
+
+            for validator in result.validators {
+                let info = validator::Info::try_from(RawValidator::from(validator))
+                    .map_err(|e| IoError::InvalidResponse(e.to_string()))?;
+                validators.push(info);
+            }
+
+            if validators.len() > total {
+                return Err(IoError::InvalidResponse(format!(
+                    "received more than the {} validators announced",
+                    total
+                )));
+            }
+            if validators.len() == total {
+                break;
+            }
+            if page * VALIDATORS_PER_PAGE >= total {
+                return Err(IoError::InvalidResponse(format!(
+                    "received {} of the {} validators announced",
+                    validators.len(),
+                    total
+                )));
+            }
+        }
+
+        Ok(validators)
+    }
+
+    /// Perform a JSON-RPC request over HTTP `GET`, returning its result
+    fn call<T: DeserializeOwned>(&self, path: &str) -> Result<T, IoError> {
+        let uri: Uri = format!("{}/{}", self.address, path)
+            .parse()
+            .map_err(|e| IoError::Rpc(format!("invalid request {}: {}", path, e)))?;
+
+        let body = block_on(self.timeout, async move {
+            let response = Client::new().get(uri).await?;
+            body::to_bytes(response.into_body()).await
+        })
+        .map_err(|e| match e.kind() {
+            ErrorKind::Timeout(timeout) => IoError::Timeout(self.peer_id.clone(), *timeout),
+            kind => IoError::Rpc(kind.to_string()),
+        })?
+        .map_err(|e| IoError::Rpc(e.to_string()))?;
+
+        let response: Response<T> = serde_json::from_slice(&body)
+            .map_err(|e| IoError::InvalidResponse(format!("{}: {}", path, e)))?;
+
+        match response {
+            Response {
+                result: Some(result),
+                ..
+            } => Ok(result),
+            Response {
+                error: Some(error), ..
+            } => Err(IoError::Rpc(format!(
+                "{} (code {}): {}",
+                error.message, error.code, error.data
+            ))),
+            _ => Err(IoError::InvalidResponse(format!(
+                "{}: neither a result nor an error",
+                path
+            ))),
+        }
+    }
+}
+
+impl fmt::Debug for RpcIo {
+    #[trusted]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RpcIo")
+            .field("peer_id", &self.peer_id)
+            .field("address", &self.address)
+            .field("timeout", &self.timeout)
+            .finish()
+    }
+}
+
+impl Io for RpcIo {
+    fn fetch_light_block(&self, height: AtHeight) -> Result<LightBlock, IoError> {
+        let path = match height {
+            AtHeight::At(height) => format!("commit?height={}", height),
+            AtHeight::Highest => "commit".to_string(),
+        };
+
+        let result: CommitResult = self.call(&path)?;
+        let signed_header = SignedHeader::try_from(result.signed_header)
+            .map_err(|e| IoError::InvalidResponse(e.to_string()))?;
+
+        let header = &signed_header.header;
+        if let AtHeight::At(height) = height {
+            if header.height != height {
+                return Err(IoError::InvalidHeight(format!(
+                    "requested {}, got {}",
+                    height, header.height
+                )));
+            }
+        }
+
+        let validators = self.fetch_validators(header.height)?;
+        let proposer = validators
+            .iter()
+            .find(|validator| validator.address == header.proposer_address)
+            .cloned();
+
+        let validator_set = ValidatorSet::new(validators, proposer)
+            .map_err(|e| IoError::InvalidResponse(e.to_string()))?;
+
+        Ok(LightBlock::new(signed_header, validator_set))
+    }
+}
+
+/// A JSON-RPC response, holding either a result or an error
+#[derive(Deserialize)]
+struct Response<T> {
+    result: Option<T>,
+    error: Option<ResponseError>,
+}
+
+#[derive(Deserialize)]
+struct ResponseError {
+    code: i32,
+    message: String,
+    #[serde(default)]
+    data: String,
+}
+
+/// Result of the `/commit` endpoint
+#[derive(Deserialize)]
+struct CommitResult {
+    signed_header: RawSignedHeader,
+}
+
+/// Result of the `/validators` endpoint
+#[derive(Deserialize)]
+struct ValidatorsResult {
+    validators: Vec<ValidatorResult>,
+    #[serde(with = "serializers::from_str")]
+    total: usize,
+}
+
+/// A validator, as returned by the `/validators` endpoint, whose public key is
+/// encoded as the bare `{ "type", "value" }` object
+#[derive(Deserialize)]
+struct ValidatorResult {
+    #[serde(with = "serializers::bytes::hexstring")]
+    address: Vec<u8>,
+    pub_key: Sum,
+    #[serde(with = "serializers::from_str")]
+    voting_power: i64,
+    #[serde(with = "serializers::from_str", default)]
+    proposer_priority: i64,
+}
+
+impl From<ValidatorResult> for RawValidator {
+    fn from(value: ValidatorResult) -> Self {
+        RawValidator {
+            address: value.address,
+            pub_key: Some(RawPublicKey {
+                sum: Some(value.pub_key),
+            }),
+            voting_power: value.voting_power,
+            proposer_priority: value.proposer_priority,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    const COMMIT_AT_HEIGHT_10: &str =
+        include_str!("../../../tests/support/rpc/commit_at_height_10.json");
+    const VALIDATORS_AT_HEIGHT_10: &str =
+        include_str!("../../../tests/support/rpc/validators_at_height_10.json");
+
+    /// Spawn a stub RPC server answering every request with the response
+    /// returned by `respond` for its path, and return its address
+    fn stub_server(respond: fn(&str) -> &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = format!("http://{}", listener.local_addr().unwrap());
+
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+
+                let mut reader = BufReader::new(&stream);
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut line = String::new();
+                while reader.read_line(&mut line).unwrap() > 2 {
+                    line.clear();
+                }
+
+                let path = request_line.split(' ').nth(1).unwrap_or_default();
+                let body = respond(path);
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+
+        address
+    }
+
+    fn kvstore(path: &str) -> &'static str {
+        if path.starts_with("/commit") {
+            COMMIT_AT_HEIGHT_10
+        } else if path.starts_with("/validators?height=10&page=1") {
+            VALIDATORS_AT_HEIGHT_10
+        } else {
+            r#"{"jsonrpc":"2.0","id":"","error":{"code":-32603,"message":"Internal error","data":"unexpected request"}}"#
+        }
+    }
+
+    fn rpc_io(address: &str) -> RpcIo {
+        RpcIo::new(PeerId::new("rpc"), address, Some(Duration::from_secs(5))).unwrap()
+    }
+
+    #[test]
+    fn fetches_light_block() {
+        let io = rpc_io(&stub_server(kvstore));
+
+        let light_block = io
+            .fetch_light_block(AtHeight::At(Height::from(10_u32)))
+            .unwrap();
+
+        let header = &light_block.signed_header.header;
+        assert_eq!(header.height, Height::from(10_u32));
+        assert_eq!(
+            header.hash(),
+            light_block.signed_header.commit.block_id.hash
+        );
+        assert_eq!(header.validators_hash, light_block.validators.hash());
+        assert_eq!(
+            light_block
+                .validators
+                .proposer()
+                .as_ref()
+                .map(|p| p.address),
+            Some(header.proposer_address)
+        );
+
+        let highest = io.fetch_light_block(AtHeight::Highest).unwrap();
+        assert_eq!(highest, light_block);
+    }
+
+    #[test]
+    fn rejects_light_block_at_another_height() {
+        let io = rpc_io(&stub_server(kvstore));
+
+        let error = io
+            .fetch_light_block(AtHeight::At(Height::from(11_u32)))
+            .unwrap_err();
+        assert!(matches!(error, IoError::InvalidHeight(_)), "{}", error);
+    }
+
+    #[test]
+    fn reports_rpc_errors() {
+        let io = rpc_io(&stub_server(
+            |_| r#"{"jsonrpc":"2.0","id":"","error":{"code":-32603,"message":"Internal error","data":"height 10 is not available"}}"#,
+        ));
+
+        let error = io
+            .fetch_light_block(AtHeight::At(Height::from(10_u32)))
+            .unwrap_err();
+        assert_eq!(
+            error,
+            IoError::Rpc("Internal error (code -32603): height 10 is not available".to_string())
+        );
+    }
+
+    /// The validators at height 10, announcing the given total
+    fn validators_with_total(total: &str) -> &'static str {
+        Box::leak(
+            VALIDATORS_AT_HEIGHT_10
+                .replace(r#""total": "1""#, &format!(r#""total": "{}""#, total))
+                .into_boxed_str(),
+        )
+    }
+
+    #[test]
+    fn rejects_too_many_validators() {
+        let io = rpc_io(&stub_server(|path| {
+            if path.starts_with("/validators") {
+                validators_with_total("10001")
+            } else {
+                kvstore(path)
+            }
+        }));
+
+        let error = io
+            .fetch_light_block(AtHeight::At(Height::from(10_u32)))
+            .unwrap_err();
+        assert!(matches!(error, IoError::InvalidResponse(_)), "{}", error);
+    }
+
+    #[test]
+    fn rejects_total_changing_between_pages() {
+        let io = rpc_io(&stub_server(|path| {
+            if path.starts_with("/validators?height=10&page=1&") {
+                validators_with_total("101")
+            } else if path.starts_with("/validators") {
+                validators_with_total("102")
+            } else {
+                kvstore(path)
+            }
+        }));
+
+        let error = io
+            .fetch_light_block(AtHeight::At(Height::from(10_u32)))
+            .unwrap_err();
+        assert!(
+            matches!(&error, IoError::InvalidResponse(e) if e.contains("changed")),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn rejects_fewer_validators_than_announced() {
+        let io = rpc_io(&stub_server(|path| {
+            if path.starts_with("/validators") {
+                validators_with_total("2")
+            } else {
+                kvstore(path)
+            }
+        }));
+
+        let error = io
+            .fetch_light_block(AtHeight::At(Height::from(10_u32)))
+            .unwrap_err();
+        assert!(
+            matches!(&error, IoError::InvalidResponse(e) if e.contains("of the 2")),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn accepts_only_http_addresses() {
+        assert!(RpcIo::new(PeerId::new("rpc"), "http://127.0.0.1:26657", None).is_ok());
+        assert!(RpcIo::new(PeerId::new("rpc"), "tcp://127.0.0.1:26657", None).is_err());
+        assert!(RpcIo::new(PeerId::new("rpc"), "not an address", None).is_err());
+    }
+}
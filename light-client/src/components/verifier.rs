@@ -0,0 +1,659 @@
+//! Provides an interface and a default implementation of the `Verifier` component
+
+use std::collections::HashSet;
+use std::fmt;
+use std::time::Duration;
+
+use tendermint::account;
+use tendermint::signature::{DefaultVerifier, Verifier as SignatureVerifier};
+use thiserror::Error;
+
+extern crate prusti_contracts;
+use prusti_contracts::*;
+
+use crate::{
+    light_client::Options,
+    types::{
+        ChainId, Hash, Height, LightBlock, SignedHeader, Time, TrustThresholdFraction, ValidatorSet,
+    },
+};
+
+/// Voting power tallied for a commit, out of the total power of a validator set.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct VotingPowerTally {
+    /// Total voting power of the validator set
+    pub total: u64,
+    /// Voting power of the validators which signed the commit
+    pub tallied: u64,
+}
+
+impl VotingPowerTally {
+    /// Whether the tallied voting power is more than the given fraction of the total
+    pub fn exceeds(&self, threshold: TrustThresholdFraction) -> bool {
+        threshold.is_exceeded_by(self.tallied, self.total)
+    }
+}
+
+impl fmt::Debug for VotingPowerTally {
+    #[trusted]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "VotingPowerTally({}/{})", self.tallied, self.total)
+    }
+}
+
+impl fmt::Display for VotingPowerTally {
+    #[trusted]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.tallied, self.total)
+    }
+}
+
+/// The various errors which can be raised by the verifier component,
+/// when validating or verifying a light block.
+#[derive(Clone, Error, PartialEq)]
+pub enum VerificationError {
+    /// The header belongs to another chain than the one being verified
+    #[error("chain ID mismatch: got {got}, expected {expected}")]
+    ChainIdMismatch {
+        /// Chain ID of the header
+        got: ChainId,
+        /// Chain ID of the chain being verified
+        expected: ChainId,
+    },
+
+    /// The untrusted header is not higher than the trusted one
+    #[error("non-increasing height: got {got}, expected height greater than {expected}")]
+    NonIncreasingHeight {
+        /// Height of the untrusted header
+        got: Height,
+        /// Height of the trusted header
+        expected: Height,
+    },
+
+    /// The untrusted header is not more recent than the trusted one
+    #[error("non-monotonic BFT time: header time {header_bft_time:?} is not after trusted header time {trusted_header_bft_time:?}")]
+    NonMonotonicBftTime {
+        /// Time of the untrusted header
+        header_bft_time: Time,
+        /// Time of the trusted header
+        trusted_header_bft_time: Time,
+    },
+
+    /// The trusted header is outside of the trusting period
+    #[error("header not within trusting period: expires_at={expires_at:?} now={now:?}")]
+    NotWithinTrustPeriod {
+        /// Time at which the trusting period of the trusted header expires
+        expires_at: Time,
+        /// Current time
+        now: Time,
+    },
+
+    /// The untrusted header is from the future, even accounting for clock drift
+    #[error("header from the future: header_time={header_time:?} now={now:?}")]
+    HeaderFromTheFuture {
+        /// Time of the untrusted header
+        header_time: Time,
+        /// Current time
+        now: Time,
+    },
+
+    /// A duration added to a time, such as the trusting period or the clock
+    /// drift, takes it out of the range of representable times
+    #[error("time overflow: {time:?} plus {duration:?} is out of range")]
+    TimeOverflow {
+        /// The time the duration was added to
+        time: Time,
+        /// The added duration
+        duration: Duration,
+    },
+
+    /// The validator set does not match the `validators_hash` of the header
+    #[error("invalid validator set: header has validators hash {header_validators_hash}, validator set hashes to {validators_hash}")]
+    InvalidValidatorSet {
+        /// Validators hash of the header
+        header_validators_hash: Hash,
+        /// Hash of the validator set
+        validators_hash: Hash,
+    },
+
+    /// The validators of an adjacent header are not the next validators of the trusted header
+    #[error("invalid next validator set: header has validators hash {header_validators_hash}, trusted header has next validators hash {next_validators_hash}")]
+    InvalidNextValidatorSet {
+        /// Validators hash of the untrusted header
+        header_validators_hash: Hash,
+        /// Next validators hash of the trusted header
+        next_validators_hash: Hash,
+    },
+
+    /// The commit is for another block than the header
+    #[error(
+        "invalid commit value: header has hash {header_hash}, commit is for block {commit_hash}"
+    )]
+    InvalidCommitValue {
+        /// Hash of the header
+        header_hash: Hash,
+        /// Hash of the block the commit is for
+        commit_hash: Hash,
+    },
+
+    /// The signature of a validator in the commit does not verify
+    #[error("invalid signature from validator {validator_address:?}: {reason}")]
+    InvalidSignature {
+        /// Address of the validator
+        validator_address: account::Id,
+        /// Why the signature failed to verify
+        reason: String,
+    },
+
+    /// The commit signatures are not aligned with the validator set of the header
+    #[error("invalid commit: {0}")]
+    InvalidCommit(String),
+
+    /// Not enough of the trusted validators signed the untrusted header, as per the trust threshold
+    #[error("insufficient signers overlap: {0}")]
+    InsufficientSignersOverlap(VotingPowerTally),
+
+    /// Less than 2/3 of the validators signed the header
+    #[error("insufficient voting power in commit: {0}")]
+    InsufficientCommitPower(VotingPowerTally),
+}
+
+impl fmt::Debug for VerificationError {
+    #[trusted]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "VerificationError({})", self)
+    }
+}
+
+/// Represents the result of the verification performed by the
+/// verifier component.
+#[derive(Clone, PartialEq)]
+pub enum Verdict {
+    /// Verification succeeded, the block is valid.
+    Success,
+    /// The minimum voting power threshold is not reached,
+    /// the block cannot be trusted yet.
+    NotEnoughTrust(VotingPowerTally),
+    /// Verification failed, the block is invalid.
+    Invalid(VerificationError),
+}
+
+impl fmt::Debug for Verdict {
+    #[trusted]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Verdict::Success => f.write_str("Success"),
+            Verdict::NotEnoughTrust(tally) => f.debug_tuple("NotEnoughTrust").field(tally).finish(),
+            Verdict::Invalid(e) => f.debug_tuple("Invalid").field(e).finish(),
+        }
+    }
+}
+
+impl From<Result<(), VerificationError>> for Verdict {
+    fn from(result: Result<(), VerificationError>) -> Self {
+        match result {
+            Ok(()) => Self::Success,
+            Err(VerificationError::InsufficientSignersOverlap(tally)) => {
+                Self::NotEnoughTrust(tally)
+            }
+            Err(e) => Self::Invalid(e),
+        }
+    }
+}
+
+/// The verifier checks:
+///
+/// a) whether a given untrusted light block is valid, and
+/// b) whether a given untrusted light block should be trusted
+///    based on a previously verified block.
+///
+/// ## Implements
+/// - [TMBC-VAL-CONTAINS-CORR.1]
+/// - [TMBC-VAL-COMMIT.1]
+pub trait Verifier: Send + Sync {
+    /// Perform the verification, with `now` as the current time.
+    fn verify(
+        &self,
+        untrusted: &LightBlock,
+        trusted: &LightBlock,
+        options: &Options,
+        now: Time,
+    ) -> Verdict;
+}
+
+/// Production implementation of the verifier.
+///
+/// Commit signatures are checked with the given signature verifier, which
+/// allows plugging in a different cryptographic backend.
+pub struct ProdVerifier {
+    signature_verifier: Box<dyn SignatureVerifier>,
+}
+
+impl ProdVerifier {
+    /// Constructs a new verifier, checking signatures with the given signature verifier
+    pub fn new(signature_verifier: Box<dyn SignatureVerifier>) -> Self {
+        Self { signature_verifier }
+    }
+}
+
+impl Default for ProdVerifier {
+    fn default() -> Self {
+        Self::new(Box::new(DefaultVerifier))
+    }
+}
+
+impl fmt::Debug for ProdVerifier {
+    #[trusted]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProdVerifier").finish()
+    }
+}
+
+impl Verifier for ProdVerifier {
+    fn verify(
+        &self,
+        untrusted: &LightBlock,
+        trusted: &LightBlock,
+        options: &Options,
+        now: Time,
+    ) -> Verdict {
+        verify(
+            untrusted,
+            trusted,
+            options,
+            now,
+            self.signature_verifier.as_ref(),
+        )
+        .into()
+    }
+}
+
+fn verify(
+    untrusted: &LightBlock,
+    trusted: &LightBlock,
+    options: &Options,
+    now: Time,
+    signature_verifier: &dyn SignatureVerifier,
+) -> Result<(), VerificationError> {
+    let untrusted_header = &untrusted.signed_header.header;
+    let trusted_header = &trusted.signed_header.header;
+
+    if untrusted_header.chain_id != options.chain_id {
+        return Err(VerificationError::ChainIdMismatch {
+            got: untrusted_header.chain_id.clone(),
+            expected: options.chain_id.clone(),
+        });
+    }
+
+    is_within_trust_period(trusted, options.trusting_period, now)?;
+
+    let drifted_now = checked_add(now, options.clock_drift)?;
+    if untrusted_header.time >= drifted_now {
+        return Err(VerificationError::HeaderFromTheFuture {
+            header_time: untrusted_header.time,
+            now,
+        });
+    }
+
+    if untrusted_header.height <= trusted_header.height {
+        return Err(VerificationError::NonIncreasingHeight {
+            got: untrusted_header.height,
+            expected: trusted_header.height,
+        });
+    }
+
+    if untrusted_header.time <= trusted_header.time {
+        return Err(VerificationError::NonMonotonicBftTime {
+            header_bft_time: untrusted_header.time,
+            trusted_header_bft_time: trusted_header.time,
+        });
+    }
+
+    // The validator set and the commit must be the ones of the untrusted header,
+    // since the tallies below only look at the validator set and at the votes
+    // rebuilt from the block ID of the commit
+    validator_set_matches(untrusted)?;
+
+    let header_hash = untrusted_header.hash();
+    let commit_hash = untrusted.signed_header.commit.block_id.hash;
+    if header_hash != commit_hash {
+        return Err(VerificationError::InvalidCommitValue {
+            header_hash,
+            commit_hash,
+        });
+    }
+
+    if untrusted_header.height == trusted_header.height.increment() {
+        // Sequential verification: the untrusted validators were announced by the trusted header
+        if untrusted_header.validators_hash != trusted_header.next_validators_hash {
+            return Err(VerificationError::InvalidNextValidatorSet {
+                header_validators_hash: untrusted_header.validators_hash,
+                next_validators_hash: trusted_header.next_validators_hash,
+            });
+        }
+    } else {
+        // Skipping verification: enough of the trusted validators must have signed
+        let tally = voting_power_in(
+            &untrusted.signed_header,
+            &trusted.validators,
+            signature_verifier,
+        )?;
+        if !tally.exceeds(options.trust_threshold) {
+            return Err(VerificationError::InsufficientSignersOverlap(tally));
+        }
+    }
+
+    // The commit must have one signature per untrusted validator, in the same order
+    untrusted
+        .signed_header
+        .commit
+        .validate(&untrusted.validators)
+        .map_err(|e| VerificationError::InvalidCommit(e.to_string()))?;
+
+    // More than 2/3 of the untrusted validators must have signed
+    let tally = voting_power_in(
+        &untrusted.signed_header,
+        &untrusted.validators,
+        signature_verifier,
+    )?;
+    if !tally.exceeds(TrustThresholdFraction::TWO_THIRDS) {
+        return Err(VerificationError::InsufficientCommitPower(tally));
+    }
+
+    Ok(())
+}
+
+/// Check that the given trusted light block has not expired yet,
+/// ie. that its header time plus the trusting period is after `now`.
+pub fn is_within_trust_period(
+    trusted: &LightBlock,
+    trusting_period: Duration,
+    now: Time,
+) -> Result<(), VerificationError> {
+    let expires_at = checked_add(trusted.signed_header.header.time, trusting_period)?;
+    if expires_at <= now {
+        return Err(VerificationError::NotWithinTrustPeriod { expires_at, now });
+    }
+
+    Ok(())
+}
+
+/// Add the given duration to the given time, failing if the result is out of range.
+fn checked_add(time: Time, duration: Duration) -> Result<Time, VerificationError> {
+    time.checked_add(duration)
+        .ok_or(VerificationError::TimeOverflow { time, duration })
+}
+
+/// Check that the validator set of the given light block is the one its header commits to.
+pub fn validator_set_matches(light_block: &LightBlock) -> Result<(), VerificationError> {
+    let header_validators_hash = light_block.signed_header.header.validators_hash;
+    let validators_hash = light_block.validators.hash();
+
+    if header_validators_hash != validators_hash {
+        return Err(VerificationError::InvalidValidatorSet {
+            header_validators_hash,
+            validators_hash,
+        });
+    }
+
+    Ok(())
+}
+
+/// Compute the voting power of the validators of the given set which signed
+/// the commit for the given header, counting each validator at most once.
+///
+/// The signature of every such validator is checked against its public key,
+/// over the sign bytes of its precommit on the chain of the header.
+pub fn voting_power_in(
+    signed_header: &SignedHeader,
+    validator_set: &ValidatorSet,
+    signature_verifier: &dyn SignatureVerifier,
+) -> Result<VotingPowerTally, VerificationError> {
+    let chain_id = &signed_header.header.chain_id;
+    let commit = &signed_header.commit;
+
+    let mut seen = HashSet::new();
+    let mut tallied = 0_u64;
+
+    for (index, sig) in commit.signatures.iter().enumerate() {
+        if !sig.is_commit() {
+            continue;
+        }
+
+        let vote = match commit.vote(index) {
+            Some(vote) => vote,
+            None => continue,
+        };
+
+        if !seen.insert(vote.validator_address) {
+            continue;
+        }
+
+        let validator = match validator_set.validator(vote.validator_address) {
+            Some(validator) => validator,
+            None => continue,
+        };
+
+        validator
+            .verify_signature(
+                signature_verifier,
+                &vote.to_signable_bytes(chain_id),
+                &vote.signature,
+            )
+            .map_err(|e| VerificationError::InvalidSignature {
+                validator_address: vote.validator_address,
+                reason: e.to_string(),
+            })?;
+
+        tallied += validator.power();
+    }
+
+    Ok(VotingPowerTally {
+        total: validator_set.total_voting_power().value(),
+        tallied,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tendermint_testgen::{light_block::LightBlock as TestgenLightBlock, Generator, Validator};
+
+    use crate::components::clock::{Clock, FixedClock};
+
+    const CHAIN_ID: &str = "test-chain";
+    const TRUSTING_PERIOD: Duration = Duration::from_secs(3600);
+
+    fn validators() -> Vec<Validator> {
+        ["a", "b", "c", "d"]
+            .iter()
+            .map(|id| Validator::new(id))
+            .collect()
+    }
+
+    /// A light block signed by all of its validators, at the given time in
+    /// seconds since the UNIX epoch
+    fn light_block(height: u64, time: u64) -> LightBlock {
+        TestgenLightBlock::new(validators())
+            .height(height)
+            .time(time)
+            .generate()
+            .unwrap()
+            .into()
+    }
+
+    fn at(secs: u64) -> Time {
+        Time::unix_epoch()
+            .checked_add(Duration::from_secs(secs))
+            .unwrap()
+    }
+
+    fn options() -> Options {
+        Options {
+            chain_id: CHAIN_ID.parse().unwrap(),
+            trust_threshold: TrustThresholdFraction::default(),
+            trusting_period: TRUSTING_PERIOD,
+            clock_drift: Duration::from_secs(10),
+        }
+    }
+
+    #[test]
+    fn trusted_block_expires_after_trusting_period() {
+        let clock = FixedClock::new(at(60));
+
+        let trusted = light_block(1, 0);
+        let untrusted = light_block(2, 30);
+
+        let verdict = ProdVerifier::default().verify(&untrusted, &trusted, &options(), clock.now());
+        assert_eq!(verdict, Verdict::Success);
+
+        clock.advance(TRUSTING_PERIOD);
+
+        let verdict = ProdVerifier::default().verify(&untrusted, &trusted, &options(), clock.now());
+        assert_eq!(
+            verdict,
+            Verdict::Invalid(VerificationError::NotWithinTrustPeriod {
+                expires_at: at(TRUSTING_PERIOD.as_secs()),
+                now: clock.now(),
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_header_from_the_future() {
+        let now = at(60);
+
+        let trusted = light_block(1, 0);
+        let untrusted = light_block(2, 70);
+
+        let verdict = ProdVerifier::default().verify(&untrusted, &trusted, &options(), now);
+        assert_eq!(
+            verdict,
+            Verdict::Invalid(VerificationError::HeaderFromTheFuture {
+                header_time: at(70),
+                now,
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_durations_out_of_time_range() {
+        let now = at(60);
+        let trusted = light_block(1, 0);
+        let untrusted = light_block(2, 30);
+        let forever = Duration::from_secs(u64::MAX);
+
+        let long_period = Options {
+            trusting_period: forever,
+            ..options()
+        };
+        let verdict = ProdVerifier::default().verify(&untrusted, &trusted, &long_period, now);
+        assert_eq!(
+            verdict,
+            Verdict::Invalid(VerificationError::TimeOverflow {
+                time: at(0),
+                duration: forever,
+            })
+        );
+
+        let long_drift = Options {
+            clock_drift: forever,
+            ..options()
+        };
+        let verdict = ProdVerifier::default().verify(&untrusted, &trusted, &long_drift, now);
+        assert_eq!(
+            verdict,
+            Verdict::Invalid(VerificationError::TimeOverflow {
+                time: now,
+                duration: forever,
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_header_from_another_chain() {
+        let trusted = light_block(1, 0);
+        let untrusted: LightBlock = TestgenLightBlock::new(validators())
+            .chain_id("other-chain")
+            .height(2)
+            .time(30)
+            .generate()
+            .unwrap()
+            .into();
+
+        let verdict = ProdVerifier::default().verify(&untrusted, &trusted, &options(), at(60));
+        assert_eq!(
+            verdict,
+            Verdict::Invalid(VerificationError::ChainIdMismatch {
+                got: "other-chain".parse().unwrap(),
+                expected: CHAIN_ID.parse().unwrap(),
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_validator_set_not_matching_header() {
+        let trusted = light_block(1, 0);
+        let mut untrusted = light_block(2, 30);
+        let forged: LightBlock = TestgenLightBlock::new(vec![Validator::new("z")])
+            .generate()
+            .unwrap()
+            .into();
+        untrusted.validators = forged.validators.clone();
+
+        let verdict = ProdVerifier::default().verify(&untrusted, &trusted, &options(), at(60));
+        assert_eq!(
+            verdict,
+            Verdict::Invalid(VerificationError::InvalidValidatorSet {
+                header_validators_hash: untrusted.signed_header.header.validators_hash,
+                validators_hash: forged.validators.hash(),
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_adjacent_header_with_unannounced_validators() {
+        let next_validators = vec![Validator::new("e")];
+        let trusted: LightBlock = TestgenLightBlock::new(validators())
+            .next_validators(next_validators)
+            .generate()
+            .unwrap()
+            .into();
+        let untrusted = light_block(2, 30);
+
+        let verdict = ProdVerifier::default().verify(&untrusted, &trusted, &options(), at(60));
+        assert_eq!(
+            verdict,
+            Verdict::Invalid(VerificationError::InvalidNextValidatorSet {
+                header_validators_hash: untrusted.signed_header.header.validators_hash,
+                next_validators_hash: trusted.signed_header.header.next_validators_hash,
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_commit_for_another_block() {
+        let trusted = light_block(1, 0);
+        let mut untrusted = light_block(3, 30);
+        untrusted.signed_header.header.app_hash = b"forged".to_vec();
+
+        let verdict = ProdVerifier::default().verify(&untrusted, &trusted, &options(), at(60));
+        assert_eq!(
+            verdict,
+            Verdict::Invalid(VerificationError::InvalidCommitValue {
+                header_hash: untrusted.signed_header.header.hash(),
+                commit_hash: light_block(3, 30).signed_header.header.hash(),
+            })
+        );
+    }
+
+    #[test]
+    fn skips_to_header_signed_by_trusted_validators() {
+        let trusted = light_block(1, 0);
+        let untrusted = light_block(5, 30);
+
+        let verdict = ProdVerifier::default().verify(&untrusted, &trusted, &options(), at(60));
+        assert_eq!(verdict, Verdict::Success);
+    }
+}
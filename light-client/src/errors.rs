@@ -0,0 +1,153 @@
+//! Toplevel errors raised by the light client.
+
+use std::time::Duration;
+
+use anomaly::{BoxError, Context};
+use thiserror::Error;
+
+use crate::{
+    components::{io::IoError, verifier::VerificationError},
+    types::{Hash, Height, PeerId, Time},
+};
+
+/// An error raised by the light client
+pub type Error = anomaly::Error<ErrorKind>;
+
+/// The various error kinds raised by the light client
+#[derive(Debug, Clone, Error, PartialEq)]
+pub enum ErrorKind {
+    /// A future did not complete within the allotted time
+    #[error("timed out after {0:?}")]
+    Timeout(Duration),
+
+    /// The async runtime could not be set up
+    #[error("runtime error: {0}")]
+    Runtime(String),
+
+    /// I/O error while fetching a light block
+    #[error("I/O error: {0}")]
+    Io(#[from] IoError),
+
+    /// The light store contains no trusted or verified light block to start from
+    #[error("no initial trusted state")]
+    NoInitialTrustedState,
+
+    /// The target height is lower than the height of the latest trusted state
+    #[error("target height ({target_height}) is lower than trusted state ({trusted_height})")]
+    TargetLowerThanTrustedState {
+        /// Target height
+        target_height: Height,
+        /// Latest trusted height
+        trusted_height: Height,
+    },
+
+    /// The target height is higher than the height of the lowest trusted state,
+    /// and thus cannot be reached by backward verification
+    #[error("target height ({target_height}) is higher than lowest trusted state ({trusted_height})")]
+    TargetHigherThanTrustedState {
+        /// Target height
+        target_height: Height,
+        /// Lowest trusted height
+        trusted_height: Height,
+    },
+
+    /// The hash of a header does not match the `last_block_id` of the header above it
+    #[error("header at height {height} has hash {got}, but the header above it links to {expected}")]
+    InvalidHashLink {
+        /// Height of the header
+        height: Height,
+        /// Hash of the previous block, as recorded by the header above it
+        expected: Hash,
+        /// Hash of the header
+        got: Hash,
+    },
+
+    /// Bisection reached the trusted state without finding a block it could verify
+    #[error("bisection for target at height {target_height} failed when reached trusted state at height {trusted_height}")]
+    BisectionFailed {
+        /// Target height
+        target_height: Height,
+        /// Latest trusted height
+        trusted_height: Height,
+    },
+
+    /// The latest trusted state has expired, and cannot be used to verify other blocks anymore
+    #[error("trusted state at height {trusted_height} is outside of the trusting period: expired at {expires_at:?}")]
+    TrustedStateOutsideTrustingPeriod {
+        /// Height of the latest trusted state
+        trusted_height: Height,
+        /// Time at which the trusting period of the trusted state expired
+        expires_at: Time,
+    },
+
+    /// The light block failed verification
+    #[error("invalid light block: {0}")]
+    InvalidLightBlock(#[source] VerificationError),
+
+    /// The header of the block fetched at the trusted height does not have the trusted hash
+    #[error("header hash {got} does not match trusted hash {expected}")]
+    TrustedHashMismatch {
+        /// The trusted hash
+        expected: Hash,
+        /// The hash of the fetched header
+        got: Hash,
+    },
+
+    /// The light store already contains another trusted block at the trusted height
+    #[error("light store already contains a conflicting trusted block at height {0}")]
+    ConflictingTrustedBlock(Height),
+
+    /// The trust threshold does not lie in [1/3, 1] or has a zero denominator
+    #[error("invalid trust threshold: {numerator}/{denominator}")]
+    InvalidTrustThreshold {
+        /// Numerator of the trust threshold
+        numerator: u64,
+        /// Denominator of the trust threshold
+        denominator: u64,
+    },
+
+    /// Some witnesses reported a header conflicting with the one of the primary
+    #[error("fork detected by peers: {0:?}")]
+    ForkDetected(Vec<PeerId>),
+
+    /// The channel to the supervisor was closed
+    #[error("channel disconnected")]
+    ChannelDisconnected,
+
+    /// No witnesses were configured for fork detection
+    #[error("no witnesses")]
+    NoWitnesses,
+
+    /// The light block is not stored with `Status::Verified`
+    #[error("light block at height {0:?} has not been verified")]
+    NotVerified(Height),
+
+    /// The conflicting and trusted light blocks are not at the same height
+    #[error("conflicting block at height {conflicting:?} does not match trusted block at height {trusted:?}")]
+    HeightMismatch {
+        /// Height of the trusted light block
+        trusted: Height,
+        /// Height of the conflicting light block
+        conflicting: Height,
+    },
+
+    /// The transaction proof does not match the light block at the given height
+    #[error("invalid transaction proof for block at height {0:?}")]
+    InvalidTxProof(Height),
+
+    /// There is no trusted light block below the given height in the light store
+    #[error("no trusted light block below height {0:?}")]
+    NoCommonBlock(Height),
+
+    /// The light store could not be read from or written to disk
+    #[error("light store error: {0}")]
+    Store(String),
+}
+
+impl ErrorKind {
+    /// Add additional context (i.e. include a source error and capture a backtrace).
+    /// You can convert the resulting `Context` into an `Error` by calling `.into()`.
+    pub fn context(self, source: impl Into<BoxError>) -> Context<Self> {
+        Context::new(self, Some(source.into()))
+    }
+}
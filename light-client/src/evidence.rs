@@ -0,0 +1,280 @@
+//! Construction of evidence of attacks against the light client.
+//!
+//! When fork detection finds a witness serving a header which conflicts with
+//! the one verified against the primary, the conflicting light block is turned
+//! into `LightClientAttackEvidence` to be reported to full nodes.
+
+use std::fmt;
+
+extern crate prusti_contracts;
+use prusti_contracts::*;
+
+use tendermint::evidence::{ConflictingBlock, LightClientAttackEvidence};
+use tendermint::validator;
+
+use crate::{
+    errors::{Error, ErrorKind},
+    store::LightStore,
+    types::{LightBlock, Status, ValidatorSet},
+};
+
+/// The kind of attack a conflicting light block is evidence of.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum AttackType {
+    /// The conflicting header could not have been derived from the state of
+    /// the common block: validators of the common validator set signed an
+    /// invalid header.
+    Lunatic,
+    /// Validators signed both the trusted and the conflicting header in the same round.
+    Equivocation,
+    /// Validators signed the trusted and the conflicting header in different rounds.
+    Amnesia,
+}
+
+impl fmt::Debug for AttackType {
+    #[trusted]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AttackType::Lunatic => f.write_str("Lunatic"),
+            AttackType::Equivocation => f.write_str("Equivocation"),
+            AttackType::Amnesia => f.write_str("Amnesia"),
+        }
+    }
+}
+
+/// Build evidence of an attack from a light block served by a witness
+/// which conflicts with the `trusted` light block at the same height.
+///
+/// The common block is the highest `Trusted` light block in the store
+/// below the height of the conflicting block.
+///
+/// Follows `newLightClientAttackEvidence` from the Go implementation:
+/// <https://github.com/tendermint/tendermint/blob/v0.34.0/light/detector.go>
+pub fn make_evidence(
+    conflicting: &LightBlock,
+    trusted: &LightBlock,
+    light_store: &dyn LightStore,
+) -> Result<(LightClientAttackEvidence, AttackType), Error> {
+    let height = conflicting.height();
+    if trusted.height() != height {
+        return Err(ErrorKind::HeightMismatch {
+            trusted: trusted.height(),
+            conflicting: height,
+        }
+        .into());
+    }
+
+    let common = light_store
+        .all(Status::Trusted)
+        .filter(|lb| lb.height() < height)
+        .max_by_key(|lb| lb.height())
+        .ok_or(ErrorKind::NoCommonBlock(height))?;
+
+    let mut evidence = LightClientAttackEvidence {
+        conflicting_block: ConflictingBlock {
+            signed_header: conflicting.signed_header.clone(),
+            validator_set: conflicting.validators.clone(),
+        },
+        common_height: common.height(),
+        byzantine_validators: vec![],
+        total_voting_power: common.validators.total_voting_power(),
+        timestamp: common.signed_header.header.time,
+    };
+
+    let attack_type = attack_type(&evidence, trusted);
+    if attack_type != AttackType::Lunatic {
+        // The trusted block at the same height is the last one both chains agree on
+        evidence.common_height = trusted.height();
+        evidence.total_voting_power = trusted.validators.total_voting_power();
+        evidence.timestamp = trusted.signed_header.header.time;
+    }
+
+    evidence.byzantine_validators =
+        byzantine_validators(&evidence, attack_type, &common.validators, trusted);
+
+    Ok((evidence, attack_type))
+}
+
+/// Classify the attack the given evidence is about
+fn attack_type(evidence: &LightClientAttackEvidence, trusted: &LightBlock) -> AttackType {
+    let conflicting_commit = &evidence.conflicting_block.signed_header.commit;
+
+    if evidence.conflicting_header_is_invalid(&trusted.signed_header.header) {
+        AttackType::Lunatic
+    } else if trusted.signed_header.commit.round == conflicting_commit.round {
+        AttackType::Equivocation
+    } else {
+        AttackType::Amnesia
+    }
+}
+
+/// Determine the validators responsible for the attack, sorted by voting power.
+///
+/// - For a lunatic attack, these are the validators of the common validator set
+///   which signed the conflicting header.
+/// - For an equivocation attack, these are the validators which signed both headers.
+///   Since the validator hashes match, both commits are aligned with the same validator set.
+/// - For an amnesia attack, the malicious validators cannot be told apart from the
+///   honest ones, so none are returned.
+fn byzantine_validators(
+    evidence: &LightClientAttackEvidence,
+    attack_type: AttackType,
+    common_validators: &ValidatorSet,
+    trusted: &LightBlock,
+) -> Vec<validator::Info> {
+    let conflicting_block = &evidence.conflicting_block;
+    let conflicting_sigs = &conflicting_block.signed_header.commit.signatures;
+
+    let mut validators: Vec<validator::Info> = match attack_type {
+        AttackType::Lunatic => conflicting_sigs
+            .iter()
+            .filter(|sig| sig.is_commit())
+            .filter_map(|sig| sig.validator_address())
+            .filter_map(|address| common_validators.validator(address))
+            .collect(),
+        AttackType::Equivocation => conflicting_sigs
+            .iter()
+            .zip(trusted.signed_header.commit.signatures.iter())
+            .filter(|(sig_a, sig_b)| sig_a.is_commit() && sig_b.is_commit())
+            .filter_map(|(sig_a, _)| sig_a.validator_address())
+            .filter_map(|address| conflicting_block.validator_set.validator(address))
+            .collect(),
+        AttackType::Amnesia => vec![],
+    };
+
+    validators.sort_by_key(|v| (std::cmp::Reverse(v.power), v.address));
+    validators
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tendermint::block::Round;
+    use tendermint::{account, vote};
+    use tendermint_testgen::{
+        light_block::LightBlock as TestgenLightBlock, Generator, LightChain, Validator,
+    };
+
+    use crate::store::memory::MemoryStore;
+    use crate::types::Height;
+
+    /// Validators with distinct voting powers, so that their order is checked
+    fn validators() -> Vec<Validator> {
+        vec![
+            Validator::new("a").voting_power(1),
+            Validator::new("b").voting_power(4),
+            Validator::new("c").voting_power(3),
+            Validator::new("d").voting_power(2),
+        ]
+    }
+
+    /// Descriptions of the blocks of a chain of three blocks
+    fn descriptions() -> Vec<TestgenLightBlock> {
+        LightChain::new(validators(), 3).light_blocks().unwrap()
+    }
+
+    fn generate(description: &TestgenLightBlock) -> LightBlock {
+        description.generate().unwrap().into()
+    }
+
+    /// A light store trusting the first two blocks of the chain
+    fn light_store() -> MemoryStore {
+        let mut light_store = MemoryStore::new();
+        for description in &descriptions()[..2] {
+            light_store.insert(generate(description), Status::Trusted);
+        }
+        light_store
+    }
+
+    fn addresses(validators: &[validator::Info]) -> Vec<account::Id> {
+        validators.iter().map(|v| v.address).collect()
+    }
+
+    fn address(id: &str) -> account::Id {
+        Validator::new(id).address()
+    }
+
+    #[test]
+    fn lunatic_attack() {
+        let trusted = generate(&descriptions()[2]);
+        // Signed by some of the trusted validators, but with another validator set
+        let conflicting = generate(
+            &TestgenLightBlock::new(vec![
+                Validator::new("a"),
+                Validator::new("c"),
+                Validator::new("z"),
+            ])
+            .height(3)
+            .time(3),
+        );
+
+        let light_store = light_store();
+        let (evidence, attack_type) = make_evidence(&conflicting, &trusted, &light_store).unwrap();
+        assert_eq!(attack_type, AttackType::Lunatic);
+
+        let common = light_store
+            .get(Height::from(2_u32), Status::Trusted)
+            .unwrap();
+        assert_eq!(evidence.common_height, common.height());
+        assert_eq!(evidence.total_voting_power, vote::Power::from(10_u32));
+        assert_eq!(evidence.timestamp, common.signed_header.header.time);
+        assert_eq!(
+            evidence.conflicting_block.signed_header,
+            conflicting.signed_header
+        );
+        assert_eq!(
+            addresses(&evidence.byzantine_validators),
+            vec![address("c"), address("a")]
+        );
+    }
+
+    #[test]
+    fn equivocation_attack() {
+        let trusted = generate(&descriptions()[2]);
+        // Same validators and round, but another time
+        let conflicting = generate(&descriptions()[2].clone().time(10).signers(&["a", "b", "d"]));
+
+        let (evidence, attack_type) =
+            make_evidence(&conflicting, &trusted, &light_store()).unwrap();
+        assert_eq!(attack_type, AttackType::Equivocation);
+        assert_eq!(evidence.common_height, trusted.height());
+        assert_eq!(evidence.timestamp, trusted.signed_header.header.time);
+        assert_eq!(
+            addresses(&evidence.byzantine_validators),
+            vec![address("b"), address("d"), address("a")]
+        );
+    }
+
+    #[test]
+    fn amnesia_attack() {
+        let trusted = generate(&descriptions()[2]);
+        let mut conflicting = generate(&descriptions()[2].clone().time(10));
+        conflicting.signed_header.commit.round = Round::from(1_u8);
+
+        let (evidence, attack_type) =
+            make_evidence(&conflicting, &trusted, &light_store()).unwrap();
+        assert_eq!(attack_type, AttackType::Amnesia);
+        assert_eq!(evidence.common_height, trusted.height());
+        assert!(evidence.byzantine_validators.is_empty());
+    }
+
+    #[test]
+    fn requires_blocks_at_same_height_above_a_trusted_block() {
+        let trusted = generate(&descriptions()[2]);
+        let conflicting = generate(&descriptions()[1].clone().time(10));
+
+        let error = make_evidence(&conflicting, &trusted, &light_store()).unwrap_err();
+        assert_eq!(
+            error.kind(),
+            &ErrorKind::HeightMismatch {
+                trusted: trusted.height(),
+                conflicting: conflicting.height(),
+            }
+        );
+
+        let conflicting = generate(&descriptions()[2].clone().time(10));
+        let error = make_evidence(&conflicting, &trusted, &MemoryStore::new()).unwrap_err();
+        assert_eq!(error.kind(), &ErrorKind::NoCommonBlock(trusted.height()));
+    }
+}
@@ -0,0 +1,421 @@
+//! Fork detection against witness full nodes.
+//!
+//! A light block which has been verified against the primary is only trusted
+//! once the same-height headers served by the witnesses agree with it. A
+//! witness serving another header only reports a fork if its header can be
+//! verified from the same trusted state as the one of the primary.
+
+use std::fmt;
+
+extern crate prusti_contracts;
+use prusti_contracts::*;
+
+use crate::{
+    components::io::{AtHeight, IoError},
+    errors::{Error, ErrorKind},
+    light_client::LightClient,
+    store::{memory::MemoryStore, LightStore},
+    types::{LightBlock, PeerId, Status},
+};
+
+/// Result of fork detection
+#[derive(Clone, PartialEq)]
+pub enum ForkDetection {
+    /// One or more witnesses disagree with the primary, or could not be checked
+    Detected(Vec<Fork>),
+    /// All witnesses agree with the primary
+    NotDetected,
+}
+
+impl fmt::Debug for ForkDetection {
+    #[trusted]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ForkDetection::Detected(forks) => f.debug_tuple("Detected").field(forks).finish(),
+            ForkDetection::NotDetected => f.write_str("NotDetected"),
+        }
+    }
+}
+
+/// Outcome of the comparison of a verified light block with a single witness
+#[derive(Clone, PartialEq)]
+pub enum Fork {
+    /// The witness has a conflicting header at the same height, which
+    /// verifies against the trusted state
+    Forked {
+        /// The witness which reported the conflicting header
+        peer_id: PeerId,
+        /// The light block verified against the primary
+        primary: Box<LightBlock>,
+        /// The conflicting light block returned by the witness
+        witness: Box<LightBlock>,
+    },
+    /// The witness returned an invalid response, or a conflicting header
+    /// which does not verify against the trusted state
+    Faulty(PeerId, ErrorKind),
+    /// The witness did not respond in time
+    Timeout(PeerId, IoError),
+}
+
+impl Fork {
+    /// The witness this outcome relates to
+    pub fn peer_id(&self) -> &PeerId {
+        match self {
+            Fork::Forked { peer_id, .. } => peer_id,
+            Fork::Faulty(peer_id, _) => peer_id,
+            Fork::Timeout(peer_id, _) => peer_id,
+        }
+    }
+
+    /// Whether the witness actually reported a conflicting header
+    pub fn is_forked(&self) -> bool {
+        matches!(self, Fork::Forked { .. })
+    }
+}
+
+impl fmt::Debug for Fork {
+    #[trusted]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Fork::Forked { peer_id, .. } => f.debug_tuple("Forked").field(peer_id).finish(),
+            Fork::Faulty(peer_id, e) => f.debug_tuple("Faulty").field(peer_id).field(e).finish(),
+            Fork::Timeout(peer_id, e) => f.debug_tuple("Timeout").field(peer_id).field(e).finish(),
+        }
+    }
+}
+
+/// Interface for a fork detector
+pub trait ForkDetector: Send + Sync {
+    /// Compare the given verified light block with the headers at the same height
+    /// served by the given witnesses, the conflicting ones of which are verified
+    /// against the trusted light block the verified one was verified from.
+    fn detect_forks(
+        &self,
+        verified_block: &LightBlock,
+        trusted_block: &LightBlock,
+        witnesses: &[&LightClient],
+    ) -> Result<ForkDetection, Error>;
+}
+
+/// A production-ready fork detector which compares the headers
+/// served by each witness with the one verified against the primary.
+#[derive(Clone, Copy, Default)]
+pub struct ProdForkDetector;
+
+impl ProdForkDetector {
+    /// Constructs a new fork detector
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn check_witness(
+        &self,
+        verified_block: &LightBlock,
+        trusted_block: &LightBlock,
+        witness: &LightClient,
+    ) -> Option<Fork> {
+        let height = verified_block.height();
+        let peer_id = witness.peer_id().clone();
+
+        match witness.provider.fetch_light_block(AtHeight::At(height)) {
+            Ok(witness_block) if witness_block.height() != height => Some(Fork::Faulty(
+                peer_id,
+                ErrorKind::Io(IoError::InvalidHeight(format!(
+                    "requested {:?}, got {:?}",
+                    height,
+                    witness_block.height()
+                ))),
+            )),
+            // Commits may legitimately differ between nodes (eg. a different subset
+            // of signatures), hence only the headers are compared.
+            Ok(witness_block)
+                if witness_block.signed_header.header != verified_block.signed_header.header =>
+            {
+                Some(self.verify_conflicting(verified_block, trusted_block, witness_block, witness))
+            }
+            Ok(_) => None,
+            Err(e) if e.is_timeout() => Some(Fork::Timeout(peer_id, e)),
+            Err(e) => Some(Fork::Faulty(peer_id, ErrorKind::Io(e))),
+        }
+    }
+
+    /// Verify the conflicting block of a witness from the trusted block, in a
+    /// scratch light store, fetching any intermediate block from the witness.
+    /// Only a conflicting block which verifies is evidence of a fork, any other
+    /// one only shows that the witness is faulty.
+    fn verify_conflicting(
+        &self,
+        verified_block: &LightBlock,
+        trusted_block: &LightBlock,
+        witness_block: LightBlock,
+        witness: &LightClient,
+    ) -> Fork {
+        let peer_id = witness.peer_id().clone();
+
+        let mut light_store = MemoryStore::new();
+        light_store.insert(trusted_block.clone(), Status::Trusted);
+        light_store.insert(witness_block.clone(), Status::Unverified);
+
+        match witness.verify_to_target(witness_block.height(), &mut light_store) {
+            Ok(_) => Fork::Forked {
+                peer_id,
+                primary: Box::new(verified_block.clone()),
+                witness: Box::new(witness_block),
+            },
+            Err(e) => Fork::Faulty(peer_id, e.kind().clone()),
+        }
+    }
+}
+
+impl fmt::Debug for ProdForkDetector {
+    #[trusted]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProdForkDetector").finish()
+    }
+}
+
+impl ForkDetector for ProdForkDetector {
+    fn detect_forks(
+        &self,
+        verified_block: &LightBlock,
+        trusted_block: &LightBlock,
+        witnesses: &[&LightClient],
+    ) -> Result<ForkDetection, Error> {
+        if witnesses.is_empty() {
+            return Err(ErrorKind::NoWitnesses.into());
+        }
+
+        let forks: Vec<Fork> = witnesses
+            .iter()
+            .filter_map(|witness| self.check_witness(verified_block, trusted_block, witness))
+            .collect();
+
+        if forks.is_empty() {
+            Ok(ForkDetection::NotDetected)
+        } else {
+            Ok(ForkDetection::Detected(forks))
+        }
+    }
+}
+
+/// Run fork detection for the given light block, which must be stored
+/// with `Status::Verified` above a `Status::Trusted` one, and promote it
+/// to `Status::Trusted` if all witnesses agree with it.
+///
+/// Conflicting headers of the witnesses are verified against the highest
+/// trusted light block below the given one.
+///
+/// If a fork is detected, or a witness is faulty or times out, the block is
+/// left as `Verified` and the detection outcome is returned to the caller.
+pub fn detect_and_promote(
+    detector: &dyn ForkDetector,
+    witnesses: &[&LightClient],
+    light_block: &LightBlock,
+    light_store: &mut dyn LightStore,
+) -> Result<ForkDetection, Error> {
+    let height = light_block.height();
+    if light_store.get(height, Status::Verified).as_ref() != Some(light_block) {
+        return Err(ErrorKind::NotVerified(height).into());
+    }
+
+    let trusted_block = light_store
+        .all(Status::Trusted)
+        .filter(|lb| lb.height() < height)
+        .max_by_key(|lb| lb.height())
+        .ok_or(ErrorKind::NoCommonBlock(height))?;
+
+    let outcome = detector.detect_forks(light_block, &trusted_block, witnesses)?;
+    if outcome == ForkDetection::NotDetected {
+        light_store.update(light_block, Status::Trusted);
+    }
+
+    Ok(outcome)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::time::Duration;
+
+    use crate::store::memory::MemoryStore;
+    use crate::types::{Height, Time};
+    use crate::utils::testing;
+
+    /// A chain of four blocks signed by the given validators, the last of
+    /// which is moved forward by `last_block_delay` seconds
+    fn chain(validators: &[&str], last_block_delay: u64) -> Vec<LightBlock> {
+        testing::chain(validators, 4, last_block_delay)
+    }
+
+    fn witness(blocks: Vec<LightBlock>) -> LightClient {
+        let now = Time::unix_epoch()
+            .checked_add(Duration::from_secs(60))
+            .unwrap();
+        testing::light_client("witness", blocks, now)
+    }
+
+    /// A light store with the first block of the given chain trusted, and
+    /// its last one verified
+    fn light_store(blocks: &[LightBlock]) -> MemoryStore {
+        let mut light_store = MemoryStore::new();
+        light_store.insert(blocks[0].clone(), Status::Trusted);
+        light_store.insert(blocks[3].clone(), Status::Verified);
+        light_store
+    }
+
+    #[test]
+    fn agreeing_witness_promotes_block() {
+        let primary = chain(&["a", "b", "c"], 0);
+        let mut light_store = light_store(&primary);
+        let witness = witness(primary.clone());
+
+        let detection = detect_and_promote(
+            &ProdForkDetector::new(),
+            &[&witness],
+            &primary[3],
+            &mut light_store,
+        )
+        .unwrap();
+
+        assert_eq!(detection, ForkDetection::NotDetected);
+        assert_eq!(
+            light_store.highest(Status::Trusted),
+            Some(primary[3].clone())
+        );
+        assert_eq!(light_store.highest(Status::Verified), None);
+    }
+
+    #[test]
+    fn verifiable_conflicting_block_is_a_fork() {
+        let primary = chain(&["a", "b", "c"], 0);
+        let conflicting = chain(&["a", "b", "c"], 1);
+        let mut light_store = light_store(&primary);
+        let witness = witness(conflicting.clone());
+
+        let detection = detect_and_promote(
+            &ProdForkDetector::new(),
+            &[&witness],
+            &primary[3],
+            &mut light_store,
+        )
+        .unwrap();
+
+        assert_eq!(
+            detection,
+            ForkDetection::Detected(vec![Fork::Forked {
+                peer_id: PeerId::new("witness"),
+                primary: Box::new(primary[3].clone()),
+                witness: Box::new(conflicting[3].clone()),
+            }])
+        );
+        assert_eq!(
+            light_store.highest(Status::Trusted),
+            Some(primary[0].clone())
+        );
+        assert_eq!(
+            light_store.highest(Status::Verified),
+            Some(primary[3].clone())
+        );
+    }
+
+    #[test]
+    fn unverifiable_conflicting_block_is_not_a_fork() {
+        let primary = chain(&["a", "b", "c"], 0);
+        let mut light_store = light_store(&primary);
+        let witness = witness(chain(&["d", "e", "f"], 0));
+
+        let detection = detect_and_promote(
+            &ProdForkDetector::new(),
+            &[&witness],
+            &primary[3],
+            &mut light_store,
+        )
+        .unwrap();
+
+        match detection {
+            ForkDetection::Detected(forks) => match forks.as_slice() {
+                [Fork::Faulty(peer_id, _)] => assert_eq!(peer_id, &PeerId::new("witness")),
+                forks => panic!("expected a faulty witness, got {:?}", forks),
+            },
+            ForkDetection::NotDetected => panic!("expected a faulty witness"),
+        }
+        assert_eq!(
+            light_store.highest(Status::Verified),
+            Some(primary[3].clone())
+        );
+    }
+
+    #[test]
+    fn witness_missing_the_block_is_faulty() {
+        let primary = chain(&["a", "b", "c"], 0);
+        let detection = ProdForkDetector::new()
+            .detect_forks(&primary[3], &primary[0], &[&witness(vec![])])
+            .unwrap();
+
+        assert_eq!(
+            detection,
+            ForkDetection::Detected(vec![Fork::Faulty(
+                PeerId::new("witness"),
+                ErrorKind::Io(IoError::NotFound(AtHeight::At(primary[3].height()))),
+            )])
+        );
+    }
+
+    #[test]
+    fn unresponsive_witness_times_out() {
+        let primary = chain(&["a", "b", "c"], 0);
+        let now = Time::unix_epoch()
+            .checked_add(Duration::from_secs(60))
+            .unwrap();
+        let witness = testing::unresponsive_light_client("witness", now);
+
+        let detection = ProdForkDetector::new()
+            .detect_forks(&primary[3], &primary[0], &[&witness])
+            .unwrap();
+
+        assert_eq!(
+            detection,
+            ForkDetection::Detected(vec![Fork::Timeout(
+                PeerId::new("witness"),
+                IoError::Timeout(PeerId::new("witness"), Duration::from_secs(5)),
+            )])
+        );
+    }
+
+    #[test]
+    fn requires_witnesses() {
+        let primary = chain(&["a", "b", "c"], 0);
+        let error = ProdForkDetector::new()
+            .detect_forks(&primary[3], &primary[0], &[])
+            .unwrap_err();
+
+        assert_eq!(error.kind(), &ErrorKind::NoWitnesses);
+    }
+
+    #[test]
+    fn requires_verified_block_above_trusted_one() {
+        let primary = chain(&["a", "b", "c"], 0);
+        let witness = witness(primary.clone());
+
+        let mut light_store = MemoryStore::new();
+        let error = detect_and_promote(
+            &ProdForkDetector::new(),
+            &[&witness],
+            &primary[3],
+            &mut light_store,
+        )
+        .unwrap_err();
+        assert_eq!(error.kind(), &ErrorKind::NotVerified(Height::from(4_u32)));
+
+        light_store.insert(primary[3].clone(), Status::Verified);
+        let error = detect_and_promote(
+            &ProdForkDetector::new(),
+            &[&witness],
+            &primary[3],
+            &mut light_store,
+        )
+        .unwrap_err();
+        assert_eq!(error.kind(), &ErrorKind::NoCommonBlock(Height::from(4_u32)));
+    }
+}
@@ -0,0 +1,87 @@
+//! Proofs of inclusion of transactions in verified light blocks.
+//!
+//! A transaction proof is only as good as the data hash it is checked
+//! against, hence the header is taken from a light block which has been
+//! verified (or is trusted) in the light store.
+
+use tendermint::abci::transaction;
+
+use crate::{
+    errors::{Error, ErrorKind},
+    store::LightStore,
+    types::Height,
+};
+
+/// Check that the given proof proves the inclusion of its transaction in the
+/// block at the given height, against the data hash of the trusted or verified
+/// light block at that height in the light store.
+pub fn verify_tx_inclusion(
+    proof: &transaction::Proof,
+    height: Height,
+    light_store: &dyn LightStore,
+) -> Result<(), Error> {
+    let light_block = light_store
+        .get_trusted_or_verified(height)
+        .ok_or(ErrorKind::NotVerified(height))?;
+
+    proof
+        .validate(&light_block.signed_header.header.data_hash)
+        .map_err(|e| ErrorKind::InvalidTxProof(height).context(e).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tendermint::abci::Transaction;
+    use tendermint::Hash;
+    use tendermint_testgen::{light_block::LightBlock as TestgenLightBlock, Generator, Validator};
+
+    use crate::{
+        store::memory::MemoryStore,
+        types::{LightBlock, Status},
+    };
+
+    /// A light block whose header commits to the given data hash
+    fn light_block(height: u64, data_hash: Hash) -> LightBlock {
+        let mut light_block: LightBlock = TestgenLightBlock::new(vec![Validator::new("a")])
+            .height(height)
+            .generate()
+            .unwrap()
+            .into();
+        light_block.signed_header.header.data_hash = data_hash;
+        light_block
+    }
+
+    #[test]
+    fn verifies_proofs_against_verified_blocks_only() {
+        let data = transaction::Data::new(vec![
+            Transaction::from(b"tx0".to_vec()),
+            Transaction::from(b"tx1".to_vec()),
+        ]);
+        let proof = data.proof(1).unwrap();
+
+        let mut light_store = MemoryStore::new();
+        light_store.insert(light_block(1, data.hash()), Status::Unverified);
+
+        let height = Height::from(1_u32);
+        assert_eq!(
+            verify_tx_inclusion(&proof, height, &light_store)
+                .unwrap_err()
+                .kind(),
+            &ErrorKind::NotVerified(height)
+        );
+
+        light_store.update(&light_block(1, data.hash()), Status::Verified);
+        assert!(verify_tx_inclusion(&proof, height, &light_store).is_ok());
+
+        light_store.insert(light_block(2, Hash::None), Status::Trusted);
+        let height = Height::from(2_u32);
+        assert_eq!(
+            verify_tx_inclusion(&proof, height, &light_store)
+                .unwrap_err()
+                .kind(),
+            &ErrorKind::InvalidTxProof(height)
+        );
+    }
+}
@@ -1,4 +1,11 @@
+pub mod components;
+pub mod errors;
+pub mod evidence;
+pub mod fork_detector;
+pub mod inclusion;
+pub mod light_client;
 pub mod store;
+pub mod supervisor;
 pub mod types;
 
 pub(crate) mod utils;
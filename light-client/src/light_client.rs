@@ -0,0 +1,608 @@
+//! Light client implementation as per the [Core Verification specification][1].
+//!
+//! [1]: https://github.com/informalsystems/tendermint-rs/blob/master/docs/spec/lightclient/verification/verification.md
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::time::Duration;
+
+extern crate prusti_contracts;
+use prusti_contracts::*;
+
+use crate::{
+    components::{
+        clock::Clock,
+        io::{AtHeight, IoError, Provider},
+        verifier::{
+            is_within_trust_period, validator_set_matches, Verdict, VerificationError, Verifier,
+        },
+    },
+    errors::{Error, ErrorKind},
+    store::LightStore,
+    types::{ChainId, Height, LightBlock, PeerId, Status, TrustOptions, TrustThresholdFraction},
+};
+
+/// Verification parameters
+#[derive(Clone, PartialEq, Eq)]
+pub struct Options {
+    /// Identifier of the chain to verify blocks of. Blocks of any other
+    /// chain are rejected.
+    pub chain_id: ChainId,
+
+    /// Defines what fraction of the total voting power of a known
+    /// and trusted validator set is sufficient for a commit to be
+    /// accepted going forward.
+    pub trust_threshold: TrustThresholdFraction,
+
+    /// How long a validator set is trusted for (must be shorter than the chain's
+    /// unbonding period)
+    pub trusting_period: Duration,
+
+    /// Correction parameter dealing with only approximately synchronized clocks.
+    /// The higher this value, the further in the future a header may be dated.
+    pub clock_drift: Duration,
+}
+
+impl Options {
+    /// Default bound on the clock drift between the light client and the chain,
+    /// matching the one of the Go implementation.
+    pub const DEFAULT_CLOCK_DRIFT: Duration = Duration::from_secs(10);
+}
+
+impl fmt::Debug for Options {
+    #[trusted]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Options")
+            .field("chain_id", &self.chain_id)
+            .field("trust_threshold", &self.trust_threshold)
+            .field("trusting_period", &self.trusting_period)
+            .field("clock_drift", &self.clock_drift)
+            .finish()
+    }
+}
+
+impl From<&TrustOptions> for Options {
+    fn from(trust_options: &TrustOptions) -> Self {
+        Self {
+            chain_id: trust_options.chain_id.clone(),
+            trust_threshold: trust_options.trust_threshold,
+            trusting_period: trust_options.period,
+            clock_drift: Self::DEFAULT_CLOCK_DRIFT,
+        }
+    }
+}
+
+/// The light client verifies light blocks fetched from a single provider,
+/// bisecting between the latest trusted block and the target height.
+pub struct LightClient {
+    /// The provider light blocks are fetched from
+    pub provider: Provider,
+    /// Verification parameters
+    pub options: Options,
+    verifier: Box<dyn Verifier>,
+    clock: Box<dyn Clock>,
+}
+
+impl fmt::Debug for LightClient {
+    #[trusted]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LightClient")
+            .field("peer_id", &self.provider.peer_id)
+            .finish()
+    }
+}
+
+impl LightClient {
+    /// Constructs a new light client
+    pub fn new(
+        provider: Provider,
+        options: Options,
+        verifier: Box<dyn Verifier>,
+        clock: Box<dyn Clock>,
+    ) -> Self {
+        Self {
+            provider,
+            options,
+            verifier,
+            clock,
+        }
+    }
+
+    /// The identifier of the provider of this light client
+    pub fn peer_id(&self) -> &PeerId {
+        &self.provider.peer_id
+    }
+
+    /// Initialize the light store from the given trust options, obtained out of band.
+    ///
+    /// The block at the trusted height is fetched from the provider, and is inserted
+    /// into the light store as `Status::Trusted` once its chain identifier and header
+    /// hash have been checked against the trust options, its validator set against
+    /// its header, and its time against the trusting period of the trust options.
+    /// Initialization fails if the light store already contains another trusted
+    /// block at that height.
+    pub fn initialize(
+        &self,
+        trust_options: &TrustOptions,
+        light_store: &mut dyn LightStore,
+    ) -> Result<LightBlock, Error> {
+        let light_block = self.fetch_light_block_at(trust_options.height)?;
+
+        let chain_id = &light_block.signed_header.header.chain_id;
+        if chain_id != &trust_options.chain_id {
+            return Err(
+                ErrorKind::InvalidLightBlock(VerificationError::ChainIdMismatch {
+                    got: chain_id.clone(),
+                    expected: trust_options.chain_id.clone(),
+                })
+                .into(),
+            );
+        }
+
+        let header_hash = light_block.signed_header.header.hash();
+        if header_hash != trust_options.hash {
+            return Err(ErrorKind::TrustedHashMismatch {
+                expected: trust_options.hash,
+                got: header_hash,
+            }
+            .into());
+        }
+
+        validator_set_matches(&light_block).map_err(ErrorKind::InvalidLightBlock)?;
+        is_within_trust_period(&light_block, trust_options.period, self.clock.now())
+            .map_err(ErrorKind::InvalidLightBlock)?;
+
+        if let Some(trusted_block) = light_store.get(trust_options.height, Status::Trusted) {
+            if trusted_block.signed_header.header.hash() != header_hash {
+                return Err(ErrorKind::ConflictingTrustedBlock(trust_options.height).into());
+            }
+        }
+
+        light_store.insert(light_block.clone(), Status::Trusted);
+        Ok(light_block)
+    }
+
+    /// Attempt to update the light client to the highest block of the provider.
+    pub fn verify_to_highest(&self, light_store: &mut dyn LightStore) -> Result<LightBlock, Error> {
+        let target_block = self
+            .provider
+            .fetch_light_block(AtHeight::Highest)
+            .map_err(ErrorKind::Io)?;
+
+        self.verify_to_target(target_block.height(), light_store)
+    }
+
+    /// Update the light client to a block of the provider at the given height.
+    ///
+    /// Starting from the highest trusted or verified block in the light store,
+    /// which must still be within its trusting period as per the light client's
+    /// clock, verify the block at the target height, bisecting whenever there is not
+    /// enough trust to skip directly to it. Every block which passes
+    /// verification is stored as `Status::Verified`, blocks which cannot be
+    /// trusted yet as `Status::Unverified`, and invalid ones as `Status::Failed`.
+    ///
+    /// Targets below the lowest trusted block are verified backwards,
+    /// see `verify_backward`.
+    pub fn verify_to_target(
+        &self,
+        target_height: Height,
+        light_store: &mut dyn LightStore,
+    ) -> Result<LightBlock, Error> {
+        if let Some(lowest_trusted) = light_store.lowest(Status::Trusted) {
+            if target_height < lowest_trusted.height() {
+                return self.verify_backward(target_height, light_store);
+            }
+        }
+
+        let mut current_height = target_height;
+
+        loop {
+            let trusted_block = light_store
+                .highest_trusted_or_verified()
+                .ok_or(ErrorKind::NoInitialTrustedState)?;
+
+            if target_height < trusted_block.height() {
+                return Err(ErrorKind::TargetLowerThanTrustedState {
+                    target_height,
+                    trusted_height: trusted_block.height(),
+                }
+                .into());
+            }
+
+            if target_height == trusted_block.height() {
+                return Ok(trusted_block);
+            }
+
+            let now = self.clock.now();
+            if let Err(VerificationError::NotWithinTrustPeriod { expires_at, .. }) =
+                is_within_trust_period(&trusted_block, self.options.trusting_period, now)
+            {
+                return Err(ErrorKind::TrustedStateOutsideTrustingPeriod {
+                    trusted_height: trusted_block.height(),
+                    expires_at,
+                }
+                .into());
+            }
+
+            let current_block = self.get_or_fetch_block(current_height, light_store)?;
+
+            match self
+                .verifier
+                .verify(&current_block, &trusted_block, &self.options, now)
+            {
+                Verdict::Success => {
+                    light_store.update(&current_block, Status::Verified);
+                    current_height = target_height;
+                }
+                Verdict::NotEnoughTrust(_) => {
+                    light_store.update(&current_block, Status::Unverified);
+                    current_height = midpoint(trusted_block.height(), current_height)?;
+                }
+                Verdict::Invalid(e) => {
+                    light_store.update(&current_block, Status::Failed);
+                    return Err(ErrorKind::InvalidLightBlock(e).into());
+                }
+            }
+        }
+    }
+
+    /// Verify the block of the provider at the given height, which must be lower
+    /// than the lowest trusted block of the light store, by following the hash
+    /// links of the headers down from that trusted block.
+    ///
+    /// Since the hash of every header is committed to by the `last_block_id` of
+    /// the header above it, neither the trusting period nor the signatures need
+    /// to be checked. The validator set served with every block is however only
+    /// bound to it by its header, and is therefore checked against the header
+    /// before the block is stored as `Status::Trusted`. A block which does not
+    /// match the hash link above it, or whose validator set does not match its
+    /// header, is stored as `Status::Failed`.
+    pub fn verify_backward(
+        &self,
+        target_height: Height,
+        light_store: &mut dyn LightStore,
+    ) -> Result<LightBlock, Error> {
+        let mut trusted_block = light_store
+            .lowest(Status::Trusted)
+            .ok_or(ErrorKind::NoInitialTrustedState)?;
+
+        if target_height > trusted_block.height() {
+            return Err(ErrorKind::TargetHigherThanTrustedState {
+                target_height,
+                trusted_height: trusted_block.height(),
+            }
+            .into());
+        }
+
+        while trusted_block.height() > target_height {
+            let height = Height::try_from(trusted_block.height().value() - 1)
+                .expect("height below a valid height is valid");
+            let current_block = self.get_or_fetch_block(height, light_store)?;
+
+            let expected = trusted_block
+                .signed_header
+                .header
+                .last_block_id
+                .map(|id| id.hash)
+                .unwrap_or_default();
+            let got = current_block.signed_header.header.hash();
+
+            if got != expected {
+                light_store.update(&current_block, Status::Failed);
+                return Err(ErrorKind::InvalidHashLink {
+                    height,
+                    expected,
+                    got,
+                }
+                .into());
+            }
+
+            if let Err(e) = validator_set_matches(&current_block) {
+                light_store.update(&current_block, Status::Failed);
+                return Err(ErrorKind::InvalidLightBlock(e).into());
+            }
+
+            light_store.update(&current_block, Status::Trusted);
+            trusted_block = current_block;
+        }
+
+        Ok(trusted_block)
+    }
+
+    /// Look in the light store for a block at the given height which has not
+    /// failed verification, or fetch it from the provider otherwise.
+    fn get_or_fetch_block(
+        &self,
+        height: Height,
+        light_store: &mut dyn LightStore,
+    ) -> Result<LightBlock, Error> {
+        if let Some((light_block, _)) = light_store.get_non_failed(height) {
+            return Ok(light_block);
+        }
+
+        let light_block = self.fetch_light_block_at(height)?;
+
+        light_store.insert(light_block.clone(), Status::Unverified);
+        Ok(light_block)
+    }
+
+    /// Fetch the light block at the given height from the provider, which
+    /// must not answer with a block at another height.
+    fn fetch_light_block_at(&self, height: Height) -> Result<LightBlock, Error> {
+        let light_block = self
+            .provider
+            .fetch_light_block(AtHeight::At(height))
+            .map_err(ErrorKind::Io)?;
+
+        if light_block.height() != height {
+            return Err(ErrorKind::Io(IoError::InvalidHeight(format!(
+                "requested {}, got {}",
+                height,
+                light_block.height()
+            )))
+            .into());
+        }
+
+        Ok(light_block)
+    }
+}
+
+/// Height halfway between the trusted height and the current one, used to bisect.
+fn midpoint(trusted_height: Height, current_height: Height) -> Result<Height, Error> {
+    let (low, high) = (trusted_height.value(), current_height.value());
+    let mid = low + (high - low) / 2;
+
+    if mid == low {
+        return Err(ErrorKind::BisectionFailed {
+            target_height: current_height,
+            trusted_height,
+        }
+        .into());
+    }
+
+    Ok(Height::try_from(mid).expect("midpoint lies between two valid heights"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::store::memory::MemoryStore;
+    use crate::types::Time;
+    use crate::utils::testing::{self, CHAIN_ID, TRUSTING_PERIOD};
+
+    /// Light blocks at heights 1 to `count`, each linked to the previous one
+    fn chain(count: u64) -> Vec<LightBlock> {
+        testing::chain(&["a"], count, 0)
+    }
+
+    /// A light client fetching the given blocks, with its clock set to `now`
+    fn light_client(blocks: Vec<LightBlock>, now: Time) -> LightClient {
+        testing::light_client("primary", blocks, now)
+    }
+
+    fn trust_options(trusted_block: &LightBlock) -> TrustOptions {
+        TrustOptions {
+            chain_id: CHAIN_ID.parse().unwrap(),
+            period: TRUSTING_PERIOD,
+            height: trusted_block.height(),
+            hash: trusted_block.signed_header.header.hash(),
+            trust_threshold: TrustThresholdFraction::default(),
+        }
+    }
+
+    #[test]
+    fn initializes_light_store_with_trusted_block() {
+        let blocks = chain(2);
+        let client = light_client(blocks.clone(), Time::unix_epoch());
+
+        let mut light_store = MemoryStore::new();
+        let trusted = client
+            .initialize(&trust_options(&blocks[1]), &mut light_store)
+            .unwrap();
+        assert_eq!(trusted, blocks[1]);
+        assert_eq!(
+            light_store.highest(Status::Trusted),
+            Some(blocks[1].clone())
+        );
+
+        // Initializing again with the same block is harmless
+        client
+            .initialize(&trust_options(&blocks[1]), &mut light_store)
+            .unwrap();
+    }
+
+    #[test]
+    fn initialization_fails_on_hash_mismatch() {
+        let blocks = chain(2);
+        let client = light_client(blocks.clone(), Time::unix_epoch());
+
+        let mut options = trust_options(&blocks[1]);
+        options.hash = blocks[0].signed_header.header.hash();
+
+        let mut light_store = MemoryStore::new();
+        let error = client.initialize(&options, &mut light_store).unwrap_err();
+        assert_eq!(
+            error.kind(),
+            &ErrorKind::TrustedHashMismatch {
+                expected: blocks[0].signed_header.header.hash(),
+                got: blocks[1].signed_header.header.hash(),
+            }
+        );
+        assert_eq!(light_store.highest(Status::Trusted), None);
+    }
+
+    #[test]
+    fn initialization_fails_on_chain_mismatch() {
+        let blocks = chain(1);
+        let client = light_client(blocks.clone(), Time::unix_epoch());
+
+        let mut options = trust_options(&blocks[0]);
+        options.chain_id = "other-chain".parse().unwrap();
+
+        let error = client
+            .initialize(&options, &mut MemoryStore::new())
+            .unwrap_err();
+        assert_eq!(
+            error.kind(),
+            &ErrorKind::InvalidLightBlock(VerificationError::ChainIdMismatch {
+                got: "test-chain".parse().unwrap(),
+                expected: "other-chain".parse().unwrap(),
+            })
+        );
+    }
+
+    #[test]
+    fn initialization_fails_on_validator_set_mismatch() {
+        let mut blocks = chain(1);
+        let forged = testing::chain(&["b"], 1, 0).remove(0).validators;
+        blocks[0].validators = forged.clone();
+        let client = light_client(blocks.clone(), Time::unix_epoch());
+
+        let error = client
+            .initialize(&trust_options(&blocks[0]), &mut MemoryStore::new())
+            .unwrap_err();
+        assert_eq!(
+            error.kind(),
+            &ErrorKind::InvalidLightBlock(VerificationError::InvalidValidatorSet {
+                header_validators_hash: blocks[0].signed_header.header.validators_hash,
+                validators_hash: forged.hash(),
+            })
+        );
+    }
+
+    #[test]
+    fn initialization_fails_outside_trusting_period() {
+        let blocks = chain(1);
+        let now = blocks[0]
+            .signed_header
+            .header
+            .time
+            .checked_add(Duration::from_secs(3600))
+            .unwrap();
+        let client = light_client(blocks.clone(), now);
+
+        let error = client
+            .initialize(&trust_options(&blocks[0]), &mut MemoryStore::new())
+            .unwrap_err();
+        assert!(matches!(
+            error.kind(),
+            ErrorKind::InvalidLightBlock(VerificationError::NotWithinTrustPeriod { .. })
+        ));
+    }
+
+    #[test]
+    fn initialization_fails_on_conflicting_trusted_block() {
+        let blocks = chain(1);
+        let client = light_client(blocks.clone(), Time::unix_epoch());
+
+        let conflicting = testing::chain(&["b"], 1, 0).remove(0);
+        let mut light_store = MemoryStore::new();
+        light_store.insert(conflicting.clone(), Status::Trusted);
+
+        let error = client
+            .initialize(&trust_options(&blocks[0]), &mut light_store)
+            .unwrap_err();
+        assert_eq!(
+            error.kind(),
+            &ErrorKind::ConflictingTrustedBlock(blocks[0].height())
+        );
+        assert_eq!(light_store.highest(Status::Trusted), Some(conflicting));
+    }
+
+    #[test]
+    fn rejects_block_at_another_height_than_requested() {
+        let blocks = chain(4);
+        let highest = blocks[3].clone();
+        let client = testing::light_client_with_io(
+            "primary",
+            Box::new(move |_: AtHeight| Ok(highest.clone())),
+            Time::unix_epoch(),
+        );
+
+        let mut light_store = MemoryStore::new();
+        light_store.insert(blocks[0].clone(), Status::Trusted);
+
+        let error = client
+            .verify_to_target(Height::from(2_u32), &mut light_store)
+            .unwrap_err();
+        assert!(matches!(
+            error.kind(),
+            ErrorKind::Io(IoError::InvalidHeight(_))
+        ));
+        assert_eq!(light_store.get_non_failed(Height::from(4_u32)), None);
+    }
+
+    #[test]
+    fn verifies_backward_from_lowest_trusted_block() {
+        let blocks = chain(4);
+        let client = light_client(blocks.clone(), Time::unix_epoch());
+
+        let mut light_store = MemoryStore::new();
+        light_store.insert(blocks[3].clone(), Status::Trusted);
+
+        let verified = client
+            .verify_to_target(Height::from(1_u32), &mut light_store)
+            .unwrap();
+        assert_eq!(verified, blocks[0]);
+
+        for block in &blocks {
+            assert_eq!(
+                light_store.get(block.height(), Status::Trusted).as_ref(),
+                Some(block)
+            );
+        }
+    }
+
+    #[test]
+    fn backward_verification_fails_on_broken_hash_link() {
+        let mut blocks = chain(4);
+        blocks[1].signed_header.header.app_hash = b"forged".to_vec();
+        let client = light_client(blocks.clone(), Time::unix_epoch());
+
+        let mut light_store = MemoryStore::new();
+        light_store.insert(blocks[3].clone(), Status::Trusted);
+
+        let error = client
+            .verify_backward(Height::from(1_u32), &mut light_store)
+            .unwrap_err();
+        assert!(matches!(
+            error.kind(),
+            ErrorKind::InvalidHashLink { height, .. } if *height == Height::from(2_u32)
+        ));
+        assert!(light_store
+            .get(Height::from(2_u32), Status::Failed)
+            .is_some());
+        assert!(light_store
+            .get(Height::from(3_u32), Status::Trusted)
+            .is_some());
+    }
+
+    #[test]
+    fn backward_verification_fails_on_forged_validator_set() {
+        let mut blocks = chain(4);
+        let forged = testing::chain(&["b"], 1, 0).remove(0).validators;
+        blocks[1].validators = forged.clone();
+        let client = light_client(blocks.clone(), Time::unix_epoch());
+
+        let mut light_store = MemoryStore::new();
+        light_store.insert(blocks[3].clone(), Status::Trusted);
+
+        let error = client
+            .verify_backward(Height::from(1_u32), &mut light_store)
+            .unwrap_err();
+        assert_eq!(
+            error.kind(),
+            &ErrorKind::InvalidLightBlock(VerificationError::InvalidValidatorSet {
+                header_validators_hash: blocks[1].signed_header.header.validators_hash,
+                validators_hash: forged.hash(),
+            })
+        );
+        assert!(light_store
+            .get(Height::from(2_u32), Status::Failed)
+            .is_some());
+        assert!(light_store
+            .get(Height::from(2_u32), Status::Trusted)
+            .is_none());
+    }
+}
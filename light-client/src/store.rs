@@ -6,6 +6,7 @@ use prusti_contracts::*;
 use crate::types::{Height, LightBlock, Status};
 use crate::utils::std_ext;
 
+pub mod file;
 pub mod memory;
 
 // #[cfg(feature = "lightstore-sled")]
@@ -24,7 +25,8 @@ pub trait LightStore: Debug + Send + Sync {
     /// Get the light block at the given height with the given status, or return `None` otherwise.
     fn get(&self, height: Height, status: Status) -> Option<LightBlock>;
 
-    /// Update the `status` of the given `light_block`.
+    /// Update the `status` of the given `light_block`, removing it from the status it was
+    /// stored with.
     fn update(&mut self, light_block: &LightBlock, status: Status);
 
     /// Insert a new light block in the store with the given status.
@@ -47,32 +49,30 @@ pub trait LightStore: Debug + Send + Sync {
     /// verification (ie. its status is not `Status::Failed`).
     #[trusted]
     fn get_non_failed(&self, height: Height) -> Option<(LightBlock, Status)> {
-        None
-        // None.or_else(|| {
-        //     self.get(height, Status::Trusted)
-        //         .map(|lb| (lb, Status::Trusted))
-        // })
-        // .or_else(|| {
-        //     self.get(height, Status::Verified)
-        //         .map(|lb| (lb, Status::Verified))
-        // })
-        // .or_else(|| {
-        //     self.get(height, Status::Unverified)
-        //         .map(|lb| (lb, Status::Unverified))
-        // })
+        None.or_else(|| {
+            self.get(height, Status::Trusted)
+                .map(|lb| (lb, Status::Trusted))
+        })
+        .or_else(|| {
+            self.get(height, Status::Verified)
+                .map(|lb| (lb, Status::Verified))
+        })
+        .or_else(|| {
+            self.get(height, Status::Unverified)
+                .map(|lb| (lb, Status::Unverified))
+        })
     }
 
-    // /// Get the light block of greatest height with the trusted or verified status.
-    // #[trusted]
-    // fn highest_trusted_or_verified(&self) -> Option<LightBlock> {
-    //     None
-    //     // let latest_trusted = self.highest(Status::Trusted);
-    //     // let latest_verified = self.highest(Status::Verified);
+    /// Get the light block of greatest height with the trusted or verified status.
+    #[trusted]
+    fn highest_trusted_or_verified(&self) -> Option<LightBlock> {
+        let latest_trusted = self.highest(Status::Trusted);
+        let latest_verified = self.highest(Status::Verified);
 
-    //     // std_ext::option::select(latest_trusted, latest_verified, |t, v| {
-    //     //     std_ext::cmp::max_by_key(t, v, |lb| lb.height())
-    //     // })
-    // }
+        std_ext::option::select(latest_trusted, latest_verified, |t, v| {
+            std_ext::cmp::max_by_key(t, v, |lb| lb.height())
+        })
+    }
 
     // /// Get the light block of lowest height with the trusted or verified status.
     // #[trusted]
@@ -86,11 +86,10 @@ pub trait LightStore: Debug + Send + Sync {
     //     // })
     // }
 
-    // /// Get the light block of the given height with the trusted or verified status.
-    // #[trusted]
-    // fn get_trusted_or_verified(&self, height: Height) -> Option<LightBlock> {
-    //     None
-    //     // self.get(height, Status::Trusted)
-    //     //     .or_else(|| self.get(height, Status::Verified))
-    // }
+    /// Get the light block of the given height with the trusted or verified status.
+    #[trusted]
+    fn get_trusted_or_verified(&self, height: Height) -> Option<LightBlock> {
+        self.get(height, Status::Trusted)
+            .or_else(|| self.get(height, Status::Verified))
+    }
 }
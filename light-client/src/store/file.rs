@@ -0,0 +1,294 @@
+//! Persistent store, keeping the light blocks on disk
+
+extern crate prusti_contracts;
+use prusti_contracts::*;
+
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use tendermint::proto::Protobuf;
+
+use crate::{
+    errors::{Error, ErrorKind},
+    store::{memory::MemoryStore, LightStore, Status},
+    types::{Height, LightBlock},
+};
+
+/// All statuses, in the order their directories are loaded in
+const STATUSES: [Status; 4] = [
+    Status::Failed,
+    Status::Unverified,
+    Status::Verified,
+    Status::Trusted,
+];
+
+/// Name of the directory holding the light blocks with the given status
+fn status_dir(status: &Status) -> &'static str {
+    match status {
+        Status::Failed => "failed",
+        Status::Unverified => "unverified",
+        Status::Verified => "verified",
+        Status::Trusted => "trusted",
+    }
+}
+
+/// Persistent store, which keeps every light block in a protobuf-encoded
+/// `<status>/<height>.pb` file under its root directory.
+///
+/// Queries are answered from an in-memory copy of the store, which is loaded
+/// when the store is opened. Like the `MemoryStore`, it holds at most one
+/// light block per height and status.
+///
+/// A status change writes the file of the new status before removing the one of
+/// the old status, so that a crash in between leaves the light block stored
+/// with both statuses rather than lost. The next status change of the light
+/// block removes the leftover file.
+///
+/// ## Panics
+/// As the `LightStore` interface is infallible, the methods which modify the
+/// store panic if the change cannot be written to disk.
+pub struct FileStore {
+    root: PathBuf,
+    memory: MemoryStore,
+}
+
+impl FileStore {
+    /// Open the store rooted at the given directory, creating it if needed.
+    pub fn open(root: impl Into<PathBuf>) -> Result<Self, Error> {
+        let root = root.into();
+        let mut memory = MemoryStore::new();
+
+        for status in STATUSES.iter() {
+            let dir = root.join(status_dir(status));
+            fs::create_dir_all(&dir).map_err(|e| store_error(&dir, e))?;
+
+            for entry in fs::read_dir(&dir).map_err(|e| store_error(&dir, e))? {
+                let path = entry.map_err(|e| store_error(&dir, e))?.path();
+                if path.extension().and_then(|ext| ext.to_str()) != Some("pb") {
+                    continue;
+                }
+
+                let bytes = fs::read(&path).map_err(|e| store_error(&path, e))?;
+                let light_block = LightBlock::decode_vec(&bytes).map_err(|e| {
+                    ErrorKind::Store(format!("cannot decode {}", path.display())).context(e)
+                })?;
+
+                memory.insert(light_block, status.clone());
+            }
+        }
+
+        Ok(Self { root, memory })
+    }
+
+    /// The root directory of this store
+    pub fn path(&self) -> &Path {
+        &self.root
+    }
+
+    fn block_path(&self, height: Height, status: &Status) -> PathBuf {
+        self.root
+            .join(status_dir(status))
+            .join(format!("{}.pb", height))
+    }
+
+    /// Durably write the light block with the given status
+    fn persist(&self, light_block: &LightBlock, status: &Status) {
+        let height = light_block.height();
+        let path = self.block_path(height, status);
+        let bytes = light_block
+            .clone()
+            .encode_vec()
+            .unwrap_or_else(|e| panic!("cannot encode light block at height {}: {}", height, e));
+
+        write_durably(&path, &bytes)
+            .unwrap_or_else(|e| panic!("cannot write {}: {}", path.display(), e));
+    }
+
+    fn delete(&self, height: Height, status: &Status) {
+        let path = self.block_path(height, status);
+        match fs::remove_file(&path) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => panic!("cannot remove {}: {}", path.display(), e),
+        }
+    }
+}
+
+/// Write the given bytes to a temporary file next to `path`, and rename it to
+/// `path` once synced to disk, syncing the directory to persist the rename.
+fn write_durably(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    let tmp_path = path.with_extension("pb.tmp");
+
+    let mut file = File::create(&tmp_path)?;
+    file.write_all(bytes)?;
+    file.sync_all()?;
+
+    fs::rename(&tmp_path, path)?;
+
+    match path.parent() {
+        Some(dir) => File::open(dir)?.sync_all(),
+        None => Ok(()),
+    }
+}
+
+fn store_error(path: &Path, e: io::Error) -> Error {
+    ErrorKind::Store(format!("cannot read {}", path.display()))
+        .context(e)
+        .into()
+}
+
+impl fmt::Debug for FileStore {
+    #[trusted]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FileStore")
+            .field("root", &self.root)
+            .finish()
+    }
+}
+
+impl LightStore for FileStore {
+    fn get(&self, height: Height, status: Status) -> Option<LightBlock> {
+        self.memory.get(height, status)
+    }
+
+    fn update(&mut self, light_block: &LightBlock, status: Status) {
+        let height = light_block.height();
+        self.persist(light_block, &status);
+
+        for other in STATUSES.iter().filter(|other| **other != status) {
+            if self.memory.get(height, other.clone()).as_ref() == Some(light_block) {
+                self.delete(height, other);
+            }
+        }
+
+        self.memory.update(light_block, status);
+    }
+
+    fn insert(&mut self, light_block: LightBlock, status: Status) {
+        self.persist(&light_block, &status);
+        self.memory.insert(light_block, status);
+    }
+
+    fn remove(&mut self, height: Height, status: Status) {
+        if self.memory.get(height, status.clone()).is_some() {
+            self.delete(height, &status);
+            self.memory.remove(height, status);
+        }
+    }
+
+    fn highest(&self, status: Status) -> Option<LightBlock> {
+        self.memory.highest(status)
+    }
+
+    fn lowest(&self, status: Status) -> Option<LightBlock> {
+        self.memory.lowest(status)
+    }
+
+    fn all(&self, status: Status) -> Box<dyn Iterator<Item = LightBlock>> {
+        self.memory.all(status)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tendermint_testgen::{light_block::LightBlock as TestgenLightBlock, Generator, Validator};
+
+    fn light_block(height: u64) -> LightBlock {
+        TestgenLightBlock::new(vec![Validator::new("a")])
+            .height(height)
+            .generate()
+            .unwrap()
+            .into()
+    }
+
+    fn temp_root(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!(
+            "light-client-file-store-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&root);
+        root
+    }
+
+    #[test]
+    fn reopened_store_has_same_contents() {
+        let root = temp_root("reopen");
+
+        let mut store = FileStore::open(&root).unwrap();
+        store.insert(light_block(1), Status::Trusted);
+        store.insert(light_block(2), Status::Verified);
+        store.insert(light_block(3), Status::Unverified);
+        store.update(&light_block(2), Status::Trusted);
+        store.remove(Height::from(3_u32), Status::Unverified);
+
+        let reopened = FileStore::open(&root).unwrap();
+        assert_eq!(
+            reopened.all(Status::Trusted).collect::<Vec<_>>(),
+            vec![light_block(1), light_block(2)]
+        );
+        assert_eq!(reopened.highest(Status::Verified), None);
+        assert_eq!(reopened.get(Height::from(3_u32), Status::Unverified), None);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn status_change_moves_file() {
+        let root = temp_root("move");
+
+        let mut store = FileStore::open(&root).unwrap();
+        store.insert(light_block(5), Status::Verified);
+        store.update(&light_block(5), Status::Failed);
+
+        assert!(!root.join("verified").join("5.pb").exists());
+        assert!(root.join("failed").join("5.pb").exists());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn interrupted_status_change_is_completed_by_the_next_one() {
+        let root = temp_root("interrupted");
+
+        let mut store = FileStore::open(&root).unwrap();
+        store.insert(light_block(5), Status::Verified);
+
+        // A crash after the trusted file was written, but before the verified
+        // one was removed
+        fs::copy(
+            root.join("verified").join("5.pb"),
+            root.join("trusted").join("5.pb"),
+        )
+        .unwrap();
+
+        let mut reopened = FileStore::open(&root).unwrap();
+        assert_eq!(reopened.highest(Status::Trusted), Some(light_block(5)));
+        assert_eq!(reopened.highest(Status::Verified), Some(light_block(5)));
+
+        reopened.update(&light_block(5), Status::Trusted);
+        assert_eq!(reopened.highest(Status::Verified), None);
+        assert!(!root.join("verified").join("5.pb").exists());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn insert_keeps_other_statuses() {
+        let root = temp_root("insert");
+
+        let mut store = FileStore::open(&root).unwrap();
+        store.insert(light_block(5), Status::Failed);
+        store.insert(light_block(5), Status::Unverified);
+
+        let reopened = FileStore::open(&root).unwrap();
+        assert_eq!(reopened.highest(Status::Failed), Some(light_block(5)));
+        assert_eq!(reopened.highest(Status::Unverified), Some(light_block(5)));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
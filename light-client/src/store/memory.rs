@@ -9,7 +9,6 @@ use crate::{
     types::{Height, LightBlock},
 };
 
-use std::collections::btree_map::Entry::*;
 use std::collections::BTreeMap;
 
 #[extern_spec]
@@ -52,10 +51,11 @@ impl StoreEntry {
     }
 }
 
-/// Transient in-memory store.
+/// Transient in-memory store, holding at most one light block per height and
+/// status.
 #[derive(Clone, Default)]
 pub struct MemoryStore {
-    store: BTreeMap<Height, StoreEntry>,
+    store: BTreeMap<(Height, Status), StoreEntry>,
 }
 
 impl MemoryStore {
@@ -65,6 +65,14 @@ impl MemoryStore {
             store: BTreeMap::new(),
         }
     }
+
+    /// Remove the given light block if it is stored with the given status
+    fn remove_block(&mut self, light_block: &LightBlock, status: Status) {
+        let height = light_block.height();
+        if self.get(height, status.clone()) == Some(light_block.clone()) {
+            self.remove(height, status);
+        }
+    }
 }
 
 impl fmt::Debug for MemoryStore {
@@ -74,44 +82,43 @@ impl fmt::Debug for MemoryStore {
     }
 }
 
+#[allow(dead_code)]
 #[trusted]
 #[ensures(get_ms(ms, light_block.height(), status) == Some(light_block))]
 fn insert_ms(ms: &mut MemoryStore, light_block: LightBlock, status: Status) {
-    ms.store
-        .insert(light_block.height(), StoreEntry::new(light_block, status));
+    ms.store.insert(
+        (light_block.height(), status.clone()),
+        StoreEntry::new(light_block, status),
+    );
 }
 
+#[allow(dead_code)]
 #[pure]
 #[trusted]
 fn get_ms(ms: &MemoryStore, height: Height, status: Status) -> Option<LightBlock> {
-    let value = ms.store.get(&height);
-    match value {
-        Some(e) => {
-            if (e.status == status) {
-                return Some(e.light_block.clone());
-            } else {
-                return None;
-            }
-        }
-        None => return None,
-    }
+    ms.store
+        .get(&(height, status))
+        .map(|e| e.light_block.clone())
 }
 
+#[allow(clippy::ptr_arg)]
 #[trusted]
 #[requires(index < veclen(vec))]
 fn get_and_unwrap(vec: &Vec<StoreEntry>, index: usize) -> StoreEntry {
-    return vec.get(index).unwrap().clone();
+    vec.get(index).unwrap().clone()
 }
 
+#[allow(dead_code)]
 #[pure]
 #[trusted]
 fn height0() -> Height {
     panic!("Nvm");
 }
 
+#[allow(dead_code)]
 #[pure]
-fn ok(olb: Option<LightBlock>) -> bool {
-    return true;
+fn ok(_olb: Option<LightBlock>) -> bool {
+    true
 }
 
 
@@ -135,34 +142,37 @@ fn ok(olb: Option<LightBlock>) -> bool {
 //     return None;
 // }
 
+#[allow(dead_code)]
 #[pure]
 #[trusted]
 #[ensures(result >= 0)]
 fn num_values(ls: &MemoryStore) -> usize {
-    return veclen(&values(ls));
+    veclen(&values(ls))
 }
 
+#[allow(dead_code)]
 #[pure]
 #[trusted]
 #[requires(idx < num_values(ls))]
 fn get_at(ls: &MemoryStore, idx: usize) -> StoreEntry {
     let vec: Vec<StoreEntry> = values(ls);
-    return get_and_unwrap(&vec, idx);
+    get_and_unwrap(&vec, idx)
 }
 
+#[allow(dead_code)]
 #[ensures(result < num_values(ls) as i64)]
 #[ensures(result > 0 ==> get_at(&ls, result as usize).status == status)]
 fn lowest_idx(ls: &MemoryStore, status: Status) -> i64 {
     let mut i = 0;
-    while i < num_values(&ls) {
-        body_invariant!(i < num_values(&ls));
+    while i < num_values(ls) {
+        body_invariant!(i < num_values(ls));
         let e = get_at(ls, i);
-        if (e.status == status) {
+        if e.status == status {
             return i as i64;
         }
         i += 1;
     }
-    return -1;
+    -1
 }
 
 // #[pure]
@@ -185,18 +195,15 @@ fn lowest_idx(ls: &MemoryStore, status: Status) -> i64 {
 //     }
 // }
 
-fn light_blocks(ls: &MemoryStore) -> Vec<LightBlock> {
-    return ls.store.values().cloned().map(|e| e.light_block).collect();
-}
-
 fn values(ls: &MemoryStore) -> Vec<StoreEntry> {
-    return ls.store.values().cloned().collect()
+    ls.store.values().cloned().collect()
 }
 
+#[allow(clippy::ptr_arg)]
 #[pure]
 #[trusted]
 fn veclen(vec: &Vec<StoreEntry>) -> usize {
-    return vec.len();
+    vec.len()
 }
 
 
@@ -204,34 +211,30 @@ impl LightStore for MemoryStore {
     #[pure]
     #[trusted]
     fn get(&self, height: Height, status: Status) -> Option<LightBlock> {
-        let value = self.store.get(&height);
-        match value {
-            Some(e) => {
-                if (e.status == status) {
-                    return Some(e.light_block.clone());
-                } else {
-                    return None;
-                }
-            }
-            None => return None,
-        }
+        self.store
+            .get(&(height, status))
+            .map(|e| e.light_block.clone())
     }
 
     fn insert(&mut self, light_block: LightBlock, status: Status) {
-        self.store
-            .insert(light_block.height(), StoreEntry::new(light_block, status));
+        self.store.insert(
+            (light_block.height(), status.clone()),
+            StoreEntry::new(light_block, status),
+        );
     }
 
     #[trusted]
     fn remove(&mut self, height: Height, status: Status) {
-        if let Occupied(e) = self.store.entry(height) {
-            if e.get().status == status {
-                e.remove_entry();
-            }
-        }
+        self.store.remove(&(height, status));
     }
 
+    /// Move the given light block from whatever status it is stored with to
+    /// the given one.
     fn update(&mut self, light_block: &LightBlock, status: Status) {
+        self.remove_block(light_block, Status::Failed);
+        self.remove_block(light_block, Status::Unverified);
+        self.remove_block(light_block, Status::Verified);
+        self.remove_block(light_block, Status::Trusted);
         self.insert(light_block.clone(), status);
     }
 
@@ -244,12 +247,12 @@ impl LightStore for MemoryStore {
         while i < veclen(&vec) {
             body_invariant!(i < veclen(&vec));
             let e = get_and_unwrap(&vec, i);
-            if (e.status == status) {
-                return Some(e.light_block)
+            if e.status == status {
+                return Some(e.light_block);
             }
             i += 1;
         }
-        return None;
+        None
     }
 
     // Note: this relies on the fact that iter() returns a list
@@ -260,12 +263,12 @@ impl LightStore for MemoryStore {
         while i < veclen(&vec) {
             body_invariant!(i < veclen(&vec));
             let e = get_and_unwrap(&vec, i);
-            if (e.status == status) {
-                return Some(e.light_block)
+            if e.status == status {
+                return Some(e.light_block);
             }
             i += 1;
         }
-        return None;
+        None
     }
 
     #[trusted]
@@ -275,7 +278,7 @@ impl LightStore for MemoryStore {
         loop {
             match it.next() {
                 Some(e) => {
-                    if (e.status == status) {
+                    if e.status == status {
                         vec.push(e.light_block.clone())
                     }
                 }
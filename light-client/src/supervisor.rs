@@ -0,0 +1,585 @@
+//! Supervisor and Handle implementation.
+//!
+//! The supervisor owns a primary instance and a list of witness instances.
+//! It verifies light blocks against the primary, cross-checks them with the
+//! witnesses via the fork detector, and swaps the primary for a witness
+//! whenever the primary fails. Requests are submitted through a [`Handle`].
+
+use std::fmt;
+use std::sync::mpsc::{self, Receiver, Sender};
+
+extern crate prusti_contracts;
+use prusti_contracts::*;
+
+use crate::{
+    errors::{Error, ErrorKind},
+    fork_detector::{detect_and_promote, Fork, ForkDetection, ForkDetector},
+    light_client::LightClient,
+    store::LightStore,
+    types::{Height, LightBlock, PeerId, Status, TrustOptions},
+};
+
+/// A light client instance: a light client talking to a single provider,
+/// together with its own light store.
+pub struct Instance {
+    /// The light client for this instance
+    pub light_client: LightClient,
+    /// The light store for this instance
+    pub light_store: Box<dyn LightStore>,
+}
+
+impl fmt::Debug for Instance {
+    #[trusted]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Instance")
+            .field("light_client", &self.light_client)
+            .finish()
+    }
+}
+
+impl Instance {
+    /// Constructs a new instance from the given light client and light store.
+    pub fn new(light_client: LightClient, light_store: Box<dyn LightStore>) -> Self {
+        Self {
+            light_client,
+            light_store,
+        }
+    }
+
+    /// The identifier of the provider of this instance
+    pub fn peer_id(&self) -> &PeerId {
+        self.light_client.peer_id()
+    }
+
+    /// Initialize the light store of this instance from the given trust options.
+    pub fn initialize(&mut self, trust_options: &TrustOptions) -> Result<LightBlock, Error> {
+        self.light_client
+            .initialize(trust_options, self.light_store.as_mut())
+    }
+
+    /// Get the latest trusted block of this instance.
+    pub fn latest_trusted(&self) -> Option<LightBlock> {
+        self.light_store.highest(Status::Trusted)
+    }
+
+    /// Attempt to update this instance to the highest block of its provider.
+    pub fn verify_to_highest(&mut self) -> Result<LightBlock, Error> {
+        self.light_client
+            .verify_to_highest(self.light_store.as_mut())
+    }
+
+    /// Attempt to update this instance to the block of its provider at the given height.
+    pub fn verify_to_target(&mut self, height: Height) -> Result<LightBlock, Error> {
+        self.light_client
+            .verify_to_target(height, self.light_store.as_mut())
+    }
+}
+
+/// Input events sent by the `Handle` to the `Supervisor`.
+/// Each event carries a channel over which the result is sent back.
+enum HandleInput {
+    /// Terminate the supervisor process
+    Terminate(Sender<()>),
+    /// Verify to the highest height, call the provided callback with result
+    VerifyToHighest(Sender<Result<LightBlock, Error>>),
+    /// Verify to the given height, call the provided callback with result
+    VerifyToTarget(Height, Sender<Result<LightBlock, Error>>),
+    /// Get the latest trusted block
+    LatestTrusted(Sender<Option<LightBlock>>),
+}
+
+/// A cloneable handle to submit requests to a running `Supervisor`.
+#[derive(Clone)]
+pub struct Handle {
+    sender: Sender<HandleInput>,
+}
+
+impl fmt::Debug for Handle {
+    #[trusted]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Handle").finish()
+    }
+}
+
+impl Handle {
+    /// Get the latest trusted block.
+    pub fn latest_trusted(&self) -> Result<Option<LightBlock>, Error> {
+        let (sender, receiver) = mpsc::channel();
+        self.send(HandleInput::LatestTrusted(sender))?;
+        receiver
+            .recv()
+            .map_err(|_| ErrorKind::ChannelDisconnected.into())
+    }
+
+    /// Verify to the highest block of the primary.
+    pub fn verify_to_highest(&self) -> Result<LightBlock, Error> {
+        let (sender, receiver) = mpsc::channel();
+        self.send(HandleInput::VerifyToHighest(sender))?;
+        receiver
+            .recv()
+            .map_err(|_| ErrorKind::ChannelDisconnected)?
+    }
+
+    /// Verify to the block of the primary at the given height.
+    pub fn verify_to_target(&self, height: Height) -> Result<LightBlock, Error> {
+        let (sender, receiver) = mpsc::channel();
+        self.send(HandleInput::VerifyToTarget(height, sender))?;
+        receiver
+            .recv()
+            .map_err(|_| ErrorKind::ChannelDisconnected)?
+    }
+
+    /// Terminate the supervisor, waiting for it to acknowledge.
+    pub fn terminate(&self) -> Result<(), Error> {
+        let (sender, receiver) = mpsc::channel();
+        self.send(HandleInput::Terminate(sender))?;
+        receiver
+            .recv()
+            .map_err(|_| ErrorKind::ChannelDisconnected.into())
+    }
+
+    fn send(&self, input: HandleInput) -> Result<(), Error> {
+        self.sender
+            .send(input)
+            .map_err(|_| ErrorKind::ChannelDisconnected.into())
+    }
+}
+
+/// The supervisor manages the primary instance and its witnesses,
+/// and processes the requests submitted through its handles.
+pub struct Supervisor {
+    /// The instance light blocks are verified against
+    primary: Instance,
+    /// The instances used for fork detection, and as replacements for a faulty primary
+    witnesses: Vec<Instance>,
+    /// Detects forks between the primary and the witnesses
+    fork_detector: Box<dyn ForkDetector>,
+    /// Channel through which to send requests to the supervisor
+    sender: Sender<HandleInput>,
+    /// Channel through which the supervisor receives requests
+    receiver: Receiver<HandleInput>,
+}
+
+impl fmt::Debug for Supervisor {
+    #[trusted]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Supervisor")
+            .field("primary", &self.primary)
+            .field("witnesses", &self.witnesses)
+            .finish()
+    }
+}
+
+impl Supervisor {
+    /// Constructs a new supervisor from the given primary and witness instances.
+    pub fn new(
+        primary: Instance,
+        witnesses: Vec<Instance>,
+        fork_detector: Box<dyn ForkDetector>,
+    ) -> Self {
+        let (sender, receiver) = mpsc::channel();
+
+        Self {
+            primary,
+            witnesses,
+            fork_detector,
+            sender,
+            receiver,
+        }
+    }
+
+    /// Create a new handle to this supervisor.
+    pub fn handle(&self) -> Handle {
+        Handle {
+            sender: self.sender.clone(),
+        }
+    }
+
+    /// The identifier of the current primary
+    pub fn primary(&self) -> &PeerId {
+        self.primary.peer_id()
+    }
+
+    /// The identifiers of the current witnesses
+    pub fn witnesses(&self) -> Vec<&PeerId> {
+        self.witnesses.iter().map(Instance::peer_id).collect()
+    }
+
+    /// Get the latest trusted block of the primary.
+    pub fn latest_trusted(&self) -> Option<LightBlock> {
+        self.primary.latest_trusted()
+    }
+
+    /// Verify to the highest block of the primary.
+    pub fn verify_to_highest(&mut self) -> Result<LightBlock, Error> {
+        self.verify(None)
+    }
+
+    /// Verify to the block of the primary at the given height.
+    pub fn verify_to_target(&mut self, height: Height) -> Result<LightBlock, Error> {
+        self.verify(Some(height))
+    }
+
+    /// Run the supervisor event loop, processing requests from the handles
+    /// until a `Terminate` request is received.
+    pub fn run(mut self) -> Result<(), Error> {
+        loop {
+            let event = self
+                .receiver
+                .recv()
+                .map_err(|_| ErrorKind::ChannelDisconnected)?;
+
+            match event {
+                HandleInput::LatestTrusted(sender) => {
+                    send_result(sender, self.latest_trusted());
+                }
+                HandleInput::VerifyToHighest(sender) => {
+                    let outcome = self.verify_to_highest();
+                    send_result(sender, outcome);
+                }
+                HandleInput::VerifyToTarget(height, sender) => {
+                    let outcome = self.verify_to_target(height);
+                    send_result(sender, outcome);
+                }
+                HandleInput::Terminate(sender) => {
+                    send_result(sender, ());
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    /// Verify against the primary, then run fork detection against the witnesses.
+    ///
+    /// If the primary is unresponsive or serves invalid light blocks, it is replaced
+    /// by the first witness and verification is attempted again. Other errors, such
+    /// as a target below the trusted state, are returned as is. Witnesses which turn out to be faulty or unresponsive
+    /// are removed, and fork detection is attempted again with the remaining ones.
+    fn verify(&mut self, height: Option<Height>) -> Result<LightBlock, Error> {
+        loop {
+            let outcome = match height {
+                None => self.primary.verify_to_highest(),
+                Some(height) => self.primary.verify_to_target(height),
+            };
+
+            let verified_block = match outcome {
+                Ok(verified_block) => verified_block,
+                Err(e) if is_faulty_primary(e.kind()) => {
+                    self.replace_faulty_primary(e)?;
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+
+            // The block was already trusted, eg. when verifying to a lower height
+            if self
+                .primary
+                .light_store
+                .get(verified_block.height(), Status::Trusted)
+                .is_some()
+            {
+                return Ok(verified_block);
+            }
+
+            loop {
+                let witnesses: Vec<&LightClient> =
+                    self.witnesses.iter().map(|w| &w.light_client).collect();
+
+                let detection = detect_and_promote(
+                    self.fork_detector.as_ref(),
+                    &witnesses,
+                    &verified_block,
+                    self.primary.light_store.as_mut(),
+                )?;
+
+                match detection {
+                    ForkDetection::NotDetected => return Ok(verified_block),
+                    ForkDetection::Detected(forks) => self.process_forks(forks)?,
+                }
+            }
+        }
+    }
+
+    /// Report the witnesses which forked, or remove the faulty ones
+    /// so that fork detection can be attempted again.
+    fn process_forks(&mut self, forks: Vec<Fork>) -> Result<(), Error> {
+        let mut forked = vec![];
+
+        for fork in forks {
+            match fork {
+                Fork::Forked { peer_id, .. } => forked.push(peer_id),
+                Fork::Faulty(peer_id, _) | Fork::Timeout(peer_id, _) => {
+                    self.witnesses.retain(|w| w.peer_id() != &peer_id);
+                }
+            }
+        }
+
+        if !forked.is_empty() {
+            return Err(ErrorKind::ForkDetected(forked).into());
+        }
+
+        Ok(())
+    }
+
+    /// Replace the primary with the first witness, failing with the given
+    /// error if there are no witnesses left.
+    ///
+    /// The light store stays with the primary, so that blocks verified after
+    /// the replacement end up next to the ones trusted before it.
+    fn replace_faulty_primary(&mut self, primary_error: Error) -> Result<(), Error> {
+        if self.witnesses.is_empty() {
+            return Err(primary_error);
+        }
+
+        let promoted = self.witnesses.remove(0);
+        let mut faulty = std::mem::replace(&mut self.primary, promoted);
+
+        // The promoted witness takes over the light store of the faulty primary,
+        // keeping its trusted state but none of the blocks it served which
+        // were not trusted yet.
+        for status in &[Status::Unverified, Status::Verified, Status::Failed] {
+            let untrusted: Vec<LightBlock> = faulty.light_store.all(status.clone()).collect();
+            for light_block in untrusted {
+                faulty
+                    .light_store
+                    .remove(light_block.height(), status.clone());
+            }
+        }
+        std::mem::swap(&mut self.primary.light_store, &mut faulty.light_store);
+
+        Ok(())
+    }
+}
+
+/// Send the result of a request back to its handle. The handle may have gone
+/// away in the meantime, which does not prevent serving the other ones.
+fn send_result<T>(sender: Sender<T>, result: T) {
+    if sender.send(result).is_err() {
+        log::warn!("dropping the result of a request whose handle is gone");
+    }
+}
+
+/// Whether the given error shows that the primary is unresponsive or serves
+/// invalid light blocks, rather than stemming from the request or the state
+/// of the light store.
+fn is_faulty_primary(kind: &ErrorKind) -> bool {
+    matches!(
+        kind,
+        ErrorKind::Io(_) | ErrorKind::InvalidLightBlock(_) | ErrorKind::InvalidHashLink { .. }
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::thread;
+    use std::time::Duration;
+
+    use crate::fork_detector::ProdForkDetector;
+    use crate::store::file::FileStore;
+    use crate::store::memory::MemoryStore;
+    use crate::types::Time;
+    use crate::utils::testing;
+
+    /// A chain of four blocks, the last of which is moved forward by
+    /// `last_block_delay` seconds, so that different delays give
+    /// conflicting last blocks
+    fn chain(last_block_delay: u64) -> Vec<LightBlock> {
+        testing::chain(&["a", "b", "c"], 4, last_block_delay)
+    }
+
+    /// An instance serving the given blocks, with the first one trusted
+    fn instance(name: &str, blocks: Vec<LightBlock>) -> Instance {
+        let mut light_store = MemoryStore::new();
+        if let Some(first) = blocks.first() {
+            light_store.insert(first.clone(), Status::Trusted);
+        }
+
+        let now = Time::unix_epoch()
+            .checked_add(Duration::from_secs(60))
+            .unwrap();
+        Instance::new(
+            testing::light_client(name, blocks, now),
+            Box::new(light_store),
+        )
+    }
+
+    fn supervisor_with(primary: Instance, witnesses: Vec<Instance>) -> Supervisor {
+        Supervisor::new(primary, witnesses, Box::new(ProdForkDetector::new()))
+    }
+
+    #[test]
+    fn trusts_block_confirmed_by_witnesses() {
+        let mut supervisor = supervisor_with(
+            instance("primary", chain(0)),
+            vec![instance("witness", chain(0))],
+        );
+
+        let verified = supervisor.verify_to_highest().unwrap();
+        assert_eq!(verified, chain(0)[3]);
+        assert_eq!(supervisor.latest_trusted(), Some(verified));
+    }
+
+    #[test]
+    fn replaces_unresponsive_primary() {
+        let mut primary = instance("primary", vec![]);
+        primary
+            .light_store
+            .insert(chain(0)[0].clone(), Status::Trusted);
+        let mut supervisor = supervisor_with(
+            primary,
+            vec![instance("first", chain(0)), instance("second", chain(0))],
+        );
+
+        let verified = supervisor.verify_to_highest().unwrap();
+        assert_eq!(verified, chain(0)[3]);
+        assert_eq!(supervisor.primary(), &PeerId::new("first"));
+        assert_eq!(supervisor.witnesses(), vec![&PeerId::new("second")]);
+    }
+
+    #[test]
+    fn promoted_witness_takes_over_trusted_state() {
+        let mut primary = instance("primary", vec![]);
+        primary
+            .light_store
+            .insert(chain(0)[0].clone(), Status::Trusted);
+        primary
+            .light_store
+            .insert(chain(0)[1].clone(), Status::Trusted);
+        primary
+            .light_store
+            .insert(chain(1)[3].clone(), Status::Unverified);
+        let mut supervisor = supervisor_with(
+            primary,
+            vec![instance("first", chain(0)), instance("second", chain(0))],
+        );
+
+        let verified = supervisor.verify_to_target(Height::from(3_u32)).unwrap();
+        assert_eq!(verified, chain(0)[2]);
+        assert_eq!(supervisor.primary(), &PeerId::new("first"));
+
+        let light_store = &supervisor.primary.light_store;
+        assert_eq!(
+            light_store.get(Height::from(2_u32), Status::Trusted),
+            Some(chain(0)[1].clone())
+        );
+        assert_eq!(
+            light_store.get(Height::from(4_u32), Status::Unverified),
+            None
+        );
+    }
+
+    #[test]
+    fn blocks_verified_by_promoted_witness_are_persisted() {
+        let root =
+            std::env::temp_dir().join(format!("light-client-supervisor-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+
+        let mut light_store = FileStore::open(&root).unwrap();
+        light_store.insert(chain(0)[0].clone(), Status::Trusted);
+        let now = Time::unix_epoch()
+            .checked_add(Duration::from_secs(60))
+            .unwrap();
+        let primary = Instance::new(
+            testing::light_client("primary", vec![], now),
+            Box::new(light_store),
+        );
+        let mut supervisor = supervisor_with(
+            primary,
+            vec![
+                Instance::new(
+                    testing::light_client("first", chain(0), now),
+                    Box::new(MemoryStore::new()),
+                ),
+                instance("second", chain(0)),
+            ],
+        );
+
+        let verified = supervisor.verify_to_highest().unwrap();
+        assert_eq!(supervisor.primary(), &PeerId::new("first"));
+        drop(supervisor);
+
+        let reopened = FileStore::open(&root).unwrap();
+        assert_eq!(reopened.highest(Status::Trusted), Some(verified));
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn keeps_primary_on_errors_not_caused_by_it() {
+        let mut primary = instance("primary", chain(0));
+        primary
+            .light_store
+            .remove(Height::from(1_u32), Status::Trusted);
+        let mut supervisor = supervisor_with(primary, vec![instance("witness", chain(0))]);
+
+        let error = supervisor.verify_to_highest().unwrap_err();
+        assert_eq!(error.kind(), &ErrorKind::NoInitialTrustedState);
+        assert_eq!(supervisor.primary(), &PeerId::new("primary"));
+
+        let mut primary = instance("primary", chain(0));
+        primary
+            .light_store
+            .insert(chain(0)[2].clone(), Status::Trusted);
+        let mut supervisor = supervisor_with(primary, vec![instance("witness", chain(0))]);
+
+        let error = supervisor
+            .verify_to_target(Height::from(2_u32))
+            .unwrap_err();
+        assert!(matches!(
+            error.kind(),
+            ErrorKind::TargetLowerThanTrustedState { .. }
+        ));
+        assert_eq!(supervisor.primary(), &PeerId::new("primary"));
+        assert_eq!(supervisor.witnesses(), vec![&PeerId::new("witness")]);
+    }
+
+    #[test]
+    fn reports_fork_and_leaves_block_verified() {
+        let mut supervisor = supervisor_with(
+            instance("primary", chain(0)),
+            vec![instance("witness", chain(1))],
+        );
+
+        let error = supervisor.verify_to_highest().unwrap_err();
+        assert_eq!(
+            error.kind(),
+            &ErrorKind::ForkDetected(vec![PeerId::new("witness")])
+        );
+        assert_eq!(supervisor.latest_trusted(), Some(chain(0)[0].clone()));
+        assert_eq!(
+            supervisor
+                .primary
+                .light_store
+                .get(Height::from(4_u32), Status::Verified),
+            Some(chain(0)[3].clone())
+        );
+    }
+
+    #[test]
+    fn handle_requests_are_processed_until_terminated() {
+        let supervisor = supervisor_with(
+            instance("primary", chain(0)),
+            vec![instance("witness", chain(0))],
+        );
+        let handle = supervisor.handle();
+        let thread = thread::spawn(move || supervisor.run());
+
+        let verified = handle.verify_to_target(Height::from(3_u32)).unwrap();
+        assert_eq!(verified, chain(0)[2]);
+        assert_eq!(handle.latest_trusted().unwrap(), Some(verified));
+
+        // A handle which gave up waiting does not stop the supervisor
+        let (sender, receiver) = mpsc::channel();
+        drop(receiver);
+        handle.send(HandleInput::VerifyToHighest(sender)).unwrap();
+        assert_eq!(handle.latest_trusted().unwrap(), Some(chain(0)[3].clone()));
+
+        handle.terminate().unwrap();
+        thread.join().unwrap().unwrap();
+        assert_eq!(
+            handle.latest_trusted().unwrap_err().kind(),
+            &ErrorKind::ChannelDisconnected
+        );
+    }
+}
@@ -1,17 +1,27 @@
 //! Defines or just re-exports the main datatypes used by the light client.
 
+use serde::{Deserialize, Serialize};
+use std::convert::{TryFrom, TryInto};
 use std::fmt;
-pub use tendermint::{block::Height, hash::Hash};
+use std::time::Duration;
+pub use tendermint::{block::Height, chain::Id as ChainId, hash::Hash, Time};
 
 extern crate prusti_contracts;
 use prusti_contracts::*;
 
 use tendermint::block::signed_header::SignedHeader as TMSignedHeader;
+use tendermint::proto::types::LightBlock as RawLightBlock;
+use tendermint::proto::Protobuf;
+use tendermint::validator::Set as TMValidatorSet;
+
+use crate::errors::{Error, ErrorKind};
 
 #[derive(Clone, PartialEq, Eq)]
 pub struct LightBlock {
     /// Header and commit of this block
     pub signed_header: SignedHeader,
+    /// Validator set at the block height
+    pub validators: ValidatorSet,
 }
 
 impl fmt::Debug for LightBlock {
@@ -23,8 +33,11 @@ impl fmt::Debug for LightBlock {
 
 impl LightBlock {
     /// Constructs a new light block
-    pub fn new(signed_header: SignedHeader) -> LightBlock {
-        Self { signed_header }
+    pub fn new(signed_header: SignedHeader, validators: ValidatorSet) -> LightBlock {
+        Self {
+            signed_header,
+            validators,
+        }
     }
 
     /// Returns the height of this block.
@@ -37,7 +50,42 @@ impl LightBlock {
     }
 }
 
-#[derive(Clone, PartialEq, Eq)]
+impl Protobuf<RawLightBlock> for LightBlock {}
+
+impl TryFrom<RawLightBlock> for LightBlock {
+    type Error = tendermint::Error;
+
+    fn try_from(value: RawLightBlock) -> Result<Self, Self::Error> {
+        Ok(Self {
+            signed_header: value
+                .signed_header
+                .ok_or(tendermint::Kind::MissingHeader)?
+                .try_into()?,
+            validators: value
+                .validator_set
+                .ok_or(tendermint::Kind::MissingValidatorSet)?
+                .try_into()?,
+        })
+    }
+}
+
+impl From<LightBlock> for RawLightBlock {
+    fn from(value: LightBlock) -> Self {
+        RawLightBlock {
+            signed_header: Some(value.signed_header.into()),
+            validator_set: Some(value.validators.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+impl From<tendermint_testgen::TMLightBlock> for LightBlock {
+    fn from(value: tendermint_testgen::TMLightBlock) -> Self {
+        Self::new(value.signed_header, value.validators)
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Status {
     /// The light block has failed verification.
     Failed,
@@ -58,3 +106,248 @@ impl fmt::Debug for Status {
 
 /// A signed header contains both a `Header` and its corresponding `Commit`.
 pub type SignedHeader = TMSignedHeader;
+
+/// A validator set is a set of instances of `Validator`.
+pub type ValidatorSet = TMValidatorSet;
+
+#[extern_spec]
+impl<T, E> std::result::Result<T, E> {
+    #[pure]
+    #[ensures(matches!(*self, Ok(_)) == result)]
+    pub fn is_ok(&self) -> bool;
+}
+
+/// The fraction of the trusted validators' voting power which must have signed
+/// an untrusted header for the light client to skip to it.
+///
+/// A trust threshold is only valid if its denominator is non-zero and it lies
+/// in `[1/3, 1]`, which is checked upon construction and deserialization.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "RawTrustThresholdFraction")]
+#[invariant(Self::is_valid(self.numerator, self.denominator))]
+pub struct TrustThresholdFraction {
+    numerator: u64,
+    denominator: u64,
+}
+
+impl TrustThresholdFraction {
+    /// Constant for a trust threshold of 1/3.
+    pub const ONE_THIRD: Self = Self {
+        numerator: 1,
+        denominator: 3,
+    };
+
+    /// Constant for a trust threshold of 2/3.
+    pub const TWO_THIRDS: Self = Self {
+        numerator: 2,
+        denominator: 3,
+    };
+
+    /// Instantiate a trust threshold, failing if the fraction does not lie in `[1/3, 1]`
+    /// or if its denominator is zero.
+    #[ensures(result.is_ok() == Self::is_valid(numerator, denominator))]
+    pub fn new(numerator: u64, denominator: u64) -> Result<Self, Error> {
+        if Self::is_valid(numerator, denominator) {
+            Ok(Self {
+                numerator,
+                denominator,
+            })
+        } else {
+            Err(ErrorKind::InvalidTrustThreshold {
+                numerator,
+                denominator,
+            }
+            .into())
+        }
+    }
+
+    /// Whether `numerator / denominator` is a valid trust threshold, i.e. the
+    /// denominator is non-zero and `1/3 <= numerator / denominator <= 1`.
+    ///
+    /// The lower bound is checked as `3 * numerator >= denominator`, in `u128`
+    /// so that it cannot overflow.
+    #[pure]
+    pub fn is_valid(numerator: u64, denominator: u64) -> bool {
+        denominator != 0
+            && numerator <= denominator
+            && (numerator as u128) * 3 >= denominator as u128
+    }
+
+    /// The numerator of this fraction
+    #[pure]
+    pub fn numerator(&self) -> u64 {
+        self.numerator
+    }
+
+    /// The denominator of this fraction, which is never zero
+    #[pure]
+    #[requires(Self::is_valid(self.numerator, self.denominator))]
+    #[ensures(result != 0)]
+    #[ensures(result >= self.numerator)]
+    pub fn denominator(&self) -> u64 {
+        self.denominator
+    }
+
+    /// Whether `signed_power / total_power > numerator / denominator`.
+    ///
+    /// The comparison is performed as `signed_power * denominator > total_power * numerator`
+    /// in `u128`: as the product of two `u64` values is below `u128::MAX`, it can neither
+    /// overflow nor involve a division.
+    #[pure]
+    #[requires(Self::is_valid(self.numerator, self.denominator))]
+    #[ensures(signed_power == 0 ==> !result)]
+    #[ensures(signed_power > total_power ==> result)]
+    pub fn is_exceeded_by(&self, signed_power: u64, total_power: u64) -> bool {
+        (signed_power as u128) * (self.denominator as u128)
+            > (total_power as u128) * (self.numerator as u128)
+    }
+}
+
+impl Default for TrustThresholdFraction {
+    fn default() -> Self {
+        Self::ONE_THIRD
+    }
+}
+
+impl fmt::Debug for TrustThresholdFraction {
+    #[trusted]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "TrustThresholdFraction({}/{})", self.numerator, self.denominator)
+    }
+}
+
+/// Unvalidated trust threshold, as found in configuration files.
+#[derive(Deserialize)]
+struct RawTrustThresholdFraction {
+    numerator: u64,
+    denominator: u64,
+}
+
+impl TryFrom<RawTrustThresholdFraction> for TrustThresholdFraction {
+    type Error = Error;
+
+    fn try_from(value: RawTrustThresholdFraction) -> Result<Self, Self::Error> {
+        Self::new(value.numerator, value.denominator)
+    }
+}
+
+/// The subjective initialization parameters of a light client, obtained out of band.
+#[derive(Clone, PartialEq, Eq)]
+pub struct TrustOptions {
+    /// Identifier of the chain the trusted block belongs to
+    pub chain_id: ChainId,
+    /// How long a trusted block remains trusted
+    pub period: Duration,
+    /// Height of the trusted block
+    pub height: Height,
+    /// Hash of the header of the trusted block
+    pub hash: Hash,
+    /// Trust threshold used when skipping over blocks
+    pub trust_threshold: TrustThresholdFraction,
+}
+
+impl fmt::Debug for TrustOptions {
+    #[trusted]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TrustOptions")
+            .field("chain_id", &self.chain_id)
+            .field("period", &self.period)
+            .field("height", &self.height)
+            .field("hash", &self.hash)
+            .field("trust_threshold", &self.trust_threshold)
+            .finish()
+    }
+}
+
+/// Identifies a full node (primary or witness) the light client talks to.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PeerId(String);
+
+impl PeerId {
+    /// Constructs a new peer identifier
+    pub fn new(id: impl Into<String>) -> Self {
+        Self(id.into())
+    }
+
+    /// Returns the identifier as a string slice
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<&str> for PeerId {
+    fn from(id: &str) -> Self {
+        Self::new(id)
+    }
+}
+
+impl fmt::Display for PeerId {
+    #[trusted]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl fmt::Debug for PeerId {
+    #[trusted]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "PeerId({})", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trust_threshold_bounds() {
+        assert!(TrustThresholdFraction::new(1, 3).is_ok());
+        assert!(TrustThresholdFraction::new(2, 3).is_ok());
+        assert!(TrustThresholdFraction::new(1, 1).is_ok());
+        assert!(TrustThresholdFraction::new(u64::MAX, u64::MAX).is_ok());
+
+        assert!(TrustThresholdFraction::new(1, 0).is_err());
+        assert!(TrustThresholdFraction::new(0, 0).is_err());
+        assert!(TrustThresholdFraction::new(1, 4).is_err());
+        assert!(TrustThresholdFraction::new(4, 3).is_err());
+    }
+
+    #[test]
+    fn trust_threshold_comparison() {
+        let one_third = TrustThresholdFraction::ONE_THIRD;
+        assert!(!one_third.is_exceeded_by(1, 3));
+        assert!(one_third.is_exceeded_by(2, 3));
+        assert!(!one_third.is_exceeded_by(0, 0));
+
+        let two_thirds = TrustThresholdFraction::TWO_THIRDS;
+        assert!(!two_thirds.is_exceeded_by(2, 3));
+        assert!(two_thirds.is_exceeded_by(3, 4));
+    }
+
+    #[test]
+    fn trust_threshold_comparison_does_not_overflow() {
+        let threshold = TrustThresholdFraction::new(u64::MAX - 1, u64::MAX).unwrap();
+        assert!(threshold.is_exceeded_by(u64::MAX, u64::MAX));
+        assert!(!threshold.is_exceeded_by(u64::MAX - 1, u64::MAX));
+    }
+
+    #[test]
+    fn trust_threshold_serde() {
+        let threshold: TrustThresholdFraction =
+            serde_json::from_str(r#"{"numerator":2,"denominator":3}"#).unwrap();
+        assert_eq!(threshold, TrustThresholdFraction::TWO_THIRDS);
+        assert_eq!(
+            serde_json::to_string(&threshold).unwrap(),
+            r#"{"numerator":2,"denominator":3}"#
+        );
+
+        assert!(serde_json::from_str::<TrustThresholdFraction>(
+            r#"{"numerator":1,"denominator":0}"#
+        )
+        .is_err());
+        assert!(serde_json::from_str::<TrustThresholdFraction>(
+            r#"{"numerator":1,"denominator":5}"#
+        )
+        .is_err());
+    }
+}
@@ -6,3 +6,6 @@ mod block_on;
 pub use block_on::block_on;
 
 pub mod std_ext;
+
+#[cfg(test)]
+pub mod testing;
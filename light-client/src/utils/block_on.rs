@@ -0,0 +1,105 @@
+//! Run futures to completion from synchronous code
+
+use std::{future::Future, time::Duration};
+
+use crate::errors::{Error, ErrorKind};
+
+/// Run a future to completion on a fresh single-threaded runtime,
+/// optionally failing with `ErrorKind::Timeout` if it does not complete in time.
+///
+/// This function will block the caller until the given future has completed
+/// or the timeout has elapsed, which lets the (synchronous) verifier drive
+/// the asynchronous RPC client.
+pub fn block_on<F: Future>(timeout: Option<Duration>, f: F) -> Result<F::Output, Error> {
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| ErrorKind::Runtime(e.to_string()))?;
+
+    match timeout {
+        Some(timeout) => rt
+            .block_on(async { tokio::time::timeout(timeout, f).await })
+            .map_err(|_| ErrorKind::Timeout(timeout).into()),
+        None => Ok(rt.block_on(f)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::{Read, Write};
+    use std::net::{SocketAddr, TcpListener};
+    use std::thread;
+
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+
+    const STATUS_REQUEST: &str = r#"{"jsonrpc":"2.0","id":"1","method":"status","params":{}}"#;
+
+    const STATUS_RESPONSE: &str = r#"{"jsonrpc":"2.0","id":"1","result":{"node_info":{"network":"test-chain"},"sync_info":{"latest_block_height":"42"}}}"#;
+
+    /// Spawn a stub JSON-RPC server which answers a single request after the given delay.
+    fn stub_server(delay: Duration) -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = [0_u8; 1024];
+            let _ = stream.read(&mut buf);
+
+            thread::sleep(delay);
+
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                STATUS_RESPONSE.len(),
+                STATUS_RESPONSE
+            );
+            let _ = stream.write_all(response.as_bytes());
+        });
+
+        addr
+    }
+
+    /// Minimal async JSON-RPC call over HTTP/1.1, returning the raw response.
+    async fn rpc_status(addr: SocketAddr) -> std::io::Result<String> {
+        let mut stream = TcpStream::connect(addr).await?;
+        let request = format!(
+            "POST / HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            addr,
+            STATUS_REQUEST.len(),
+            STATUS_REQUEST
+        );
+        stream.write_all(request.as_bytes()).await?;
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await?;
+        Ok(response)
+    }
+
+    #[test]
+    fn completes_without_timeout() {
+        let addr = stub_server(Duration::from_millis(0));
+        let response = block_on(None, rpc_status(addr)).unwrap().unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.ends_with(STATUS_RESPONSE));
+    }
+
+    #[test]
+    fn completes_before_timeout() {
+        let addr = stub_server(Duration::from_millis(10));
+        let response = block_on(Some(Duration::from_secs(5)), rpc_status(addr))
+            .unwrap()
+            .unwrap();
+        assert!(response.contains(r#""latest_block_height":"42""#));
+    }
+
+    #[test]
+    fn times_out_on_unresponsive_server() {
+        let timeout = Duration::from_millis(50);
+        let addr = stub_server(Duration::from_secs(2));
+        let err = block_on(Some(timeout), rpc_status(addr)).unwrap_err();
+        assert_eq!(err.kind(), &ErrorKind::Timeout(timeout));
+    }
+}
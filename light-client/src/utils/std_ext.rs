@@ -16,6 +16,7 @@ use prusti_contracts::*;
 
     /// Stable version of `std::cmp::min_by_key`.
     #[trusted]
+    #[allow(dead_code)]
     pub fn min_by_key<A, B: Ord>(a: A, b: A, key: impl Fn(&A) -> B) -> A {
         if key(&a) <= key(&b) {
             a
@@ -0,0 +1,78 @@
+//! Light blocks and light clients shared by the unit tests
+
+use std::time::Duration;
+
+use tendermint_testgen::{Generator, LightChain, Validator};
+
+use crate::{
+    components::{
+        clock::FixedClock,
+        io::{AtHeight, Io, IoError, Provider},
+        verifier::ProdVerifier,
+    },
+    light_client::{LightClient, Options},
+    types::{LightBlock, PeerId, Time, TrustThresholdFraction},
+};
+
+/// Chain ID of the blocks served to the test light clients
+pub const CHAIN_ID: &str = "test-chain";
+
+/// Trusting period of the test light clients
+pub const TRUSTING_PERIOD: Duration = Duration::from_secs(3600);
+
+/// Light blocks at heights 1 to `length`, one second apart and signed by the
+/// validators with the given IDs. The last block is moved forward by
+/// `last_block_delay` seconds, so that different delays give conflicting
+/// last blocks.
+pub fn chain(validators: &[&str], length: u64, last_block_delay: u64) -> Vec<LightBlock> {
+    let validators = validators.iter().map(|id| Validator::new(id)).collect();
+    let mut descriptions = LightChain::new(validators, length).light_blocks().unwrap();
+    if let Some(last) = descriptions.pop() {
+        descriptions.push(last.time(length + last_block_delay));
+    }
+
+    descriptions
+        .iter()
+        .map(|description| description.generate().unwrap().into())
+        .collect()
+}
+
+/// A light client fetching the given blocks from a provider with the given
+/// name, with its clock set to `now`
+pub fn light_client(name: &str, blocks: Vec<LightBlock>, now: Time) -> LightClient {
+    let io = move |at: AtHeight| match at {
+        AtHeight::At(height) => blocks
+            .iter()
+            .find(|block| block.height() == height)
+            .cloned()
+            .ok_or(IoError::NotFound(at)),
+        AtHeight::Highest => blocks.last().cloned().ok_or(IoError::NotFound(at)),
+    };
+
+    light_client_with_io(name, Box::new(io), now)
+}
+
+/// A light client whose provider with the given name times out on every
+/// request, with its clock set to `now`
+pub fn unresponsive_light_client(name: &str, now: Time) -> LightClient {
+    let peer_id = PeerId::new(name);
+    let io = move |_: AtHeight| Err(IoError::Timeout(peer_id.clone(), Duration::from_secs(5)));
+
+    light_client_with_io(name, Box::new(io), now)
+}
+
+/// A light client fetching light blocks from the given `io`, through a
+/// provider with the given name, with its clock set to `now`
+pub fn light_client_with_io(name: &str, io: Box<dyn Io>, now: Time) -> LightClient {
+    LightClient::new(
+        Provider::new(PeerId::new(name), io),
+        Options {
+            chain_id: CHAIN_ID.parse().unwrap(),
+            trust_threshold: TrustThresholdFraction::default(),
+            trusting_period: TRUSTING_PERIOD,
+            clock_drift: Options::DEFAULT_CLOCK_DRIFT,
+        },
+        Box::new(ProdVerifier::default()),
+        Box::new(FixedClock::new(now)),
+    )
+}
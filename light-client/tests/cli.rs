@@ -0,0 +1,328 @@
+//! Runs the `light-client` binary against fixture-backed providers.
+//!
+//! Each test writes a chain of signed light blocks into the directories served
+//! to the binary as `file://` endpoints, one for the primary and one for the
+//! witness, then checks the output of its commands.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use tendermint::proto::Protobuf;
+use tendermint_testgen::{light_block::LightBlock as TestgenLightBlock, Generator, Validator};
+
+use tendermint_light_client::types::LightBlock;
+
+const CHAIN_ID: &str = "cli-test-chain";
+const CHAIN_LENGTH: u64 = 5;
+
+/// A chain of `CHAIN_LENGTH` blocks one second apart, signed by all
+/// validators, the first of which is dated at `start` seconds since the UNIX
+/// epoch. The last block is moved forward by `last_block_delay` seconds, so
+/// that different delays give conflicting last blocks.
+fn chain(start: u64, last_block_delay: u64) -> Vec<LightBlock> {
+    let validators = ["a", "b", "c", "d"]
+        .iter()
+        .map(|id| Validator::new(id))
+        .collect();
+
+    let mut descriptions = vec![TestgenLightBlock::new(validators)
+        .chain_id(CHAIN_ID)
+        .time(start)];
+    while (descriptions.len() as u64) < CHAIN_LENGTH {
+        let next = descriptions.last().unwrap().next().unwrap();
+        descriptions.push(next);
+    }
+    let last = descriptions.pop().unwrap();
+    let last_time = start + CHAIN_LENGTH - 1 + last_block_delay;
+    descriptions.push(last.time(last_time));
+
+    descriptions
+        .iter()
+        .map(|description| {
+            let generated = description.generate().unwrap();
+            LightBlock::new(generated.signed_header, generated.validators)
+        })
+        .collect()
+}
+
+/// Write the given blocks as fixtures, in the layout read by `DirectoryIo`
+fn write_fixtures(dir: &Path, blocks: &[LightBlock]) {
+    fs::create_dir_all(dir).unwrap();
+    for light_block in blocks {
+        let path = dir.join(format!("{}.pb", light_block.height()));
+        fs::write(path, light_block.clone().encode_vec().unwrap()).unwrap();
+    }
+}
+
+/// A fresh directory for the fixtures and home of a test
+struct Setup {
+    root: PathBuf,
+    chain: Vec<LightBlock>,
+}
+
+impl Setup {
+    /// Serve a chain starting five minutes ago from the primary, and the
+    /// same chain from the witness, except for its last block which is
+    /// delayed by `witness_delay` seconds
+    fn new(name: &str, witness_delay: u64) -> Self {
+        let root =
+            std::env::temp_dir().join(format!("light-client-cli-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+        let start = now.as_secs() - 300;
+
+        write_fixtures(&root.join("witness"), &chain(start, witness_delay));
+        let chain = chain(start, 0);
+        write_fixtures(&root.join("primary"), &chain);
+
+        Self { root, chain }
+    }
+
+    fn endpoint(&self, name: &str) -> String {
+        format!("file://{}", self.root.join(name).display())
+    }
+
+    fn hash(&self, height: u64) -> String {
+        self.chain[height as usize - 1]
+            .signed_header
+            .header
+            .hash()
+            .to_string()
+    }
+
+    fn run(&self, command: &str, args: &[&str]) -> Output {
+        let home = self.root.join("home");
+        Command::new(env!("CARGO_BIN_EXE_light-client"))
+            .arg(command)
+            .arg("--home")
+            .arg(&home)
+            .args(args)
+            .output()
+            .unwrap()
+    }
+
+    fn init(&self, height: u64, hash: &str) -> Output {
+        self.run(
+            "init",
+            &[
+                "--chain-id",
+                CHAIN_ID,
+                "--height",
+                &height.to_string(),
+                "--hash",
+                hash,
+                "--primary",
+                &self.endpoint("primary"),
+                "--witness",
+                &self.endpoint("witness"),
+            ],
+        )
+    }
+}
+
+impl Drop for Setup {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.root);
+    }
+}
+
+fn stdout(output: &Output) -> String {
+    assert!(
+        output.status.success(),
+        "command failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+fn stderr(output: &Output) -> String {
+    assert!(!output.status.success(), "command unexpectedly succeeded");
+    String::from_utf8(output.stderr.clone()).unwrap()
+}
+
+#[test]
+fn init_sync_status_inspect() {
+    let setup = Setup::new("sync", 0);
+
+    let output = stdout(&setup.init(1, &setup.hash(1)));
+    assert!(output.contains(&format!(
+        "initialized at height 1 with hash {}",
+        setup.hash(1)
+    )));
+
+    let output = stdout(&setup.run("status", &[]));
+    assert!(output.contains(&format!("highest trusted: 1 ({})", setup.hash(1))));
+    assert!(output.contains("highest verified: none"));
+
+    let output = stdout(&setup.run("sync", &["--height", "3"]));
+    assert!(output.contains(&format!("verified height 3 with hash {}", setup.hash(3))));
+
+    let output = stdout(&setup.run("sync", &[]));
+    assert!(output.contains(&format!("verified height 5 with hash {}", setup.hash(5))));
+
+    let output = stdout(&setup.run("status", &[]));
+    assert!(output.contains(&format!("highest trusted: 5 ({})", setup.hash(5))));
+
+    let output = stdout(&setup.run("inspect", &["--height", "5"]));
+    assert!(output.contains("status: trusted"));
+    assert!(output.contains(&format!("hash: {}", setup.hash(5))));
+    assert!(output.contains(&format!("last block id: {}", setup.hash(4))));
+    assert!(output.contains("4 of 4 signatures"));
+
+    // Blocks below the trusted one are reached by following the hash links
+    let setup = Setup::new("backward", 0);
+    stdout(&setup.init(4, &setup.hash(4)));
+    let output = stdout(&setup.run("sync", &["--height", "2"]));
+    assert!(output.contains(&format!("verified height 2 with hash {}", setup.hash(2))));
+}
+
+#[test]
+fn init_fails_on_hash_mismatch() {
+    let setup = Setup::new("mismatch", 0);
+
+    let error = stderr(&setup.init(1, &setup.hash(2)));
+    assert!(error.contains("does not match trusted hash"), "{}", error);
+
+    let error = stderr(&setup.run("status", &[]));
+    assert!(
+        error.contains("has the light client been initialized?"),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn init_fails_on_trusting_period_out_of_range() {
+    let setup = Setup::new("period", 0);
+
+    let output = setup.run(
+        "init",
+        &[
+            "--chain-id",
+            CHAIN_ID,
+            "--height",
+            "1",
+            "--hash",
+            &setup.hash(1),
+            "--trusting-period",
+            &u64::MAX.to_string(),
+            "--primary",
+            &setup.endpoint("primary"),
+            "--witness",
+            &setup.endpoint("witness"),
+        ],
+    );
+    let error = stderr(&output);
+    assert!(error.contains("time overflow"), "{}", error);
+}
+
+#[test]
+fn sync_detects_fork() {
+    let setup = Setup::new("fork", 1);
+
+    stdout(&setup.init(1, &setup.hash(1)));
+
+    let error = stderr(&setup.run("sync", &[]));
+    assert!(error.contains("fork detected"), "{}", error);
+
+    // The block of the primary was verified, but not trusted
+    let output = stdout(&setup.run("status", &[]));
+    assert!(output.contains(&format!("highest verified: 5 ({})", setup.hash(5))));
+}
+
+#[test]
+fn rejects_unsupported_endpoints() {
+    let setup = Setup::new("endpoint", 0);
+
+    let output = setup.run(
+        "init",
+        &[
+            "--chain-id",
+            CHAIN_ID,
+            "--height",
+            "1",
+            "--hash",
+            &setup.hash(1),
+            "--primary",
+            "tcp://127.0.0.1:26657",
+            "--witness",
+            &setup.endpoint("witness"),
+        ],
+    );
+    let error = stderr(&output);
+    assert!(error.contains("unsupported endpoint"), "{}", error);
+}
+
+/// Serve the commit and validators of the block at height 10 of a kvstore
+/// chain over HTTP, as the RPC endpoint of a full node would
+#[cfg(feature = "rpc-client")]
+fn rpc_server() -> String {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+
+    const COMMIT: &str = include_str!("support/rpc/commit_at_height_10.json");
+    const VALIDATORS: &str = include_str!("support/rpc/validators_at_height_10.json");
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = format!("http://{}", listener.local_addr().unwrap());
+
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+
+            let mut reader = BufReader::new(&stream);
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 2 {
+                line.clear();
+            }
+
+            let body = if request_line.contains("/validators") {
+                VALIDATORS
+            } else {
+                COMMIT
+            };
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            stream.write_all(response.as_bytes()).unwrap();
+        }
+    });
+
+    address
+}
+
+#[cfg(feature = "rpc-client")]
+#[test]
+fn init_from_rpc_endpoint() {
+    let setup = Setup::new("rpc", 0);
+    let endpoint = rpc_server();
+    let hash = "84F558CDCCA7D7D7B26F4BB02862C0DA72607F74937C549AEB0ACFDEC599C877";
+
+    let output = setup.run(
+        "init",
+        &[
+            "--chain-id",
+            "dockerchain",
+            "--height",
+            "10",
+            "--hash",
+            hash,
+            // The fixture dates back to December 2020
+            "--trusting-period",
+            &(100 * 365 * 24 * 60 * 60_u64).to_string(),
+            "--primary",
+            &endpoint,
+            "--witness",
+            &endpoint,
+        ],
+    );
+    let output = stdout(&output);
+    assert!(output.contains(&format!("initialized at height 10 with hash {}", hash)));
+}
@@ -0,0 +1,226 @@
+//! Property-based tests of the light store implementations.
+//!
+//! Random sequences of operations are run both on a store and on a trivially
+//! correct reference model, and the results of every query are compared.
+//! Proptest shrinks any divergence down to a minimal sequence of operations.
+//!
+//! To test another `LightStore` implementation, add a test calling
+//! `check_against_model` with a fresh instance of it.
+
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::fs;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use proptest::collection::vec;
+use proptest::prelude::*;
+
+use tendermint_testgen::{light_block::LightBlock as TestgenLightBlock, Generator, Validator};
+
+use tendermint_light_client::{
+    store::{file::FileStore, memory::MemoryStore, LightStore},
+    types::{Height, LightBlock, Status, Time},
+};
+
+/// Status of a light block, with a useful `Debug` output for shrunk failures
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum ModelStatus {
+    Failed,
+    Unverified,
+    Verified,
+    Trusted,
+}
+
+impl ModelStatus {
+    fn status(self) -> Status {
+        match self {
+            ModelStatus::Failed => Status::Failed,
+            ModelStatus::Unverified => Status::Unverified,
+            ModelStatus::Verified => Status::Verified,
+            ModelStatus::Trusted => Status::Trusted,
+        }
+    }
+}
+
+/// A light block is summarized by its height and a variant, which
+/// distinguishes different blocks at the same height
+type Block = (u64, u8);
+
+/// Operations on a light store
+#[derive(Clone, Debug)]
+enum Op {
+    Insert(Block, ModelStatus),
+    Update(Block, ModelStatus),
+    Remove(u64, ModelStatus),
+    Get(u64, ModelStatus),
+    Highest(ModelStatus),
+    Lowest(ModelStatus),
+    All(ModelStatus),
+}
+
+/// Reference model of the `LightStore` contract: at most one block per height
+/// and status
+#[derive(Debug, Default)]
+struct Model {
+    blocks: BTreeMap<(u64, ModelStatus), u8>,
+}
+
+impl Model {
+    /// Overrides the block with the same height and status only
+    fn insert(&mut self, (height, variant): Block, status: ModelStatus) {
+        self.blocks.insert((height, status), variant);
+    }
+
+    /// Moves the block from the statuses it is stored with to the given one
+    fn update(&mut self, (height, variant): Block, status: ModelStatus) {
+        self.blocks
+            .retain(|(h, _), v| !(*h == height && *v == variant));
+        self.blocks.insert((height, status), variant);
+    }
+
+    fn remove(&mut self, height: u64, status: ModelStatus) {
+        self.blocks.remove(&(height, status));
+    }
+
+    fn get(&self, height: u64, status: ModelStatus) -> Option<Block> {
+        self.all(status).into_iter().find(|(h, _)| *h == height)
+    }
+
+    fn all(&self, status: ModelStatus) -> Vec<Block> {
+        self.blocks
+            .iter()
+            .filter(|((_, s), _)| *s == status)
+            .map(|((height, _), variant)| (*height, *variant))
+            .collect()
+    }
+}
+
+/// The variant of a light block is its time, in seconds since the UNIX epoch
+fn light_block((height, variant): Block) -> LightBlock {
+    let generated = TestgenLightBlock::new(vec![Validator::new("a")])
+        .height(height)
+        .time(variant.into())
+        .generate()
+        .unwrap();
+    LightBlock::new(generated.signed_header, generated.validators)
+}
+
+fn summary(light_block: &LightBlock) -> Block {
+    let time = light_block.signed_header.header.time;
+    let variant = time.duration_since(Time::unix_epoch()).unwrap().as_secs();
+    (light_block.height().value(), u8::try_from(variant).unwrap())
+}
+
+fn status() -> impl Strategy<Value = ModelStatus> {
+    prop_oneof![
+        Just(ModelStatus::Failed),
+        Just(ModelStatus::Unverified),
+        Just(ModelStatus::Verified),
+        Just(ModelStatus::Trusted),
+    ]
+}
+
+/// Few heights and variants, so that operations often hit the same blocks
+fn block() -> impl Strategy<Value = Block> {
+    (1..6_u64, 0..3_u8)
+}
+
+fn op() -> impl Strategy<Value = Op> {
+    prop_oneof![
+        (block(), status()).prop_map(|(b, s)| Op::Insert(b, s)),
+        (block(), status()).prop_map(|(b, s)| Op::Update(b, s)),
+        (1..6_u64, status()).prop_map(|(h, s)| Op::Remove(h, s)),
+        (1..6_u64, status()).prop_map(|(h, s)| Op::Get(h, s)),
+        status().prop_map(Op::Highest),
+        status().prop_map(Op::Lowest),
+        status().prop_map(Op::All),
+    ]
+}
+
+/// Run the given operations on the given store and on the model, failing
+/// as soon as a query returns different results on each
+fn check_against_model(store: &mut dyn LightStore, ops: &[Op]) -> Result<(), TestCaseError> {
+    let mut model = Model::default();
+
+    for (index, op) in ops.iter().enumerate() {
+        match *op {
+            Op::Insert(block, status) => {
+                store.insert(light_block(block), status.status());
+                model.insert(block, status);
+            }
+            Op::Update(block, status) => {
+                store.update(&light_block(block), status.status());
+                model.update(block, status);
+            }
+            Op::Remove(height, status) => {
+                store.remove(Height::try_from(height).unwrap(), status.status());
+                model.remove(height, status);
+            }
+            Op::Get(height, status) => {
+                let got = store
+                    .get(Height::try_from(height).unwrap(), status.status())
+                    .as_ref()
+                    .map(summary);
+                prop_assert_eq!(got, model.get(height, status), "at op #{}: {:?}", index, op);
+            }
+            Op::Highest(status) => {
+                let got = store.highest(status.status()).as_ref().map(summary);
+                let expected = model.all(status).last().copied();
+                prop_assert_eq!(got, expected, "at op #{}: {:?}", index, op);
+            }
+            Op::Lowest(status) => {
+                let got = store.lowest(status.status()).as_ref().map(summary);
+                let expected = model.all(status).first().copied();
+                prop_assert_eq!(got, expected, "at op #{}: {:?}", index, op);
+            }
+            Op::All(status) => {
+                let got = store
+                    .all(status.status())
+                    .map(|lb| summary(&lb))
+                    .collect::<Vec<_>>();
+                prop_assert_eq!(got, model.all(status), "at op #{}: {:?}", index, op);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+proptest! {
+    #[test]
+    fn memory_store_matches_model(ops in vec(op(), 0..64)) {
+        check_against_model(&mut MemoryStore::new(), &ops)?;
+    }
+
+    #[test]
+    fn file_store_matches_model(ops in vec(op(), 0..64)) {
+        static CASE: AtomicUsize = AtomicUsize::new(0);
+        let root = std::env::temp_dir().join(format!(
+            "light-store-proptest-{}-{}",
+            std::process::id(),
+            CASE.fetch_add(1, Ordering::SeqCst)
+        ));
+
+        let mut store = FileStore::open(&root).unwrap();
+        let outcome = check_against_model(&mut store, &ops);
+
+        // Reopening the store must neither lose nor alter any of its blocks
+        let reopened = FileStore::open(&root).unwrap();
+        let contents = |store: &FileStore| {
+            [
+                ModelStatus::Failed,
+                ModelStatus::Unverified,
+                ModelStatus::Verified,
+                ModelStatus::Trusted,
+            ]
+            .iter()
+            .map(|status| store.all(status.status()).map(|lb| summary(&lb)).collect())
+            .collect::<Vec<Vec<Block>>>()
+        };
+        let (before, after) = (contents(&store), contents(&reopened));
+
+        fs::remove_dir_all(&root).unwrap();
+        outcome?;
+        prop_assert_eq!(before, after);
+    }
+}
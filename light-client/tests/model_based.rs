@@ -0,0 +1,305 @@
+//! Replays the traces produced by the TLA+ model of the light client
+//! (see `[LCV-DIST-STORE.1]` and the light client verification specification)
+//! against the verifier and the in-memory light store.
+//!
+//! The traces under `tests/support/model_based/single_step` are a few of the
+//! counterexamples found by the Apalache model checker for the tests of the
+//! upstream model, as translated to light blocks by Jsonatr and Testgen: a
+//! successful verification, a block failing verification after a successful
+//! one, a header from the future, a trusted block out of its trusting period,
+//! a non-monotonic height, and a height verified several times. Each one starts
+//! from a trusted block, followed by the blocks returned by the provider, each
+//! with the current time and the expected verdict of verifying it against the
+//! latest block verified so far. Every step checks the verdict of the verifier
+//! against the model's, then compares the contents of the light store with the
+//! ones the model expects from its verdicts.
+
+use std::collections::BTreeSet;
+use std::convert::TryFrom;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use tendermint::proto::crypto::{public_key::Sum, PublicKey as RawPublicKey};
+use tendermint::proto::serializers;
+use tendermint::proto::types::{SignedHeader as RawSignedHeader, Validator as RawValidator};
+use tendermint::validator;
+
+use tendermint_light_client::{
+    components::verifier::{ProdVerifier, Verdict, Verifier},
+    light_client::Options,
+    store::{memory::MemoryStore, LightStore},
+    types::{LightBlock, SignedHeader, Status, Time, TrustThresholdFraction},
+};
+
+const TRACES_DIR: &str = "tests/support/model_based/single_step";
+
+/// A trace of the TLA+ model
+#[derive(Deserialize)]
+struct Trace {
+    description: String,
+    initial: Initial,
+    input: Vec<Step>,
+}
+
+/// Initial state of the light client
+#[derive(Deserialize)]
+struct Initial {
+    signed_header: RawSignedHeader,
+    next_validator_set: ModelValidatorSet,
+    /// Trusting period, in nanoseconds
+    #[serde(with = "serializers::from_str")]
+    trusting_period: u64,
+}
+
+/// A block returned by the provider, and the expected outcome of its verification
+#[derive(Deserialize)]
+struct Step {
+    block: ModelBlock,
+    now: Time,
+    verdict: ModelVerdict,
+}
+
+#[derive(Deserialize)]
+struct ModelBlock {
+    signed_header: RawSignedHeader,
+    validator_set: ModelValidatorSet,
+    next_validator_set: ModelValidatorSet,
+}
+
+#[derive(Deserialize)]
+struct ModelValidatorSet {
+    validators: Vec<ModelValidator>,
+}
+
+/// A validator, whose public key is encoded as the bare `{ "type", "value" }` object
+#[derive(Deserialize)]
+struct ModelValidator {
+    #[serde(with = "serializers::bytes::hexstring")]
+    address: Vec<u8>,
+    pub_key: Sum,
+    #[serde(with = "serializers::from_str")]
+    voting_power: i64,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+enum ModelVerdict {
+    Success,
+    NotEnoughTrust,
+    Invalid,
+}
+
+impl ModelValidatorSet {
+    fn to_validator_set(&self) -> validator::Set {
+        let validators = self
+            .validators
+            .iter()
+            .map(|v| {
+                validator::Info::try_from(RawValidator {
+                    address: v.address.clone(),
+                    pub_key: Some(RawPublicKey {
+                        sum: Some(v.pub_key.clone()),
+                    }),
+                    voting_power: v.voting_power,
+                    proposer_priority: 0,
+                })
+                .unwrap()
+            })
+            .collect();
+
+        validator::Set::without_proposer(validators).unwrap()
+    }
+}
+
+impl ModelBlock {
+    fn light_block(&self) -> LightBlock {
+        LightBlock::new(
+            SignedHeader::try_from(self.signed_header.clone()).unwrap(),
+            self.validator_set.to_validator_set(),
+        )
+    }
+
+    /// The block as trusted by the model, which only keeps track of the next
+    /// validators of the trusted block: those are the ones the signers of a
+    /// later block must overlap with.
+    fn trusted_block(&self) -> LightBlock {
+        LightBlock::new(
+            SignedHeader::try_from(self.signed_header.clone()).unwrap(),
+            self.next_validator_set.to_validator_set(),
+        )
+    }
+}
+
+/// Contents of a light store: the height and status of each of its blocks
+type Contents = BTreeSet<(u64, Status)>;
+
+fn store_contents(light_store: &MemoryStore) -> Contents {
+    let mut contents = BTreeSet::new();
+
+    for status in &[
+        Status::Trusted,
+        Status::Verified,
+        Status::Unverified,
+        Status::Failed,
+    ] {
+        for light_block in light_store.all(status.clone()) {
+            contents.insert((light_block.height().value(), status.clone()));
+        }
+    }
+
+    contents
+}
+
+/// The light store of the model, which starts with the trusted block, then
+/// keeps every block returned by the provider with the status given by the
+/// verdict of its latest verification.
+struct ModelStore {
+    blocks: Vec<(LightBlock, Status)>,
+}
+
+impl ModelStore {
+    fn new(trusted: LightBlock) -> Self {
+        Self {
+            blocks: vec![(trusted, Status::Trusted)],
+        }
+    }
+
+    fn record(&mut self, light_block: &LightBlock, verdict: ModelVerdict) {
+        let status = match verdict {
+            ModelVerdict::Success => Status::Verified,
+            ModelVerdict::NotEnoughTrust => Status::Unverified,
+            ModelVerdict::Invalid => Status::Failed,
+        };
+
+        match self.blocks.iter_mut().find(|(lb, _)| lb == light_block) {
+            Some(entry) => entry.1 = status,
+            None => self.blocks.push((light_block.clone(), status)),
+        }
+    }
+
+    fn contents(&self) -> Contents {
+        self.blocks
+            .iter()
+            .map(|(light_block, status)| (light_block.height().value(), status.clone()))
+            .collect()
+    }
+}
+
+fn model_verdict(verdict: &Verdict) -> ModelVerdict {
+    match verdict {
+        Verdict::Success => ModelVerdict::Success,
+        Verdict::NotEnoughTrust(_) => ModelVerdict::NotEnoughTrust,
+        Verdict::Invalid(_) => ModelVerdict::Invalid,
+    }
+}
+
+fn status_name(status: &Status) -> &'static str {
+    match status {
+        Status::Failed => "failed",
+        Status::Unverified => "unverified",
+        Status::Verified => "verified",
+        Status::Trusted => "trusted",
+    }
+}
+
+/// Line-by-line diff of the expected and actual contents of the light store
+fn store_diff(expected: &Contents, actual: &Contents) -> Option<String> {
+    if expected == actual {
+        return None;
+    }
+
+    let mut diff = String::new();
+    for entry in expected.union(actual) {
+        let sign = match (expected.contains(entry), actual.contains(entry)) {
+            (true, true) => ' ',
+            (true, false) => '-',
+            _ => '+',
+        };
+        diff.push_str(&format!(
+            "{} {}: {}\n",
+            sign,
+            entry.0,
+            status_name(&entry.1)
+        ));
+    }
+
+    Some(diff)
+}
+
+/// Replay the given trace, returning a description of the first divergence
+/// from it, if any
+fn replay(trace: &Trace) -> Result<(), String> {
+    let initial = SignedHeader::try_from(trace.initial.signed_header.clone()).unwrap();
+    let options = Options {
+        chain_id: initial.header.chain_id.clone(),
+        trust_threshold: TrustThresholdFraction::default(),
+        trusting_period: Duration::from_nanos(trace.initial.trusting_period),
+        clock_drift: Duration::from_secs(0),
+    };
+    let verifier = ProdVerifier::default();
+
+    let mut trusted = LightBlock::new(initial, trace.initial.next_validator_set.to_validator_set());
+    let mut model_store = ModelStore::new(trusted.clone());
+    let mut light_store = MemoryStore::new();
+    light_store.insert(trusted.clone(), Status::Trusted);
+
+    for (index, step) in trace.input.iter().enumerate() {
+        let untrusted = step.block.light_block();
+        let verdict = verifier.verify(&untrusted, &trusted, &options, step.now);
+
+        let status = match verdict {
+            Verdict::Success => Status::Verified,
+            Verdict::NotEnoughTrust(_) => Status::Unverified,
+            Verdict::Invalid(_) => Status::Failed,
+        };
+        light_store.update(&untrusted, status);
+        model_store.record(&untrusted, step.verdict);
+
+        let diff = store_diff(&model_store.contents(), &store_contents(&light_store));
+        if model_verdict(&verdict) != step.verdict || diff.is_some() {
+            return Err(format!(
+                "step {}: expected {:?}, verifier returned {:?}, \
+                 light store (- expected, + actual):\n{}",
+                index,
+                step.verdict,
+                verdict,
+                diff.unwrap_or_else(|| "  unchanged\n".to_string())
+            ));
+        }
+
+        if verdict == Verdict::Success {
+            trusted = step.block.trusted_block();
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn model_based_traces() {
+    let mut traces = fs::read_dir(Path::new(TRACES_DIR))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension() == Some(OsStr::new("json")))
+        .collect::<Vec<_>>();
+    traces.sort();
+    assert!(!traces.is_empty(), "no traces found in {}", TRACES_DIR);
+
+    let failures = traces
+        .iter()
+        .filter_map(|path| {
+            let trace: Trace = serde_json::from_str(&fs::read_to_string(path).unwrap())
+                .unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+
+            replay(&trace)
+                .err()
+                .map(|e| format!("{} ({}): {}", path.display(), trace.description, e))
+        })
+        .collect::<Vec<_>>();
+
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+}
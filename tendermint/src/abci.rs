@@ -0,0 +1,7 @@
+//! Application BlockChain Interface (ABCI) types
+//!
+//! <https://github.com/tendermint/spec/tree/master/spec/abci>
+
+pub mod transaction;
+
+pub use self::transaction::Transaction;
@@ -0,0 +1,320 @@
+//! Transactions
+
+use crate::merkle::{self, proofs_from_byte_vectors, simple_hash_from_byte_vectors};
+use crate::{serializers, Error, Kind};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sha2::{Digest, Sha256};
+use std::convert::{TryFrom, TryInto};
+use std::fmt::{self, Debug, Display};
+use std::str::FromStr;
+use subtle_encoding::{Encoding, Hex};
+use tendermint_proto::types::{Data as RawData, TxProof as RawProof};
+use tendermint_proto::Protobuf;
+
+extern crate prusti_contracts;
+use prusti_contracts::*;
+
+/// Size of a transaction hash in bytes
+pub const HASH_LENGTH: usize = 32;
+
+/// Transaction hashes: the SHA-256 of the transaction bytes
+#[derive(Copy, Clone, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub struct Hash([u8; HASH_LENGTH]);
+
+impl Hash {
+    /// Create a new transaction hash from raw bytes
+    pub fn new(bytes: [u8; HASH_LENGTH]) -> Hash {
+        Hash(bytes)
+    }
+
+    /// Borrow the transaction hash as a byte slice
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0[..]
+    }
+}
+
+impl AsRef<[u8]> for Hash {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl Debug for Hash {
+    #[trusted]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "transaction::Hash({})", self)
+    }
+}
+
+impl Display for Hash {
+    #[trusted]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in &self.0 {
+            write!(f, "{:02X}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Hash {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut bytes = [0u8; HASH_LENGTH];
+        let length = Hex::upper_case()
+            .decode_to_slice(s.as_bytes(), &mut bytes)
+            .or_else(|_| Hex::lower_case().decode_to_slice(s.as_bytes(), &mut bytes))
+            .map_err(|e| Kind::Parse.context(e))?;
+        if length != HASH_LENGTH {
+            return Err(Kind::InvalidHashSize.into());
+        }
+        Ok(Hash(bytes))
+    }
+}
+
+impl<'de> Deserialize<'de> for Hash {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        serializers::from_str::deserialize(deserializer)
+    }
+}
+
+impl Serialize for Hash {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializers::from_str::serialize(self, serializer)
+    }
+}
+
+/// Transactions are arbitrary byte arrays whose contents are validated by the
+/// application, and which are included in blocks as-is.
+///
+/// They are encoded as base64 strings in JSON.
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub struct Transaction(#[serde(with = "serializers::bytes::base64string")] Vec<u8>);
+
+impl From<Vec<u8>> for Transaction {
+    fn from(bytes: Vec<u8>) -> Transaction {
+        Transaction(bytes)
+    }
+}
+
+impl From<Transaction> for Vec<u8> {
+    fn from(tx: Transaction) -> Vec<u8> {
+        tx.0
+    }
+}
+
+impl Transaction {
+    /// Borrow the contents of this transaction as a byte slice
+    pub fn as_bytes(&self) -> &[u8] {
+        self.0.as_slice()
+    }
+
+    /// Compute the hash of this transaction
+    pub fn hash(&self) -> Hash {
+        let mut bytes = [0u8; HASH_LENGTH];
+        bytes.copy_from_slice(&Sha256::digest(self.as_bytes()));
+        Hash(bytes)
+    }
+}
+
+impl AsRef<[u8]> for Transaction {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+/// The transactions included in a block
+///
+/// <https://github.com/tendermint/spec/blob/d46cd7f573a2c6a2399fcab2cde981330aa63f37/spec/core/data_structures.md#data>
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Data {
+    #[serde(with = "serializers::nullable")]
+    txs: Vec<Transaction>,
+}
+
+impl Protobuf<RawData> for Data {}
+
+impl From<RawData> for Data {
+    fn from(value: RawData) -> Self {
+        Data {
+            txs: value.txs.into_iter().map(Transaction::from).collect(),
+        }
+    }
+}
+
+impl From<Data> for RawData {
+    fn from(value: Data) -> Self {
+        RawData {
+            txs: value.txs.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl Data {
+    /// Create transaction data from the given transactions
+    pub fn new(txs: Vec<Transaction>) -> Data {
+        Data { txs }
+    }
+
+    /// Iterate over the transactions
+    pub fn iter(&self) -> std::slice::Iter<'_, Transaction> {
+        self.txs.iter()
+    }
+
+    /// Number of transactions
+    pub fn len(&self) -> usize {
+        self.txs.len()
+    }
+
+    /// Whether there are no transactions
+    pub fn is_empty(&self) -> bool {
+        self.txs.is_empty()
+    }
+
+    /// Compute the hash committed to by `Header::data_hash`: the Merkle root
+    /// of the hashes of the transactions.
+    pub fn hash(&self) -> crate::Hash {
+        crate::Hash::Sha256(simple_hash_from_byte_vectors(self.tx_hashes()))
+    }
+
+    /// Compute a proof of inclusion of the transaction at the given index
+    /// in this data, or `None` if the index is out of range.
+    pub fn proof(&self, index: usize) -> Option<Proof> {
+        let data = self.txs.get(index)?.clone();
+        let (root_hash, mut proofs) = proofs_from_byte_vectors(&self.tx_hashes());
+
+        Some(Proof {
+            root_hash: crate::Hash::Sha256(root_hash),
+            data,
+            proof: proofs.swap_remove(index),
+        })
+    }
+
+    fn tx_hashes(&self) -> Vec<Vec<u8>> {
+        self.txs
+            .iter()
+            .map(|tx| tx.hash().as_bytes().to_vec())
+            .collect()
+    }
+}
+
+impl AsRef<[Transaction]> for Data {
+    fn as_ref(&self) -> &[Transaction] {
+        self.txs.as_slice()
+    }
+}
+
+/// Proof that a transaction is included in the transaction data of a block,
+/// whose header commits to `root_hash` as its `data_hash`.
+///
+/// <https://github.com/tendermint/tendermint/blob/v0.34.0/types/tx.go#L84-L122>
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Proof {
+    /// Merkle root of the transaction data
+    pub root_hash: crate::Hash,
+
+    /// The transaction
+    pub data: Transaction,
+
+    /// Proof of inclusion of the hash of the transaction
+    pub proof: merkle::Proof,
+}
+
+impl Protobuf<RawProof> for Proof {}
+
+impl TryFrom<RawProof> for Proof {
+    type Error = Error;
+
+    fn try_from(value: RawProof) -> Result<Self, Self::Error> {
+        Ok(Proof {
+            root_hash: value.root_hash.try_into()?,
+            data: value.data.into(),
+            proof: value.proof.ok_or(Kind::InvalidProof)?.try_into()?,
+        })
+    }
+}
+
+impl From<Proof> for RawProof {
+    fn from(value: Proof) -> Self {
+        RawProof {
+            root_hash: value.root_hash.into(),
+            data: value.data.into(),
+            proof: Some(value.proof.into()),
+        }
+    }
+}
+
+impl Proof {
+    /// Check that this proves the inclusion of the transaction
+    /// in the block whose header has the given data hash.
+    pub fn validate(&self, data_hash: &crate::Hash) -> Result<(), Error> {
+        if &self.root_hash != data_hash {
+            return Err(Kind::InvalidProof
+                .context(format!(
+                    "proof is for data hash {}, expected {}",
+                    self.root_hash, data_hash
+                ))
+                .into());
+        }
+
+        let root_hash = match self.root_hash {
+            crate::Hash::Sha256(hash) => hash,
+            crate::Hash::None => return Err(Kind::InvalidProof.context("empty root hash").into()),
+        };
+
+        self.proof.verify(&root_hash, self.data.hash().as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data() -> Data {
+        Data::new(
+            (0..5)
+                .map(|i| Transaction::from(format!("tx{}", i).into_bytes()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn transaction_hash() {
+        let tx = Transaction::from(b"tx".to_vec());
+        let hash = tx.hash();
+
+        assert_eq!(
+            hash.to_string(),
+            "1B5B9CCB3E8D006A5230DE9BDA23FF91EDC794D4F56410560830B418528E446C"
+        );
+        assert_eq!(hash.to_string().parse::<Hash>().unwrap(), hash);
+        assert!(hash.to_string()[..8].parse::<Hash>().is_err());
+    }
+
+    #[test]
+    fn empty_data_hash() {
+        assert_eq!(
+            Data::default().hash().to_string(),
+            "E3B0C44298FC1C149AFBF4C8996FB92427AE41E4649B934CA495991B7852B855"
+        );
+    }
+
+    #[test]
+    fn inclusion_proofs() {
+        let data = data();
+        let data_hash = data.hash();
+
+        for (index, tx) in data.iter().enumerate() {
+            let proof = data.proof(index).unwrap();
+            assert_eq!(&proof.data, tx);
+            assert!(proof.validate(&data_hash).is_ok());
+            assert!(proof.validate(&Data::default().hash()).is_err());
+
+            let mut forged = proof.clone();
+            forged.data = Transaction::from(b"forged".to_vec());
+            assert!(forged.validate(&data_hash).is_err());
+        }
+
+        assert!(data.proof(5).is_none());
+    }
+}
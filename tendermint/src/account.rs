@@ -0,0 +1,199 @@
+//! Tendermint accounts
+
+use crate::error::{Error, Kind};
+use crate::public_key::Ed25519;
+#[cfg(feature = "secp256k1")]
+use crate::public_key::Secp256k1;
+use crate::PublicKey;
+#[cfg(feature = "secp256k1")]
+use ripemd160::Ripemd160;
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+use sha2::{Digest, Sha256};
+use std::convert::{TryFrom, TryInto};
+use std::fmt::{self, Debug, Display};
+use std::str::FromStr;
+use subtle_encoding::{Encoding, Hex};
+use tendermint_proto::Protobuf;
+
+extern crate prusti_contracts;
+use prusti_contracts::*;
+
+/// Size of an account ID in bytes
+pub const LENGTH: usize = 20;
+
+/// Account IDs
+#[derive(Copy, Clone, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub struct Id([u8; LENGTH]); // JSON custom serialization for priv_validator_key.json
+
+impl Protobuf<Vec<u8>> for Id {}
+
+impl TryFrom<Vec<u8>> for Id {
+    type Error = Error;
+
+    fn try_from(value: Vec<u8>) -> Result<Self, Self::Error> {
+        if value.len() != LENGTH {
+            return Err(Kind::InvalidAccountIdLength.into());
+        }
+        let mut slice: [u8; LENGTH] = [0; LENGTH];
+        slice.copy_from_slice(&value[..]);
+        Ok(Id(slice))
+    }
+}
+
+impl From<Id> for Vec<u8> {
+    fn from(value: Id) -> Self {
+        value.as_bytes().to_vec()
+    }
+}
+
+impl Id {
+    /// Create a new account ID from raw bytes
+    pub fn new(bytes: [u8; LENGTH]) -> Id {
+        Id(bytes)
+    }
+
+    /// Borrow the account ID as a byte slice
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0[..]
+    }
+}
+
+impl AsRef<[u8]> for Id {
+    fn as_ref(&self) -> &[u8] {
+        self.0.as_ref()
+    }
+}
+
+impl Debug for Id {
+    #[trusted]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "account::Id({})", self)
+    }
+}
+
+impl Display for Id {
+    #[trusted]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in &self.0 {
+            write!(f, "{:02X}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+/// Decode account ID from hex
+impl FromStr for Id {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Accept either upper or lower case hex
+        let bytes = Hex::upper_case()
+            .decode(s)
+            .or_else(|_| Hex::lower_case().decode(s))
+            .map_err(|e| Kind::Parse.context(e))?;
+
+        bytes.try_into()
+    }
+}
+
+impl<'de> Deserialize<'de> for Id {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Self::from_str(&s).map_err(|e| D::Error::custom(format!("{}", e)))
+    }
+}
+
+impl Serialize for Id {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_string().serialize(serializer)
+    }
+}
+
+/// Derive the account ID of an Ed25519 public key:
+/// the first 20 bytes of the SHA-256 of the public key.
+impl From<Ed25519> for Id {
+    fn from(pk: Ed25519) -> Id {
+        let digest = Sha256::digest(pk.as_bytes());
+        let mut bytes = [0u8; LENGTH];
+        bytes.copy_from_slice(&digest[..LENGTH]);
+        Id(bytes)
+    }
+}
+
+/// Derive the account ID of a public key, according to its type.
+impl From<PublicKey> for Id {
+    fn from(pk: PublicKey) -> Id {
+        match pk {
+            PublicKey::Ed25519(pk) => Id::from(pk),
+            #[cfg(feature = "secp256k1")]
+            PublicKey::Secp256k1(pk) => Id::from(pk),
+        }
+    }
+}
+
+/// Derive the account ID of a secp256k1 public key:
+/// the RIPEMD160 of the SHA-256 of the compressed public key.
+#[cfg(feature = "secp256k1")]
+impl From<Secp256k1> for Id {
+    fn from(pk: Secp256k1) -> Id {
+        let sha_digest = Sha256::digest(&pk.to_bytes());
+        let ripemd_digest = Ripemd160::digest(&sha_digest[..]);
+        let mut bytes = [0u8; LENGTH];
+        bytes.copy_from_slice(&ripemd_digest[..LENGTH]);
+        Id(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hex_round_trip() {
+        let id_hex = "21FE31DFA154A261626BF854046FD2271B7BED4B";
+        let id = Id::from_str(id_hex).unwrap();
+
+        assert_eq!(id.to_string(), id_hex);
+        assert_eq!(Id::from_str(&id_hex.to_lowercase()).unwrap(), id);
+        assert_eq!(
+            serde_json::from_str::<Id>(&format!("\"{}\"", id_hex)).unwrap(),
+            id
+        );
+    }
+
+    #[test]
+    fn invalid_ids_are_rejected() {
+        assert!(Id::from_str("21FE31DF").is_err());
+        assert!(Id::from_str("ZZFE31DFA154A261626BF854046FD2271B7BED4B").is_err());
+        assert!(Id::try_from(vec![0u8; LENGTH + 1]).is_err());
+    }
+
+    #[test]
+    fn ed25519_id() {
+        // RFC 8032 test vector 1
+        let pk_bytes = Hex::lower_case()
+            .decode("d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a")
+            .unwrap();
+        let pk = Ed25519::from_bytes(&pk_bytes).unwrap();
+
+        assert_eq!(
+            Id::from(pk).to_string(),
+            "21FE31DFA154A261626BF854046FD2271B7BED4B"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "secp256k1")]
+    fn secp256k1_id() {
+        // The secp256k1 generator point, ie. the public key of the secret key 1
+        let pk_bytes = Hex::upper_case()
+            .decode("0279BE667EF9DCBBAC55A06295CE870B07029BFCDB2DCE28D959F2815B16F81798")
+            .unwrap();
+        let pk = Secp256k1::from_sec1_bytes(&pk_bytes).unwrap();
+
+        assert_eq!(
+            Id::from(pk).to_string(),
+            "751E76E8199196D454941C45D1B3A323F1433BD6"
+        );
+    }
+}
@@ -1,10 +1,223 @@
 //! Blocks within the chains of a Tendermint network
 
+mod commit;
+pub mod commit_sig;
 mod height;
 pub mod header;
+mod id;
+mod meta;
+pub mod parts;
+mod round;
 pub mod signed_header;
+mod size;
 
 pub use self::{
+    commit::*,
+    commit_sig::*,
     height::*,
     header::Header,
+    id::{Id, ParseId},
+    meta::Meta,
+    round::*,
+    size::Size,
 };
+use crate::{abci::transaction, evidence, Error, Kind};
+use parts::PartSet;
+use prost::Message;
+use serde::{Deserialize, Serialize};
+use std::convert::{TryFrom, TryInto};
+use tendermint_proto::types::Block as RawBlock;
+use tendermint_proto::Protobuf;
+
+/// Blocks consist of a header, transactions, votes (the commit), and a list of
+/// evidence of malfeasance (i.e. signing conflicting votes).
+///
+/// <https://github.com/tendermint/spec/blob/d46cd7f573a2c6a2399fcab2cde981330aa63f37/spec/core/data_structures.md#block>
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "RawBlock", into = "RawBlock")]
+#[non_exhaustive]
+pub struct Block {
+    /// Block header
+    pub header: Header,
+
+    /// Transaction data
+    pub data: transaction::Data,
+
+    /// Evidence of malfeasance
+    pub evidence: evidence::Data,
+
+    /// Last commit, absent for the first block
+    pub last_commit: Option<Commit>,
+}
+
+impl Protobuf<RawBlock> for Block {}
+
+impl TryFrom<RawBlock> for Block {
+    type Error = Error;
+
+    fn try_from(value: RawBlock) -> Result<Self, Self::Error> {
+        let header: Header = value.header.ok_or(Kind::MissingHeader)?.try_into()?;
+        // The Go implementation encodes the absent last commit of the first block
+        // as an empty commit
+        let last_commit = value
+            .last_commit
+            .map(TryInto::try_into)
+            .transpose()?
+            .filter(|c| c != &Commit::default());
+
+        Block::new(
+            header,
+            value.data.unwrap_or_default().into(),
+            value.evidence.unwrap_or_default().try_into()?,
+            last_commit,
+        )
+    }
+}
+
+impl From<Block> for RawBlock {
+    fn from(value: Block) -> Self {
+        RawBlock {
+            header: Some(value.header.into()),
+            data: Some(value.data.into()),
+            evidence: Some(value.evidence.into()),
+            last_commit: Some(value.last_commit.unwrap_or_default().into()),
+        }
+    }
+}
+
+impl Block {
+    /// Builds a new block, checking that the header commits to its contents
+    pub fn new(
+        header: Header,
+        data: transaction::Data,
+        evidence: evidence::Data,
+        last_commit: Option<Commit>,
+    ) -> Result<Self, Error> {
+        let is_first_block = header.height.value() == 1;
+        if last_commit.is_none() && !is_first_block {
+            return Err(Kind::InvalidBlock
+                .context("last_commit is empty on non-first block")
+                .into());
+        }
+        if last_commit.is_some() && is_first_block {
+            return Err(Kind::InvalidBlock
+                .context("last_commit is filled on first block")
+                .into());
+        }
+
+        let data_hash = data.hash();
+        if header.data_hash != data_hash {
+            return Err(Kind::InvalidBlock
+                .context(format!(
+                    "wrong data hash: expected {}, got {}",
+                    header.data_hash, data_hash
+                ))
+                .into());
+        }
+
+        let last_commit_hash = last_commit.clone().unwrap_or_default().hash();
+        if header.last_commit_hash != last_commit_hash {
+            return Err(Kind::InvalidBlock
+                .context(format!(
+                    "wrong last commit hash: expected {}, got {}",
+                    header.last_commit_hash, last_commit_hash
+                ))
+                .into());
+        }
+
+        Ok(Block {
+            header,
+            data,
+            evidence,
+            last_commit,
+        })
+    }
+
+    /// Protobuf encoding of this block, as split into parts for gossiping
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let raw = RawBlock::from(self.clone());
+        let mut buf = Vec::with_capacity(raw.encoded_len());
+        raw.encode(&mut buf).expect("buffer has enough capacity");
+        buf
+    }
+
+    /// The part set of this block
+    pub fn part_set(&self) -> Result<PartSet, Error> {
+        PartSet::from_data(&self.to_bytes())
+    }
+
+    /// The ID of this block: the hash of its header, together with
+    /// the header of its part set.
+    pub fn id(&self) -> Result<Id, Error> {
+        Ok(Id {
+            hash: self.header.hash(),
+            part_set_header: self.part_set()?.header(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abci::Transaction;
+    use crate::{account, Hash, Time};
+
+    fn header(height: u32, data: &transaction::Data, last_commit: &Option<Commit>) -> Header {
+        Header {
+            version: Default::default(),
+            chain_id: "test-chain".parse().unwrap(),
+            height: Height::from(height),
+            time: Time::unix_epoch(),
+            last_block_id: None,
+            last_commit_hash: last_commit.clone().unwrap_or_default().hash(),
+            data_hash: data.hash(),
+            validators_hash: Hash::None,
+            next_validators_hash: Hash::None,
+            consensus_hash: Hash::None,
+            app_hash: vec![],
+            last_results_hash: Hash::None,
+            evidence_hash: Hash::None,
+            proposer_address: account::Id::new([0; 20]),
+        }
+    }
+
+    fn data() -> transaction::Data {
+        transaction::Data::new(vec![
+            Transaction::from(b"tx1".to_vec()),
+            Transaction::from(b"tx2".to_vec()),
+        ])
+    }
+
+    #[test]
+    fn protobuf_round_trip() {
+        let data = data();
+        let block = Block::new(header(1, &data, &None), data, Default::default(), None).unwrap();
+
+        let decoded = Block::decode_vec(&block.to_bytes()).unwrap();
+        assert_eq!(decoded, block);
+
+        let id = block.id().unwrap();
+        assert_eq!(id.hash, block.header.hash());
+        assert_eq!(id.part_set_header.total, 1);
+    }
+
+    #[test]
+    fn rejects_mismatching_hashes() {
+        let data = data();
+
+        let mut wrong_data_hash = header(1, &data, &None);
+        wrong_data_hash.data_hash = transaction::Data::default().hash();
+        assert!(Block::new(wrong_data_hash, data.clone(), Default::default(), None).is_err());
+
+        let last_commit = Some(Commit {
+            height: Height::from(1_u32),
+            ..Commit::default()
+        });
+        let mut wrong_commit_hash = header(2, &data, &last_commit);
+        wrong_commit_hash.last_commit_hash = Hash::None;
+        let result = Block::new(wrong_commit_hash, data.clone(), Default::default(), last_commit);
+        assert!(result.is_err());
+
+        assert!(Block::new(header(2, &data, &None), data, Default::default(), None).is_err());
+    }
+}
@@ -2,17 +2,21 @@
 
 use crate::block::commit_sig::CommitSig;
 use crate::block::{Height, Id, Round};
-use crate::{Error, Kind};
+use crate::merkle::simple_hash_from_byte_vectors;
+use crate::vote::{self, Vote};
+use crate::{validator, Error, Hash, Kind};
+use prost::Message;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::convert::{TryFrom, TryInto};
-use tendermint_proto::types::Commit as RawCommit;
+use tendermint_proto::types::{Commit as RawCommit, CommitSig as RawCommitSig};
 
 /// Commit contains the justification (ie. a set of signatures) that a block was committed by a set
 /// of validators.
 /// TODO: Update links below!
 /// <https://github.com/tendermint/tendermint/blob/51dc810d041eaac78320adc6d53ad8b160b06601/types/block.go#L486-L502>
 /// <https://github.com/tendermint/spec/blob/d46cd7f573a2c6a2399fcab2cde981330aa63f37/spec/core/data_structures.md#lastcommit>
-#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
 #[serde(try_from = "RawCommit", into = "RawCommit")] // Used by testgen Generator trait
 pub struct Commit {
     /// Block height
@@ -57,6 +61,109 @@ impl From<Commit> for RawCommit {
     }
 }
 
+impl Commit {
+    /// Compute the hash committed to by `Header::last_commit_hash`: the Merkle
+    /// root of the protobuf encodings of the signatures.
+    ///
+    /// <https://github.com/tendermint/tendermint/blob/v0.34.0/types/block.go#L837-L852>
+    pub fn hash(&self) -> Hash {
+        let signature_bytes = self
+            .signatures
+            .iter()
+            .map(|sig| {
+                let raw = RawCommitSig::from(sig.clone());
+                let mut buf = Vec::with_capacity(raw.encoded_len());
+                raw.encode(&mut buf).expect("buffer has enough capacity");
+                buf
+            })
+            .collect();
+
+        Hash::Sha256(simple_hash_from_byte_vectors(signature_bytes))
+    }
+
+    /// Reconstruct the precommit cast by the validator at the given index,
+    /// or `None` if the validator was absent or the index is out of range.
+    pub fn vote(&self, index: usize) -> Option<Vote> {
+        let commit_sig = self.signatures.get(index)?;
+        let (validator_address, timestamp, signature, block_id) = match commit_sig {
+            CommitSig::BlockIDFlagAbsent => return None,
+            CommitSig::BlockIDFlagCommit {
+                validator_address,
+                timestamp,
+                signature,
+            } => (validator_address, timestamp, signature, Some(self.block_id)),
+            CommitSig::BlockIDFlagNil {
+                validator_address,
+                timestamp,
+                signature,
+            } => (validator_address, timestamp, signature, None),
+        };
+
+        Some(Vote {
+            vote_type: vote::Type::Precommit,
+            height: self.height,
+            round: self.round,
+            block_id,
+            timestamp: Some(*timestamp),
+            validator_address: *validator_address,
+            validator_index: vote::ValidatorIndex::try_from(index).ok()?,
+            signature: signature.clone(),
+        })
+    }
+
+    /// Check that the signatures of this commit are aligned with the given
+    /// validator set: there must be exactly one signature per validator, and
+    /// every non-absent signature must be from the validator at its index.
+    ///
+    /// <https://github.com/tendermint/tendermint/blob/v0.34.0/types/validator_set.go#L667-L672>
+    pub fn validate(&self, validator_set: &validator::Set) -> Result<(), Error> {
+        let validators = validator_set.validators();
+
+        if self.signatures.len() != validators.len() {
+            return Err(Kind::SignatureCountMismatch
+                .context(format!(
+                    "commit has {} signatures, validator set has {} validators",
+                    self.signatures.len(),
+                    validators.len()
+                ))
+                .into());
+        }
+
+        let mut signers = HashSet::new();
+
+        for (index, sig) in self.signatures.iter().enumerate() {
+            if let Some(validator_address) = sig.validator_address() {
+                if !signers.insert(validator_address) {
+                    return Err(Kind::DuplicateValidator
+                        .context(format!(
+                            "validator {} signed more than once (again at index {})",
+                            validator_address, index
+                        ))
+                        .into());
+                }
+            }
+        }
+
+        for (index, (sig, validator)) in self.signatures.iter().zip(validators).enumerate() {
+            let validator_address = match sig.validator_address() {
+                Some(validator_address) => validator_address,
+                None => continue,
+            };
+
+            if validator_address != validator.address {
+                return Err(Kind::InvalidValidatorAddress
+                    .context(format!(
+                        "signature at index {} is from {}, expected {}",
+                        index, validator_address, validator.address
+                    ))
+                    .into());
+            }
+        }
+
+        Ok(())
+    }
+}
+
 impl Default for Commit {
     fn default() -> Self {
         Commit {
@@ -68,3 +175,84 @@ impl Default for Commit {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::public_key::{Ed25519, PublicKey};
+    use crate::{account, Signature, Time};
+    use ed25519_dalek::SecretKey;
+
+    fn validator(seed: u8, power: u32) -> validator::Info {
+        let public_key = Ed25519::from(&SecretKey::from_bytes(&[seed; 32]).unwrap());
+        validator::Info {
+            address: account::Id::from(public_key),
+            pub_key: PublicKey::Ed25519(public_key),
+            power: vote::Power::from(power),
+            proposer_priority: validator::ProposerPriority::from(0),
+        }
+    }
+
+    fn commit_sig(validator_address: account::Id) -> CommitSig {
+        CommitSig::BlockIDFlagCommit {
+            validator_address,
+            timestamp: Time::unix_epoch(),
+            signature: Signature::try_from(vec![0; 64]).unwrap(),
+        }
+    }
+
+    fn commit(signatures: Vec<CommitSig>) -> Commit {
+        Commit {
+            height: Height::from(1_u32),
+            round: Round::default(),
+            block_id: Id::default(),
+            signatures,
+        }
+    }
+
+    #[test]
+    fn signatures_must_be_aligned_with_validators() {
+        let validator_set =
+            validator::Set::without_proposer(vec![validator(1, 20), validator(2, 10)]).unwrap();
+        let addresses = validator_set
+            .validators()
+            .iter()
+            .map(|v| v.address)
+            .collect::<Vec<_>>();
+
+        let aligned = commit(vec![commit_sig(addresses[0]), CommitSig::BlockIDFlagAbsent]);
+        assert!(aligned.validate(&validator_set).is_ok());
+
+        let kind_of = |commit: Commit, validator_set: &validator::Set| {
+            Kind::of(&commit.validate(validator_set).unwrap_err()).cloned()
+        };
+
+        let too_short = commit(vec![commit_sig(addresses[0])]);
+        assert_eq!(
+            kind_of(too_short, &validator_set),
+            Some(Kind::SignatureCountMismatch)
+        );
+
+        let swapped = commit(vec![commit_sig(addresses[1]), commit_sig(addresses[0])]);
+        assert_eq!(
+            kind_of(swapped, &validator_set),
+            Some(Kind::InvalidValidatorAddress)
+        );
+
+        let duplicate = commit(vec![commit_sig(addresses[0]), commit_sig(addresses[0])]);
+        assert_eq!(
+            kind_of(duplicate, &validator_set),
+            Some(Kind::DuplicateValidator)
+        );
+
+        // A validator listed twice in the set, signing at both of its indices,
+        // is aligned but still signs twice
+        let doubled_set =
+            validator::Set::without_proposer(vec![validator(1, 20), validator(1, 10)]).unwrap();
+        let doubled = commit(vec![commit_sig(addresses[0]), commit_sig(addresses[0])]);
+        assert_eq!(
+            kind_of(doubled, &doubled_set),
+            Some(Kind::DuplicateValidator)
+        );
+    }
+}
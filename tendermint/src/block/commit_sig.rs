@@ -9,7 +9,7 @@ use tendermint_proto::types::CommitSig as RawCommitSig;
 
 /// CommitSig represents a signature of a validator.
 /// It's a part of the Commit and can be used to reconstruct the vote set given the validator set.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum CommitSig {
     /// no vote was received from a validator.
     BlockIDFlagAbsent,
@@ -64,16 +64,15 @@ impl CommitSig {
 }
 
 // Todo: https://github.com/informalsystems/tendermint-rs/issues/259 - CommitSig Timestamp can be zero time
-// Todo: https://github.com/informalsystems/tendermint-rs/issues/260 - CommitSig validator address missing in Absent vote
+// Absent CommitSigs carry no validator address (https://github.com/informalsystems/tendermint-rs/issues/260):
+// signatures are matched with validators by position, see `Commit::validate`.
 impl TryFrom<RawCommitSig> for CommitSig {
     type Error = Error;
 
     fn try_from(value: RawCommitSig) -> Result<Self, Self::Error> {
         if value.block_id_flag == BlockIdFlag::Absent.to_i32().unwrap() {
-            if value.timestamp.is_some() {
-                let timestamp = value.timestamp.unwrap();
-                // 0001-01-01T00:00:00.000Z translates to EPOCH-62135596800 seconds
-                if timestamp.nanos != 0 || timestamp.seconds != -62135596800 {
+            if let Some(timestamp) = value.timestamp {
+                if !Time::try_from(timestamp)?.is_zero() {
                     return Err(Kind::InvalidTimestamp
                         .context("absent commitsig has non-zero timestamp")
                         .into());
@@ -1,7 +1,226 @@
-use crate::{block};
-#[derive(Clone, PartialEq, Eq)]
+//! Block headers
+
+use crate::merkle::simple_hash_from_byte_vectors;
+use crate::{account, block, chain, serializers, Error, Hash, Kind, Time};
+use prost::Message;
+use serde::{Deserialize, Serialize};
+use std::convert::{TryFrom, TryInto};
+use tendermint_proto::types::{BlockId as RawBlockId, Header as RawHeader};
+use tendermint_proto::version::Consensus as RawConsensusVersion;
+use tendermint_proto::Protobuf;
+
+/// Block `Header` values contain metadata about the block and about the
+/// consensus, as well as commitments to the data in the current block, the
+/// previous block, and the results returned by the application.
+///
+/// <https://github.com/tendermint/spec/blob/d46cd7f573a2c6a2399fcab2cde981330aa63f37/spec/core/data_structures.md#header>
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "RawHeader", into = "RawHeader")]
 pub struct Header {
+    /// Header version
+    pub version: Version,
+
+    /// Chain ID
+    pub chain_id: chain::Id,
+
     /// Current block height
     pub height: block::Height,
 
+    /// Current timestamp
+    pub time: Time,
+
+    /// Previous block info, absent for the first block
+    pub last_block_id: Option<block::Id>,
+
+    /// Commit from validators from the last block
+    pub last_commit_hash: Hash,
+
+    /// Merkle root of transaction hashes
+    pub data_hash: Hash,
+
+    /// Validators for the current block
+    pub validators_hash: Hash,
+
+    /// Validators for the next block
+    pub next_validators_hash: Hash,
+
+    /// Consensus params for the current block
+    pub consensus_hash: Hash,
+
+    /// State after txs from the previous block
+    pub app_hash: Vec<u8>,
+
+    /// Root hash of all results from the txs from the previous block
+    pub last_results_hash: Hash,
+
+    /// Hash of evidence included in the block
+    pub evidence_hash: Hash,
+
+    /// Original proposer of the block
+    pub proposer_address: account::Id,
+}
+
+impl Protobuf<RawHeader> for Header {}
+
+impl TryFrom<RawHeader> for Header {
+    type Error = Error;
+
+    fn try_from(value: RawHeader) -> Result<Self, Self::Error> {
+        Ok(Header {
+            version: value.version.ok_or(Kind::MissingVersion)?.try_into()?,
+            chain_id: value.chain_id.try_into()?,
+            height: value.height.try_into()?,
+            time: value.time.ok_or(Kind::NoTimestamp)?.try_into()?,
+            // An empty block ID stands for the absent one of the first block
+            last_block_id: value
+                .last_block_id
+                .map(TryInto::try_into)
+                .transpose()?
+                .filter(|id| id != &block::Id::default()),
+            last_commit_hash: value.last_commit_hash.try_into()?,
+            data_hash: value.data_hash.try_into()?,
+            validators_hash: value.validators_hash.try_into()?,
+            next_validators_hash: value.next_validators_hash.try_into()?,
+            consensus_hash: value.consensus_hash.try_into()?,
+            app_hash: value.app_hash,
+            last_results_hash: value.last_results_hash.try_into()?,
+            evidence_hash: value.evidence_hash.try_into()?,
+            proposer_address: value.proposer_address.try_into()?,
+        })
+    }
+}
+
+impl From<Header> for RawHeader {
+    fn from(value: Header) -> Self {
+        RawHeader {
+            version: Some(value.version.into()),
+            chain_id: value.chain_id.into(),
+            height: value.height.into(),
+            time: Some(value.time.into()),
+            last_block_id: value.last_block_id.map(Into::into),
+            last_commit_hash: value.last_commit_hash.into(),
+            data_hash: value.data_hash.into(),
+            validators_hash: value.validators_hash.into(),
+            next_validators_hash: value.next_validators_hash.into(),
+            consensus_hash: value.consensus_hash.into(),
+            app_hash: value.app_hash,
+            last_results_hash: value.last_results_hash.into(),
+            evidence_hash: value.evidence_hash.into(),
+            proposer_address: value.proposer_address.into(),
+        }
+    }
+}
+
+impl Header {
+    /// Hash this header
+    ///
+    /// The hash is the Merkle root of the protobuf encodings of the header
+    /// fields, in the order they are declared in the protobuf definition.
+    ///
+    /// <https://github.com/tendermint/tendermint/blob/v0.34.0/types/block.go#L393-L429>
+    pub fn hash(&self) -> Hash {
+        let raw = RawHeader::from(self.clone());
+
+        let fields_bytes = vec![
+            encode(&raw.version.unwrap_or_default()),
+            encode(&raw.chain_id),
+            encode(&raw.height),
+            encode(&raw.time.unwrap_or_default()),
+            // An absent block ID is encoded as an empty one, with a (non-nullable) empty part set header
+            encode(
+                &raw.last_block_id
+                    .unwrap_or_else(|| RawBlockId::from(block::Id::default())),
+            ),
+            encode(&raw.last_commit_hash),
+            encode(&raw.data_hash),
+            encode(&raw.validators_hash),
+            encode(&raw.next_validators_hash),
+            encode(&raw.consensus_hash),
+            encode(&raw.app_hash),
+            encode(&raw.last_results_hash),
+            encode(&raw.evidence_hash),
+            encode(&raw.proposer_address),
+        ];
+
+        Hash::Sha256(simple_hash_from_byte_vectors(fields_bytes))
+    }
+}
+
+/// Protobuf encoding of a single header field
+fn encode<M: Message>(field: &M) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(field.encoded_len());
+    field
+        .encode(&mut buf)
+        .expect("buffer has enough capacity");
+    buf
+}
+
+/// `Version` contains the protocol version for the blockchain and the
+/// application.
+///
+/// <https://github.com/tendermint/spec/blob/d46cd7f573a2c6a2399fcab2cde981330aa63f37/spec/core/data_structures.md#version>
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Version {
+    /// Block version
+    #[serde(with = "serializers::from_str")]
+    pub block: u64,
+
+    /// App version
+    #[serde(with = "serializers::from_str")]
+    pub app: u64,
+}
+
+impl Protobuf<RawConsensusVersion> for Version {}
+
+impl TryFrom<RawConsensusVersion> for Version {
+    type Error = Error;
+
+    fn try_from(value: RawConsensusVersion) -> Result<Self, Self::Error> {
+        Ok(Version {
+            block: value.block,
+            app: value.app,
+        })
+    }
+}
+
+impl From<Version> for RawConsensusVersion {
+    fn from(value: Version) -> Self {
+        RawConsensusVersion {
+            block: value.block,
+            app: value.app,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A header of a live chain, in the JSON encoding of the RPC endpoints
+    fn header_with_known_hash() -> Header {
+        let raw: RawHeader = serde_json::from_str(include_str!(
+            "../../tests/support/serialization/block/header_with_known_hash.json"
+        ))
+        .unwrap();
+        Header::try_from(raw).unwrap()
+    }
+
+    #[test]
+    fn protobuf_round_trip() {
+        let header = header_with_known_hash();
+        assert_eq!(header.version, Version { block: 11, app: 1 });
+        assert_ne!(header.evidence_hash, Hash::None);
+
+        let decoded = Header::decode_vec(&header.encode_vec().unwrap()).unwrap();
+        assert_eq!(decoded, header);
+    }
+
+    #[test]
+    fn hash_matches_known_hash() {
+        let header = header_with_known_hash();
+        assert_eq!(
+            header.hash().to_string(),
+            "F30A71F2409FB15AACAEDB6CC122DFA2525BEE9CAE521721B06BFDCA291B8D56"
+        );
+    }
 }
@@ -1,16 +1,117 @@
+use crate::error::{Error, Kind};
+use crate::serializers;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::convert::{TryFrom, TryInto};
 use std::fmt;
 use std::fmt::Debug;
+use std::str::FromStr;
 
 extern crate prusti_contracts;
 use prusti_contracts::*;
 
-
+/// Block height for a particular chain (i.e. number of blocks created since
+/// the chain began)
+///
+/// A height of 0 represents a chain which has not yet produced a block.
 #[derive(Copy, Clone, Eq, Hash, PartialEq, PartialOrd, Ord)]
 pub struct Height(u64);
 
+impl TryFrom<i64> for Height {
+    type Error = Error;
+
+    fn try_from(value: i64) -> Result<Self, Self::Error> {
+        Ok(Height(value.try_into().map_err(|_| Kind::NegativeHeight)?))
+    }
+}
+
+impl From<Height> for i64 {
+    fn from(value: Height) -> Self {
+        value.value() as i64 // does not overflow. The value is <= i64::MAX
+    }
+}
+
+impl TryFrom<u64> for Height {
+    type Error = Error;
+
+    fn try_from(value: u64) -> Result<Self, Self::Error> {
+        // Make sure the u64 value can be converted safely to i64
+        let _ival: i64 = value.try_into().map_err(|_| Kind::IntegerOverflow)?;
+
+        Ok(Height(value))
+    }
+}
+
+impl From<Height> for u64 {
+    fn from(value: Height) -> Self {
+        value.value()
+    }
+}
+
+impl From<u32> for Height {
+    fn from(value: u32) -> Self {
+        Height(value as u64)
+    }
+}
+
+impl From<u16> for Height {
+    fn from(value: u16) -> Self {
+        Height(value as u64)
+    }
+}
+
+impl From<u8> for Height {
+    fn from(value: u8) -> Self {
+        Height(value as u64)
+    }
+}
+
+impl Height {
+    /// Get inner integer value. Alternative to `.0` or `.into()`
+    #[pure]
+    pub fn value(&self) -> u64 {
+        self.0
+    }
+
+    /// Increment the block height by 1
+    pub fn increment(self) -> Self {
+        Height::try_from(self.0.checked_add(1).expect("height overflow")).unwrap()
+    }
+}
+
 impl Debug for Height {
     #[trusted]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "block::Height({})", self.0)
     }
 }
+
+impl fmt::Display for Height {
+    #[trusted]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Heights are encoded as strings, like other 64-bit integers of Tendermint's JSON
+impl<'de> Deserialize<'de> for Height {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        serializers::from_str::deserialize(deserializer)
+    }
+}
+
+impl Serialize for Height {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializers::from_str::serialize(self, serializer)
+    }
+}
+
+impl FromStr for Height {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        Height::try_from(
+            s.parse::<u64>()
+                .map_err(|_| Kind::Parse.context("height decode"))?,
+        )
+    }
+}
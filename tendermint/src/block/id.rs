@@ -1,5 +1,5 @@
 use crate::{
-    block::parts::Header as PartSetHeader,
+    block::{parts::Header as PartSetHeader, Header},
     error::{Error, Kind},
     hash::{Algorithm, Hash},
 };
@@ -16,7 +16,7 @@ use tendermint_proto::types::{
 use tendermint_proto::Protobuf;
 
 /// Length of a block ID prefix displayed for debugging purposes
-pub const PREFIX_LENGTH: usize = 10;
+pub const PREFIX_LENGTH: usize = 12;
 
 /// Block identifiers which contain two distinct Merkle roots of the block,
 /// as well as the number of parts in the block.
@@ -126,27 +126,56 @@ impl From<Id> for RawCanonicalBlockId {
 impl Id {
     /// Get a shortened 12-character prefix of a block ID (ala git)
     pub fn prefix(&self) -> String {
-        let mut result = self.to_string();
+        let mut result = self.hash.to_string();
         result.truncate(PREFIX_LENGTH);
         result
     }
 }
 
-// TODO: match gaia serialization? e.g `D2F5991B98D708FD2C25AA2BEBED9358F24177DE:1:C37A55FB95E9`
+/// Block IDs are displayed as their bare hash when they have no part set
+/// header, and as `hash:total:part_set_hash` otherwise, e.g.
+/// `D2F5991B98D708FD2C25AA2BEBED9358F24177DE:1:C37A55FB95E9...`, which is
+/// how gaia displays them (with the full part set hash, so that it round-trips).
 impl Display for Id {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", &self.hash)
+        if self.part_set_header == PartSetHeader::default() {
+            write!(f, "{}", &self.hash)
+        } else {
+            write!(
+                f,
+                "{}:{}:{}",
+                &self.hash, self.part_set_header.total, &self.part_set_header.hash
+            )
+        }
     }
 }
 
-// TODO: match gaia serialization?
+/// Parses either format produced by the `Display` implementation
 impl FromStr for Id {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Error> {
+        let fields = s.split(':').collect::<Vec<_>>();
+
+        let (hash, part_set_header) = match fields.as_slice() {
+            [hash] => (*hash, PartSetHeader::default()),
+            [hash, total, part_set_hash] => {
+                let total = total
+                    .parse::<u32>()
+                    .map_err(|e| Kind::Parse.context(format!("invalid part set total: {}", e)))?;
+                let part_set_hash = Hash::from_hex_upper(Algorithm::Sha256, part_set_hash)?;
+                (*hash, PartSetHeader::new(total, part_set_hash)?)
+            }
+            _ => {
+                return Err(Kind::Parse
+                    .context(format!("expected `hash` or `hash:total:part_hash`, got {}", s))
+                    .into())
+            }
+        };
+
         Ok(Self {
-            hash: Hash::from_hex_upper(Algorithm::Sha256, s)?,
-            part_set_header: PartSetHeader::default(),
+            hash: Hash::from_hex_upper(Algorithm::Sha256, hash)?,
+            part_set_header,
         })
     }
 }
@@ -157,6 +186,35 @@ pub trait ParseId {
     fn parse_block_id(&self) -> Result<Id, Error>;
 }
 
+impl ParseId for str {
+    fn parse_block_id(&self) -> Result<Id, Error> {
+        self.parse()
+    }
+}
+
+impl ParseId for String {
+    fn parse_block_id(&self) -> Result<Id, Error> {
+        self.as_str().parse_block_id()
+    }
+}
+
+/// The ID of a header only contains its hash, since the part set header
+/// can only be computed from the whole block.
+impl ParseId for Header {
+    fn parse_block_id(&self) -> Result<Id, Error> {
+        Ok(Id {
+            hash: self.hash(),
+            part_set_header: PartSetHeader::default(),
+        })
+    }
+}
+
+impl ParseId for RawBlockId {
+    fn parse_block_id(&self) -> Result<Id, Error> {
+        Id::try_from(self.clone())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -164,6 +222,9 @@ mod tests {
     const EXAMPLE_SHA256_ID: &str =
         "26C0A41F3243C6BCD7AD2DFF8A8D83A71D29D307B5326C227F734A1A512FE47D";
 
+    const EXAMPLE_PART_SET_HASH: &str =
+        "C37A55FB95E9E5B5C4E5F9B5D8A1F8E9C7A0B3D2E1F4A5B6C7D8E9F0A1B2C3D4";
+
     #[test]
     fn parses_hex_strings() {
         let id = Id::from_str(EXAMPLE_SHA256_ID).unwrap();
@@ -180,4 +241,26 @@ mod tests {
         let id = Id::from_str(EXAMPLE_SHA256_ID).unwrap();
         assert_eq!(&id.to_string(), EXAMPLE_SHA256_ID)
     }
+
+    #[test]
+    fn full_format_round_trips() {
+        let s = format!("{}:3:{}", EXAMPLE_SHA256_ID, EXAMPLE_PART_SET_HASH);
+        let id = s.parse_block_id().unwrap();
+
+        assert_eq!(id.part_set_header.total, 3);
+        assert_eq!(id.part_set_header.hash.to_string(), EXAMPLE_PART_SET_HASH);
+        assert_eq!(id.to_string(), s);
+        assert_eq!(RawBlockId::from(id).parse_block_id().unwrap(), id);
+
+        assert!(format!("{}:3", EXAMPLE_SHA256_ID).parse_block_id().is_err());
+        assert!(format!("{}:x:{}", EXAMPLE_SHA256_ID, EXAMPLE_PART_SET_HASH)
+            .parse_block_id()
+            .is_err());
+    }
+
+    #[test]
+    fn prefix_has_twelve_characters() {
+        let s = format!("{}:3:{}", EXAMPLE_SHA256_ID, EXAMPLE_PART_SET_HASH);
+        assert_eq!(s.parse_block_id().unwrap().prefix(), "26C0A41F3243");
+    }
 }
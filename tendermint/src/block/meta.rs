@@ -1,12 +1,12 @@
 //! Block metadata
 
-use super::{Header, Id};
+use super::{Block, Header, Id};
 use crate::{Error, Kind};
 use serde::{Deserialize, Serialize};
 use std::convert::{TryFrom, TryInto};
 use tendermint_proto::types::BlockMeta as RawMeta;
 
-/// Block metadata - Todo: implement constructor and getters
+/// Block metadata
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(try_from = "RawMeta", into = "RawMeta")]
 pub struct Meta {
@@ -52,3 +52,23 @@ impl From<Meta> for RawMeta {
         }
     }
 }
+
+impl Meta {
+    /// Compute the metadata of the given block
+    pub fn new(block: &Block) -> Result<Self, Error> {
+        Ok(Meta {
+            block_id: block.id()?,
+            block_size: block
+                .to_bytes()
+                .len()
+                .try_into()
+                .map_err(|_| Kind::IntegerOverflow)?,
+            header: block.header.clone(),
+            num_txs: block
+                .data
+                .len()
+                .try_into()
+                .map_err(|_| Kind::IntegerOverflow)?,
+        })
+    }
+}
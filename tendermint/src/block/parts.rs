@@ -1,16 +1,27 @@
 //! Block parts
 
+use crate::consensus::MAX_BLOCK_SIZE_BYTES;
 use crate::hash::Algorithm;
 use crate::hash::SHA256_HASH_SIZE;
+use crate::merkle::{self, proofs_from_byte_vectors};
 use crate::Hash;
 use crate::{Error, Kind};
 use serde::{Deserialize, Serialize};
-use std::convert::TryFrom;
+use std::convert::{TryFrom, TryInto};
 use tendermint_proto::types::{
-    CanonicalPartSetHeader as RawCanonicalPartSetHeader, PartSetHeader as RawPartSetHeader,
+    CanonicalPartSetHeader as RawCanonicalPartSetHeader, Part as RawPart,
+    PartSetHeader as RawPartSetHeader,
 };
 use tendermint_proto::Protobuf;
 
+/// Size of the parts a serialized block is split into, except for the last one
+/// which may be smaller. Matches `BlockPartSizeBytes` from:
+/// <https://github.com/tendermint/tendermint/blob/v0.34.0/types/params.go>
+pub const BLOCK_PART_SIZE_BYTES: usize = 65536;
+
+/// Maximum number of parts of a block no larger than `MAX_BLOCK_SIZE_BYTES`
+pub const MAX_BLOCK_PARTS: u32 = MAX_BLOCK_SIZE_BYTES.div_ceil(BLOCK_PART_SIZE_BYTES as u64) as u32;
+
 /// Block parts header
 #[derive(
     Clone, Copy, Debug, Default, Hash, Eq, PartialEq, PartialOrd, Ord, Deserialize, Serialize,
@@ -89,3 +100,290 @@ impl Header {
         Ok(Header { total, hash })
     }
 }
+
+/// A part of a serialized block, with a proof of its inclusion
+/// in the part set whose root hash is `Header::hash`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Part {
+    /// Index of this part
+    pub index: u32,
+
+    /// Bytes of this part
+    pub bytes: Vec<u8>,
+
+    /// Proof that this part is included in the part set
+    pub proof: merkle::Proof,
+}
+
+impl Protobuf<RawPart> for Part {}
+
+impl TryFrom<RawPart> for Part {
+    type Error = Error;
+
+    fn try_from(value: RawPart) -> Result<Self, Self::Error> {
+        if value.bytes.len() > BLOCK_PART_SIZE_BYTES {
+            return Err(Kind::InvalidPart
+                .context(format!("part is {} bytes long", value.bytes.len()))
+                .into());
+        }
+
+        Ok(Part {
+            index: value.index,
+            bytes: value.bytes,
+            proof: value.proof.ok_or(Kind::InvalidProof)?.try_into()?,
+        })
+    }
+}
+
+impl From<Part> for RawPart {
+    fn from(value: Part) -> Self {
+        RawPart {
+            index: value.index,
+            bytes: value.bytes,
+            proof: Some(value.proof.into()),
+        }
+    }
+}
+
+/// The set of parts a serialized block is split into for gossiping.
+///
+/// A part set is either built from the serialized block, or starts out
+/// empty from a `Header` and is filled in as parts are received, in any order.
+///
+/// <https://github.com/tendermint/tendermint/blob/v0.34.0/types/part_set.go>
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PartSet {
+    header: Header,
+    parts: Vec<Option<Part>>,
+    count: u32,
+}
+
+impl PartSet {
+    /// Split the given serialized block into parts of `BLOCK_PART_SIZE_BYTES`
+    pub fn from_data(data: &[u8]) -> Result<Self, Error> {
+        if data.is_empty() {
+            return Self::from_header(Header::default());
+        }
+
+        let chunks: Vec<Vec<u8>> = data
+            .chunks(BLOCK_PART_SIZE_BYTES)
+            .map(<[u8]>::to_vec)
+            .collect();
+        let total: u32 = chunks.len().try_into().map_err(|_| Kind::IntegerOverflow)?;
+        check_total(total)?;
+
+        let (root_hash, proofs) = proofs_from_byte_vectors(&chunks);
+        let parts = chunks
+            .into_iter()
+            .zip(proofs)
+            .zip(0..)
+            .map(|((bytes, proof), index)| {
+                Some(Part {
+                    index,
+                    bytes,
+                    proof,
+                })
+            })
+            .collect();
+
+        Ok(PartSet {
+            header: Header::new(total, Hash::Sha256(root_hash))?,
+            parts,
+            count: total,
+        })
+    }
+
+    /// Create an empty part set, to be filled with the parts described by the given header.
+    ///
+    /// Fails if the header announces more than `MAX_BLOCK_PARTS` parts, as space for
+    /// all of them is allocated upfront.
+    pub fn from_header(header: Header) -> Result<Self, Error> {
+        check_total(header.total)?;
+
+        Ok(PartSet {
+            header,
+            parts: vec![None; header.total as usize],
+            count: 0,
+        })
+    }
+
+    /// The header of this part set
+    pub fn header(&self) -> Header {
+        self.header
+    }
+
+    /// Total number of parts in this part set
+    pub fn total(&self) -> u32 {
+        self.header.total
+    }
+
+    /// Number of parts received so far
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    /// Whether all parts have been received
+    pub fn is_complete(&self) -> bool {
+        self.count == self.header.total
+    }
+
+    /// Get the part at the given index, if it has been received
+    pub fn part(&self, index: u32) -> Option<&Part> {
+        self.parts.get(index as usize)?.as_ref()
+    }
+
+    /// Add the given part, after checking its proof against the part set header.
+    ///
+    /// Returns `false` if the part had already been received.
+    pub fn add_part(&mut self, part: Part) -> Result<bool, Error> {
+        if part.index >= self.header.total {
+            return Err(Kind::InvalidPart
+                .context(format!(
+                    "part index {} out of range, expected less than {}",
+                    part.index, self.header.total
+                ))
+                .into());
+        }
+
+        if self.parts[part.index as usize].is_some() {
+            return Ok(false);
+        }
+
+        if part.proof.index != u64::from(part.index)
+            || part.proof.total != u64::from(self.header.total)
+        {
+            return Err(Kind::InvalidProof
+                .context("proof does not match the part index or the part set size")
+                .into());
+        }
+
+        let root_hash = match self.header.hash {
+            Hash::Sha256(hash) => hash,
+            Hash::None => return Err(Kind::InvalidPartSetHeader.into()),
+        };
+        part.proof.verify(&root_hash, &part.bytes)?;
+
+        let index = part.index as usize;
+        self.parts[index] = Some(part);
+        self.count += 1;
+        Ok(true)
+    }
+
+    /// Reassemble the serialized block, once all parts have been received
+    pub fn assemble(&self) -> Option<Vec<u8>> {
+        if !self.is_complete() {
+            return None;
+        }
+
+        let bytes = self
+            .parts
+            .iter()
+            .flatten()
+            .flat_map(|part| part.bytes.iter().copied())
+            .collect();
+
+        Some(bytes)
+    }
+}
+
+/// Check that a block split into `total` parts does not exceed `MAX_BLOCK_SIZE_BYTES`
+fn check_total(total: u32) -> Result<(), Error> {
+    if total > MAX_BLOCK_PARTS {
+        return Err(Kind::InvalidPartSetHeader
+            .context(format!(
+                "{} parts, expected at most {}",
+                total, MAX_BLOCK_PARTS
+            ))
+            .into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block_bytes(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i % 251) as u8).collect()
+    }
+
+    #[test]
+    fn splits_into_fixed_size_parts() {
+        let data = block_bytes(2 * BLOCK_PART_SIZE_BYTES + 100);
+        let part_set = PartSet::from_data(&data).unwrap();
+
+        assert_eq!(part_set.total(), 3);
+        assert!(part_set.is_complete());
+        assert_eq!(part_set.part(0).unwrap().bytes.len(), BLOCK_PART_SIZE_BYTES);
+        assert_eq!(part_set.part(2).unwrap().bytes.len(), 100);
+        assert_eq!(part_set.assemble().unwrap(), data);
+    }
+
+    #[test]
+    fn reassembles_parts_received_in_any_order() {
+        let data = block_bytes(4 * BLOCK_PART_SIZE_BYTES + 1);
+        let source = PartSet::from_data(&data).unwrap();
+        let mut part_set = PartSet::from_header(source.header()).unwrap();
+
+        for index in &[4, 1, 3, 0] {
+            let part = source.part(*index).unwrap().clone();
+            assert!(part_set.add_part(part).unwrap());
+        }
+        assert!(!part_set.is_complete());
+        assert!(part_set.assemble().is_none());
+
+        // Receiving a part twice is a no-op
+        let part = source.part(1).unwrap().clone();
+        assert!(!part_set.add_part(part).unwrap());
+
+        let part = source.part(2).unwrap().clone();
+        assert!(part_set.add_part(part).unwrap());
+        assert!(part_set.is_complete());
+        assert_eq!(part_set.assemble().unwrap(), data);
+    }
+
+    #[test]
+    fn rejects_invalid_parts() {
+        let data = block_bytes(3 * BLOCK_PART_SIZE_BYTES);
+        let source = PartSet::from_data(&data).unwrap();
+        let mut part_set = PartSet::from_header(source.header()).unwrap();
+
+        let mut tampered = source.part(0).unwrap().clone();
+        tampered.bytes[0] ^= 1;
+        assert!(part_set.add_part(tampered).is_err());
+
+        let mut misplaced = source.part(0).unwrap().clone();
+        misplaced.index = 1;
+        assert!(part_set.add_part(misplaced).is_err());
+
+        let mut out_of_range = source.part(0).unwrap().clone();
+        out_of_range.index = 3;
+        assert!(part_set.add_part(out_of_range).is_err());
+
+        let other = PartSet::from_data(&block_bytes(3 * BLOCK_PART_SIZE_BYTES - 1)).unwrap();
+        assert!(part_set.add_part(other.part(2).unwrap().clone()).is_err());
+
+        assert_eq!(part_set.count(), 0);
+    }
+
+    #[test]
+    fn rejects_headers_of_oversized_blocks() {
+        assert_eq!(
+            MAX_BLOCK_PARTS as u64 * BLOCK_PART_SIZE_BYTES as u64,
+            MAX_BLOCK_SIZE_BYTES
+        );
+
+        let hash = Hash::Sha256([0; SHA256_HASH_SIZE]);
+        let header = Header::new(MAX_BLOCK_PARTS, hash).unwrap();
+        assert_eq!(
+            PartSet::from_header(header).unwrap().total(),
+            MAX_BLOCK_PARTS
+        );
+
+        let header = Header::new(MAX_BLOCK_PARTS + 1, hash).unwrap();
+        assert!(PartSet::from_header(header).is_err());
+
+        let header = Header::new(u32::MAX, hash).unwrap();
+        assert!(PartSet::from_header(header).is_err());
+    }
+}
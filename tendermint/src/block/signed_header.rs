@@ -1,13 +1,26 @@
-use std::{fmt};
-use crate::{block};
+//! SignedHeader contains a commit and a block header.
+//! It is what the rpc endpoint /commit returns and hence can be used by a
+//! light client.
+
+use std::{
+    convert::{TryFrom, TryInto},
+    fmt,
+};
+use crate::{block, Error, Kind};
+use tendermint_proto::types::SignedHeader as RawSignedHeader;
+use tendermint_proto::Protobuf;
 
 extern crate prusti_contracts;
 use prusti_contracts::*;
 
+/// Signed block headers
 #[non_exhaustive]
 #[derive(Clone, PartialEq, Eq)]
 pub struct SignedHeader {
+    /// Block header
     pub header: block::Header,
+    /// Commit containing signatures for the header
+    pub commit: block::Commit,
 }
 
 impl fmt::Debug for SignedHeader {
@@ -17,3 +30,42 @@ impl fmt::Debug for SignedHeader {
             .finish()
     }
 }
+
+impl Protobuf<RawSignedHeader> for SignedHeader {}
+
+impl TryFrom<RawSignedHeader> for SignedHeader {
+    type Error = Error;
+
+    fn try_from(value: RawSignedHeader) -> Result<Self, Self::Error> {
+        let header = value
+            .header
+            .ok_or_else(|| Kind::InvalidSignedHeader.context("missing header field"))?
+            .try_into()?;
+        let commit = value
+            .commit
+            .ok_or_else(|| Kind::InvalidSignedHeader.context("missing commit field"))?
+            .try_into()?;
+        Self::new(header, commit)
+    }
+}
+
+impl From<SignedHeader> for RawSignedHeader {
+    fn from(value: SignedHeader) -> Self {
+        RawSignedHeader {
+            header: Some(value.header.into()),
+            commit: Some(value.commit.into()),
+        }
+    }
+}
+
+impl SignedHeader {
+    /// Constructor.
+    pub fn new(header: block::Header, commit: block::Commit) -> Result<Self, Error> {
+        if header.height != commit.height {
+            return Err(Kind::InvalidSignedHeader
+                .context("header and commit height mismatch")
+                .into());
+        }
+        Ok(Self { header, commit })
+    }
+}
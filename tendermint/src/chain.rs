@@ -0,0 +1,5 @@
+//! Chain information
+
+mod id;
+
+pub use self::id::{Id, MAX_LENGTH};
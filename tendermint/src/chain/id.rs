@@ -0,0 +1,146 @@
+//! Tendermint blockchain identifiers
+
+use crate::error::{Error, Kind};
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+use std::convert::TryFrom;
+use std::fmt::{self, Debug, Display};
+use std::str::FromStr;
+use tendermint_proto::Protobuf;
+
+extern crate prusti_contracts;
+use prusti_contracts::*;
+
+/// Maximum length of a `chain::Id` name. Matches `MaxChainIDLen` from:
+/// <https://github.com/tendermint/tendermint/blob/v0.34.0/types/genesis.go>
+pub const MAX_LENGTH: usize = 50;
+
+/// Chain identifier (e.g. 'gaia-9000')
+#[derive(Clone, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub struct Id(String);
+
+impl Protobuf<String> for Id {}
+
+impl TryFrom<String> for Id {
+    type Error = Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        if value.is_empty() {
+            return Err(Kind::InvalidChainId.context("empty chain ID").into());
+        }
+
+        if value.len() > MAX_LENGTH {
+            return Err(Kind::InvalidChainId
+                .context(format!(
+                    "chain ID is {} bytes long, the maximum is {}",
+                    value.len(),
+                    MAX_LENGTH
+                ))
+                .into());
+        }
+
+        if value.chars().any(|c| c.is_ascii_control()) {
+            return Err(Kind::InvalidChainId
+                .context("chain ID contains control characters")
+                .into());
+        }
+
+        Ok(Id(value))
+    }
+}
+
+impl From<Id> for String {
+    fn from(value: Id) -> Self {
+        value.0
+    }
+}
+
+impl Id {
+    /// Get a `str` reference to this chain ID
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+
+    /// Get a byte slice of this chain ID
+    pub fn as_bytes(&self) -> &[u8] {
+        self.0.as_bytes()
+    }
+}
+
+impl AsRef<str> for Id {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl Debug for Id {
+    #[trusted]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "chain::Id({})", self.0)
+    }
+}
+
+impl Display for Id {
+    #[trusted]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl FromStr for Id {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        Self::try_from(s.to_owned())
+    }
+}
+
+impl PartialEq<str> for Id {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl<'de> Deserialize<'de> for Id {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Self::from_str(&s).map_err(|e| D::Error::custom(format!("{}", e)))
+    }
+}
+
+impl Serialize for Id {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE_CHAIN_ID: &str = "gaia-9000";
+
+    #[test]
+    fn parses_valid_chain_ids() {
+        let id = EXAMPLE_CHAIN_ID.parse::<Id>().unwrap();
+        assert_eq!(id.to_string(), EXAMPLE_CHAIN_ID);
+        assert!(id == *EXAMPLE_CHAIN_ID);
+
+        let longest = "x".repeat(MAX_LENGTH);
+        assert!(longest.parse::<Id>().is_ok());
+    }
+
+    #[test]
+    fn rejects_invalid_chain_ids() {
+        assert!("".parse::<Id>().is_err());
+        assert!("x".repeat(MAX_LENGTH + 1).parse::<Id>().is_err());
+        assert!("gaia\n9000".parse::<Id>().is_err());
+    }
+
+    #[test]
+    fn serde_round_trip() {
+        let id: Id = serde_json::from_str("\"gaia-9000\"").unwrap();
+        assert_eq!(id.as_str(), EXAMPLE_CHAIN_ID);
+        assert_eq!(serde_json::to_string(&id).unwrap(), "\"gaia-9000\"");
+        assert!(serde_json::from_str::<Id>("\"\"").is_err());
+    }
+}
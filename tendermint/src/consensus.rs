@@ -0,0 +1,11 @@
+//! Tendermint consensus
+
+mod params;
+pub mod state;
+mod state_file;
+
+pub use self::{
+    params::{Params, ValidatorParams, VersionParams, MAX_BLOCK_SIZE_BYTES},
+    state::State,
+    state_file::StateFile,
+};
@@ -0,0 +1,276 @@
+//! Tendermint consensus parameters
+
+use crate::hash::SHA256_HASH_SIZE;
+use crate::{block, evidence, public_key, Error, Kind};
+use prost::Message;
+use sha2::{Digest, Sha256};
+use std::convert::{TryFrom, TryInto};
+use tendermint_proto::abci::ConsensusParams as RawParams;
+use tendermint_proto::types::{
+    HashedParams as RawHashedParams, ValidatorParams as RawValidatorParams,
+    VersionParams as RawVersionParams,
+};
+use tendermint_proto::Protobuf;
+
+/// Upper bound on the `max_bytes` block size parameter (100 MiB)
+pub const MAX_BLOCK_SIZE_BYTES: u64 = 104_857_600;
+
+/// Tendermint consensus parameters
+///
+/// <https://github.com/tendermint/spec/blob/d46cd7f573a2c6a2399fcab2cde981330aa63f37/spec/core/data_structures.md#consensusparams>
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Params {
+    /// Block size parameters
+    pub block: block::Size,
+
+    /// Evidence parameters
+    pub evidence: evidence::Params,
+
+    /// Validator parameters
+    pub validator: ValidatorParams,
+
+    /// Version parameters
+    pub version: Option<VersionParams>,
+}
+
+impl Protobuf<RawParams> for Params {}
+
+impl TryFrom<RawParams> for Params {
+    type Error = Error;
+
+    fn try_from(value: RawParams) -> Result<Self, Self::Error> {
+        let params = Self {
+            block: value
+                .block
+                .ok_or_else(|| Kind::InvalidConsensusParams.context("missing block params"))?
+                .try_into()?,
+            evidence: value
+                .evidence
+                .ok_or_else(|| Kind::InvalidConsensusParams.context("missing evidence params"))?
+                .try_into()?,
+            validator: value
+                .validator
+                .ok_or_else(|| Kind::InvalidConsensusParams.context("missing validator params"))?
+                .try_into()?,
+            version: value.version.map(Into::into),
+        };
+
+        params.validate()?;
+        Ok(params)
+    }
+}
+
+impl From<Params> for RawParams {
+    fn from(value: Params) -> Self {
+        RawParams {
+            block: Some(value.block.into()),
+            evidence: Some(value.evidence.into()),
+            validator: Some(value.validator.into()),
+            version: value.version.map(Into::into),
+        }
+    }
+}
+
+impl Params {
+    /// Check that these parameters are within the bounds Tendermint accepts
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.block.max_bytes == 0 || self.block.max_bytes > MAX_BLOCK_SIZE_BYTES {
+            return Err(Kind::InvalidConsensusParams
+                .context(format!(
+                    "block.max_bytes must be in (0, {}], got {}",
+                    MAX_BLOCK_SIZE_BYTES, self.block.max_bytes
+                ))
+                .into());
+        }
+
+        if self.block.max_gas < -1 {
+            return Err(Kind::InvalidConsensusParams
+                .context(format!(
+                    "block.max_gas must be greater or equal to -1, got {}",
+                    self.block.max_gas
+                ))
+                .into());
+        }
+
+        if self.evidence.max_age_num_blocks == 0 {
+            return Err(Kind::InvalidConsensusParams
+                .context("evidence.max_age_num_blocks must be positive")
+                .into());
+        }
+
+        if self.evidence.max_age_duration.as_nanos() == 0 {
+            return Err(Kind::InvalidConsensusParams
+                .context("evidence.max_age_duration must be positive")
+                .into());
+        }
+
+        if self.evidence.max_bytes < 0 || self.evidence.max_bytes as u64 > self.block.max_bytes {
+            return Err(Kind::InvalidConsensusParams
+                .context(format!(
+                    "evidence.max_bytes must be in [0, {}], got {}",
+                    self.block.max_bytes, self.evidence.max_bytes
+                ))
+                .into());
+        }
+
+        if self.validator.pub_key_types.is_empty() {
+            return Err(Kind::InvalidConsensusParams
+                .context("validator.pub_key_types must not be empty")
+                .into());
+        }
+
+        Ok(())
+    }
+
+    /// Compute the hash of these parameters, as committed to by
+    /// `Header::consensus_hash`.
+    ///
+    /// Only the block size parameters are hashed, as in Tendermint.
+    pub fn hash(&self) -> crate::Hash {
+        let hashed = RawHashedParams {
+            block_max_bytes: self.block.max_bytes as i64,
+            block_max_gas: self.block.max_gas,
+        };
+
+        let mut bytes = Vec::with_capacity(hashed.encoded_len());
+        hashed.encode(&mut bytes).unwrap(); // Vec<u8> is never too small
+
+        let digest = Sha256::digest(&bytes);
+        let mut hash = [0u8; SHA256_HASH_SIZE];
+        hash.copy_from_slice(&digest);
+        crate::Hash::Sha256(hash)
+    }
+}
+
+/// Validator consensus parameters
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ValidatorParams {
+    /// Public key algorithms validators are allowed to use
+    pub pub_key_types: Vec<public_key::Algorithm>,
+}
+
+impl Protobuf<RawValidatorParams> for ValidatorParams {}
+
+impl TryFrom<RawValidatorParams> for ValidatorParams {
+    type Error = Error;
+
+    fn try_from(value: RawValidatorParams) -> Result<Self, Self::Error> {
+        Ok(Self {
+            pub_key_types: value
+                .pub_key_types
+                .iter()
+                .map(|s| s.parse())
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+impl From<ValidatorParams> for RawValidatorParams {
+    fn from(value: ValidatorParams) -> Self {
+        RawValidatorParams {
+            pub_key_types: value
+                .pub_key_types
+                .iter()
+                .map(|algorithm| algorithm.as_str().to_owned())
+                .collect(),
+        }
+    }
+}
+
+/// Version consensus parameters
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct VersionParams {
+    /// Version of the application
+    pub app_version: u64,
+}
+
+impl Protobuf<RawVersionParams> for VersionParams {}
+
+impl From<RawVersionParams> for VersionParams {
+    fn from(value: RawVersionParams) -> Self {
+        Self {
+            app_version: value.app_version,
+        }
+    }
+}
+
+impl From<VersionParams> for RawVersionParams {
+    fn from(value: VersionParams) -> Self {
+        RawVersionParams {
+            app_version: value.app_version,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn params() -> Params {
+        Params {
+            block: block::Size {
+                max_bytes: 22_020_096,
+                max_gas: -1,
+            },
+            evidence: evidence::Params {
+                max_age_num_blocks: 100_000,
+                max_age_duration: Duration::from_secs(48 * 60 * 60),
+                max_bytes: 1_048_576,
+            },
+            validator: ValidatorParams {
+                pub_key_types: vec![public_key::Algorithm::Ed25519],
+            },
+            version: Some(VersionParams { app_version: 1 }),
+        }
+    }
+
+    #[test]
+    fn protobuf_round_trip() {
+        let raw = RawParams::from(params());
+        assert_eq!(raw.validator.as_ref().unwrap().pub_key_types, ["ed25519"]);
+        assert_eq!(Params::try_from(raw).unwrap(), params());
+    }
+
+    #[test]
+    fn out_of_bounds_params_are_rejected() {
+        assert!(params().validate().is_ok());
+
+        let mut p = params();
+        p.block.max_bytes = MAX_BLOCK_SIZE_BYTES + 1;
+        assert!(p.validate().is_err());
+
+        let mut p = params();
+        p.block.max_bytes = 0;
+        assert!(p.validate().is_err());
+
+        let mut p = params();
+        p.block.max_gas = -2;
+        assert!(p.validate().is_err());
+
+        let mut p = params();
+        p.evidence.max_bytes = p.block.max_bytes as i64 + 1;
+        assert!(p.validate().is_err());
+
+        let mut p = params();
+        p.evidence.max_age_num_blocks = 0;
+        assert!(p.validate().is_err());
+
+        let mut p = params();
+        p.validator.pub_key_types.clear();
+        assert!(Params::try_from(RawParams::from(p)).is_err());
+    }
+
+    #[test]
+    fn hash_only_covers_block_size() {
+        let mut p = params();
+        let hash = p.hash();
+
+        p.evidence.max_age_num_blocks += 1;
+        p.version = None;
+        assert_eq!(p.hash(), hash);
+
+        p.block.max_gas = 1_000;
+        assert_ne!(p.hash(), hash);
+    }
+}
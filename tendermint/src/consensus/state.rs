@@ -0,0 +1,122 @@
+//! Consensus state: the position of a signer within the consensus protocol
+
+use crate::{block, serializers};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fmt;
+
+extern crate prusti_contracts;
+use prusti_contracts::*;
+
+/// Step of a proposal
+pub const PROPOSE_STEP: i8 = 1;
+
+/// Step of a prevote
+pub const PREVOTE_STEP: i8 = 2;
+
+/// Step of a precommit
+pub const PRECOMMIT_STEP: i8 = 3;
+
+/// Position in the consensus protocol (height, round and step), along with
+/// the ID of the block signed at that position, if any.
+///
+/// States are ordered by height, then round, then step, so that a signer can
+/// refuse to sign at a position lower than the last one it signed.
+/// The block ID only breaks ties between states at the same position.
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub struct State {
+    /// Current block height
+    #[serde(with = "serializers::from_str")]
+    pub height: block::Height,
+
+    /// Current consensus round
+    pub round: block::Round,
+
+    /// Current consensus step (see `PROPOSE_STEP`, `PREVOTE_STEP` and `PRECOMMIT_STEP`)
+    pub step: i8,
+
+    /// Block ID being proposed or voted on, if any
+    pub block_id: Option<block::Id>,
+}
+
+impl State {
+    /// Height, round and step of this state, ignoring the block ID
+    pub fn position(&self) -> (block::Height, block::Round, i8) {
+        (self.height, self.round, self.step)
+    }
+
+    /// Get the short prefix of the block ID of this state, or `"none"`
+    pub fn block_id_prefix(&self) -> String {
+        self.block_id
+            .as_ref()
+            .map(block::Id::prefix)
+            .unwrap_or_else(|| "none".to_owned())
+    }
+}
+
+impl Ord for State {
+    fn cmp(&self, other: &State) -> Ordering {
+        self.position()
+            .cmp(&other.position())
+            .then_with(|| self.block_id.cmp(&other.block_id))
+    }
+}
+
+impl PartialOrd for State {
+    fn partial_cmp(&self, other: &State) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for State {
+    #[trusted]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}/{}/{} ({})",
+            self.height,
+            self.round,
+            self.step,
+            self.block_id_prefix()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(height: u32, round: u16, step: i8) -> State {
+        State {
+            height: block::Height::from(height),
+            round: block::Round::from(round),
+            step,
+            block_id: None,
+        }
+    }
+
+    #[test]
+    fn ordered_by_height_then_round_then_step() {
+        assert!(state(2, 0, PROPOSE_STEP) > state(1, 5, PRECOMMIT_STEP));
+        assert!(state(1, 1, PROPOSE_STEP) > state(1, 0, PRECOMMIT_STEP));
+        assert!(state(1, 0, PRECOMMIT_STEP) > state(1, 0, PREVOTE_STEP));
+        assert_eq!(
+            state(1, 0, PREVOTE_STEP).cmp(&state(1, 0, PREVOTE_STEP)),
+            Ordering::Equal
+        );
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let mut state = state(42, 1, PRECOMMIT_STEP);
+        state.block_id = Some(
+            "26C0A41F3243C6BCD7AD2DFF8A8D83A71D29D307B5326C227F734A1A512FE47D"
+                .parse()
+                .unwrap(),
+        );
+
+        let json = serde_json::to_string(&state).unwrap();
+        assert!(json.starts_with(r#"{"height":"42","round":"1","step":3,"#));
+        assert_eq!(serde_json::from_str::<State>(&json).unwrap(), state);
+    }
+}
@@ -0,0 +1,170 @@
+//! Persistent consensus state, protecting signers against double signing
+
+use super::State;
+use crate::{Error, Kind};
+use std::cmp::Ordering;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Consensus state persisted as JSON in a file, recording the last position
+/// at which a signer signed.
+///
+/// Updates are refused when the new state is not strictly beyond the last
+/// signed one, unless it is at the same position and for the same block ID
+/// (i.e. the exact same message is being signed again).
+/// The file is written atomically, so that a crash while updating it leaves
+/// either the previous or the new state on disk.
+#[derive(Debug)]
+pub struct StateFile {
+    path: PathBuf,
+    state: State,
+}
+
+impl StateFile {
+    /// Load the state file at the given path, or create it with the given
+    /// initial state if it does not exist
+    pub fn load_or_create(path: impl AsRef<Path>, initial: State) -> Result<Self, Error> {
+        let path = path.as_ref().to_owned();
+
+        if path.exists() {
+            let json = fs::read_to_string(&path)
+                .map_err(|e| Kind::Io.context(format!("{}: {}", path.display(), e)))?;
+            let state = serde_json::from_str(&json)
+                .map_err(|e| Kind::Parse.context(format!("{}: {}", path.display(), e)))?;
+
+            Ok(Self { path, state })
+        } else {
+            let state_file = Self {
+                path,
+                state: initial,
+            };
+            state_file.persist()?;
+            Ok(state_file)
+        }
+    }
+
+    /// Path of the state file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Last signed state
+    pub fn state(&self) -> &State {
+        &self.state
+    }
+
+    /// Check whether signing at the given state is allowed, without recording it
+    pub fn check(&self, new_state: &State) -> Result<(), Error> {
+        match new_state.position().cmp(&self.state.position()) {
+            Ordering::Greater => Ok(()),
+            Ordering::Equal if new_state.block_id == self.state.block_id => Ok(()),
+            _ => Err(Kind::DoubleSign
+                .context(format!(
+                    "last signed state: {}, attempted: {}",
+                    self.state, new_state
+                ))
+                .into()),
+        }
+    }
+
+    /// Record the given state as the last signed one, if signing at it is allowed
+    pub fn update(&mut self, new_state: State) -> Result<(), Error> {
+        self.check(&new_state)?;
+
+        let previous = std::mem::replace(&mut self.state, new_state);
+        self.persist().inspect_err(|_| {
+            self.state = previous;
+        })
+    }
+
+    /// Atomically and durably write the current state to disk, by writing it to
+    /// a temporary file which then replaces the state file
+    fn persist(&self) -> Result<(), Error> {
+        let json = serde_json::to_string(&self.state).map_err(|e| Kind::Parse.context(e))?;
+
+        let mut tmp_path = self.path.clone().into_os_string();
+        tmp_path.push(".tmp");
+        let tmp_path = PathBuf::from(tmp_path);
+
+        let io_error =
+            |e: std::io::Error| Kind::Io.context(format!("{}: {}", self.path.display(), e));
+
+        let mut file = File::create(&tmp_path).map_err(io_error)?;
+        file.write_all(json.as_bytes()).map_err(io_error)?;
+        file.sync_all().map_err(io_error)?;
+        fs::rename(&tmp_path, &self.path).map_err(io_error)?;
+
+        // Persist the rename itself, lest the previous state come back after a crash
+        let dir = match self.path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        File::open(dir)
+            .and_then(|dir| dir.sync_all())
+            .map_err(io_error)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block;
+    use crate::consensus::state::{PRECOMMIT_STEP, PREVOTE_STEP};
+
+    const BLOCK_ID: &str = "26C0A41F3243C6BCD7AD2DFF8A8D83A71D29D307B5326C227F734A1A512FE47D";
+
+    fn state(height: u32, step: i8, block_id: Option<&str>) -> State {
+        State {
+            height: block::Height::from(height),
+            round: block::Round::default(),
+            step,
+            block_id: block_id.map(|id| id.parse().unwrap()),
+        }
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "tendermint-{}-{}.json",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn refuses_to_sign_at_or_below_last_state() {
+        let path = temp_path("double-sign");
+        let mut file = StateFile::load_or_create(&path, state(1, PREVOTE_STEP, None)).unwrap();
+
+        file.update(state(1, PRECOMMIT_STEP, Some(BLOCK_ID))).unwrap();
+
+        // Same position and block ID: signing the same message again
+        assert!(file.update(state(1, PRECOMMIT_STEP, Some(BLOCK_ID))).is_ok());
+        // Same position, different block ID
+        assert!(file.update(state(1, PRECOMMIT_STEP, None)).is_err());
+        // Lower position
+        assert!(file.update(state(1, PREVOTE_STEP, Some(BLOCK_ID))).is_err());
+
+        file.update(state(2, PREVOTE_STEP, None)).unwrap();
+        assert_eq!(file.state(), &state(2, PREVOTE_STEP, None));
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn state_survives_reload() {
+        let path = temp_path("reload");
+        let mut file = StateFile::load_or_create(&path, state(1, PREVOTE_STEP, None)).unwrap();
+        file.update(state(5, PRECOMMIT_STEP, Some(BLOCK_ID))).unwrap();
+
+        let reloaded = StateFile::load_or_create(&path, state(1, PREVOTE_STEP, None)).unwrap();
+        assert_eq!(reloaded.state(), &state(5, PRECOMMIT_STEP, Some(BLOCK_ID)));
+        assert!(reloaded.check(&state(4, PRECOMMIT_STEP, None)).is_err());
+
+        fs::remove_file(&path).unwrap();
+    }
+}
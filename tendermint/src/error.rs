@@ -0,0 +1,170 @@
+//! Error types
+
+use anomaly::{BoxError, Context};
+use thiserror::Error;
+
+/// Error type
+pub type Error = BoxError;
+
+/// Kinds of errors
+#[derive(Clone, Eq, PartialEq, Debug, Error)]
+pub enum Kind {
+    /// Input/output error
+    #[error("I/O error")]
+    Io,
+
+    /// Error parsing data
+    #[error("parse error")]
+    Parse,
+
+    /// Value out-of-range
+    #[error("value out of range")]
+    OutOfRange,
+
+    /// Integer overflow
+    #[error("integer overflow")]
+    IntegerOverflow,
+
+    /// Negative height
+    #[error("negative height")]
+    NegativeHeight,
+
+    /// Negative round
+    #[error("negative round")]
+    NegativeRound,
+
+    /// Negative voting power
+    #[error("negative power")]
+    NegativePower,
+
+    /// Negative validator index in vote
+    #[error("negative validator index")]
+    NegativeValidatorIndex,
+
+    /// Invalid hash size
+    #[error("invalid hash size")]
+    InvalidHashSize,
+
+    /// Invalid block
+    #[error("invalid block")]
+    InvalidBlock,
+
+    /// Invalid first header
+    #[error("invalid first header")]
+    InvalidFirstHeader,
+
+    /// Invalid PartSetHeader
+    #[error("invalid part set header")]
+    InvalidPartSetHeader,
+
+    /// Invalid block part
+    #[error("invalid block part")]
+    InvalidPart,
+
+    /// Invalid Merkle proof
+    #[error("invalid Merkle proof")]
+    InvalidProof,
+
+    /// Invalid timestamp
+    #[error("invalid timestamp")]
+    InvalidTimestamp,
+
+    /// Missing timestamp
+    #[error("no timestamp")]
+    NoTimestamp,
+
+    /// Invalid signature
+    #[error("invalid signature")]
+    InvalidSignature,
+
+    /// Invalid validator address
+    #[error("invalid validator address")]
+    InvalidValidatorAddress,
+
+    /// Invalid consensus parameters
+    #[error("invalid consensus parameters")]
+    InvalidConsensusParams,
+
+    /// Invalid chain ID
+    #[error("invalid chain ID")]
+    InvalidChainId,
+
+    /// Invalid account ID length
+    #[error("invalid account ID length")]
+    InvalidAccountIdLength,
+
+    /// Invalid BlockIdFlag
+    #[error("invalid block id flag")]
+    BlockIdFlag,
+
+    /// Invalid message type
+    #[error("invalid message type")]
+    InvalidMessageType,
+
+    /// Invalid evidence
+    #[error("invalid evidence")]
+    InvalidEvidence,
+
+    /// Invalid signed header
+    #[error("invalid signed header")]
+    InvalidSignedHeader,
+
+    /// Missing signed header
+    #[error("missing signed header")]
+    MissingHeader,
+
+    /// Missing header version
+    #[error("missing version")]
+    MissingVersion,
+
+    /// Missing validator set
+    #[error("missing validator set")]
+    MissingValidatorSet,
+
+    /// Missing vote
+    #[error("missing vote")]
+    MissingVote,
+
+    /// Vote which does not belong to a vote set
+    #[error("invalid vote")]
+    InvalidVote,
+
+    /// Missing public key
+    #[error("missing public key")]
+    MissingPublicKey,
+
+    /// Malformed cryptographic key
+    #[error("invalid key")]
+    InvalidKey,
+
+    /// Signature does not verify against the public key
+    #[error("signature verification failed")]
+    SignatureVerificationFailed,
+
+    /// Number of commit signatures does not match the size of the validator set
+    #[error("commit signature count does not match validator count")]
+    SignatureCountMismatch,
+
+    /// Validator signed a commit more than once
+    #[error("duplicate validator in commit")]
+    DuplicateValidator,
+
+    /// Attempt to sign at or below the last signed consensus state
+    #[error("double sign attempt")]
+    DoubleSign,
+}
+
+impl Kind {
+    /// Add additional context.
+    pub fn context(self, source: impl Into<BoxError>) -> Context<Kind> {
+        Context::new(self, Some(source.into()))
+    }
+
+    /// Get the kind of the given error, if it was raised by this crate.
+    pub fn of(error: &Error) -> Option<&Kind> {
+        error
+            .downcast_ref::<Context<Kind>>()
+            .map(Context::kind)
+            .or_else(|| error.downcast_ref::<Kind>())
+    }
+}
@@ -0,0 +1,499 @@
+//! Evidence of malfeasance by validators (i.e. signing conflicting votes
+//! or attacking light clients).
+
+use crate::{
+    block::{self, signed_header::SignedHeader},
+    validator, vote, Error, Kind, Time, Vote,
+};
+use std::convert::{TryFrom, TryInto};
+use std::time::Duration;
+use tendermint_proto::google::protobuf::Duration as RawDuration;
+use tendermint_proto::types::{
+    evidence::Sum as RawSum, DuplicateVoteEvidence as RawDuplicateVoteEvidence,
+    Evidence as RawEvidence, EvidenceList as RawEvidenceList,
+    EvidenceParams as RawEvidenceParams, LightBlock as RawLightBlock,
+    LightClientAttackEvidence as RawLightClientAttackEvidence,
+};
+use tendermint_proto::Protobuf;
+
+/// Evidence of malfeasance by validators
+///
+/// <https://github.com/tendermint/spec/blob/d46cd7f573a2c6a2399fcab2cde981330aa63f37/spec/core/data_structures.md#evidence>
+#[derive(Clone, Debug, PartialEq)]
+pub enum Evidence {
+    /// Duplicate vote evidence
+    DuplicateVote(Box<DuplicateVoteEvidence>),
+
+    /// Light client attack evidence
+    LightClientAttack(Box<LightClientAttackEvidence>),
+}
+
+impl Protobuf<RawEvidence> for Evidence {}
+
+impl TryFrom<RawEvidence> for Evidence {
+    type Error = Error;
+
+    fn try_from(value: RawEvidence) -> Result<Self, Self::Error> {
+        match value.sum.ok_or(Kind::InvalidEvidence)? {
+            RawSum::DuplicateVoteEvidence(ev) => Ok(Evidence::DuplicateVote(Box::new(ev.try_into()?))),
+            RawSum::LightClientAttackEvidence(ev) => {
+                Ok(Evidence::LightClientAttack(Box::new(ev.try_into()?)))
+            }
+        }
+    }
+}
+
+impl From<Evidence> for RawEvidence {
+    fn from(value: Evidence) -> Self {
+        let sum = match value {
+            Evidence::DuplicateVote(ev) => RawSum::DuplicateVoteEvidence((*ev).into()),
+            Evidence::LightClientAttack(ev) => RawSum::LightClientAttackEvidence((*ev).into()),
+        };
+        RawEvidence { sum: Some(sum) }
+    }
+}
+
+/// Evidence data: the list of evidence included in a block
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Data {
+    evidence: Vec<Evidence>,
+}
+
+impl Protobuf<RawEvidenceList> for Data {}
+
+impl TryFrom<RawEvidenceList> for Data {
+    type Error = Error;
+
+    fn try_from(value: RawEvidenceList) -> Result<Self, Self::Error> {
+        Ok(Data {
+            evidence: value
+                .evidence
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<Result<Vec<_>, _>>()?,
+        })
+    }
+}
+
+impl From<Data> for RawEvidenceList {
+    fn from(value: Data) -> Self {
+        RawEvidenceList {
+            evidence: value.evidence.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl Data {
+    /// Create evidence data from the given list of evidence
+    pub fn new(evidence: Vec<Evidence>) -> Data {
+        Data { evidence }
+    }
+
+    /// Iterate over the evidence
+    pub fn iter(&self) -> std::slice::Iter<'_, Evidence> {
+        self.evidence.iter()
+    }
+
+    /// Whether there is no evidence
+    pub fn is_empty(&self) -> bool {
+        self.evidence.is_empty()
+    }
+}
+
+/// Duplicate vote evidence: a validator signed two conflicting votes
+/// for the same height, round and vote type.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DuplicateVoteEvidence {
+    /// The vote for the lower of the two block IDs
+    pub vote_a: Vote,
+    /// The vote for the higher of the two block IDs
+    pub vote_b: Vote,
+    /// Total voting power of the validator set at the height of the votes
+    pub total_voting_power: vote::Power,
+    /// Voting power of the offending validator
+    pub validator_power: vote::Power,
+    /// Time of the block at the height of the votes
+    pub timestamp: Time,
+}
+
+impl Protobuf<RawDuplicateVoteEvidence> for DuplicateVoteEvidence {}
+
+impl TryFrom<RawDuplicateVoteEvidence> for DuplicateVoteEvidence {
+    type Error = Error;
+
+    fn try_from(value: RawDuplicateVoteEvidence) -> Result<Self, Self::Error> {
+        Self::new(
+            value.vote_a.ok_or(Kind::MissingVote)?.try_into()?,
+            value.vote_b.ok_or(Kind::MissingVote)?.try_into()?,
+            value.total_voting_power.try_into()?,
+            value.validator_power.try_into()?,
+            value.timestamp.ok_or(Kind::NoTimestamp)?.try_into()?,
+        )
+    }
+}
+
+impl From<DuplicateVoteEvidence> for RawDuplicateVoteEvidence {
+    fn from(value: DuplicateVoteEvidence) -> Self {
+        RawDuplicateVoteEvidence {
+            vote_a: Some(value.vote_a.into()),
+            vote_b: Some(value.vote_b.into()),
+            total_voting_power: value.total_voting_power.into(),
+            validator_power: value.validator_power.into(),
+            timestamp: Some(value.timestamp.into()),
+        }
+    }
+}
+
+impl DuplicateVoteEvidence {
+    /// Constructor, which orders the votes by block ID and checks that they
+    /// actually conflict with each other.
+    pub fn new(
+        vote_a: Vote,
+        vote_b: Vote,
+        total_voting_power: vote::Power,
+        validator_power: vote::Power,
+        timestamp: Time,
+    ) -> Result<Self, Error> {
+        if vote_a.height != vote_b.height
+            || vote_a.round != vote_b.round
+            || vote_a.vote_type != vote_b.vote_type
+        {
+            return Err(Kind::InvalidEvidence
+                .context("votes are not for the same height, round and type")
+                .into());
+        }
+        if vote_a.validator_address != vote_b.validator_address {
+            return Err(Kind::InvalidEvidence
+                .context("votes are from different validators")
+                .into());
+        }
+        if vote_a.block_id == vote_b.block_id {
+            return Err(Kind::InvalidEvidence
+                .context("votes are for the same block ID")
+                .into());
+        }
+        if validator_power > total_voting_power {
+            return Err(Kind::InvalidEvidence
+                .context("validator power exceeds total voting power")
+                .into());
+        }
+
+        let (vote_a, vote_b) = if vote_a.block_id <= vote_b.block_id {
+            (vote_a, vote_b)
+        } else {
+            (vote_b, vote_a)
+        };
+
+        Ok(Self {
+            vote_a,
+            vote_b,
+            total_voting_power,
+            validator_power,
+            timestamp,
+        })
+    }
+
+    /// Height of the conflicting votes
+    pub fn height(&self) -> block::Height {
+        self.vote_a.height
+    }
+}
+
+/// A light block, as embedded in light client attack evidence:
+/// a signed header together with the validator set which signed it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConflictingBlock {
+    /// Header and commit of the conflicting block
+    pub signed_header: SignedHeader,
+    /// Validator set of the conflicting block
+    pub validator_set: validator::Set,
+}
+
+impl Protobuf<RawLightBlock> for ConflictingBlock {}
+
+impl TryFrom<RawLightBlock> for ConflictingBlock {
+    type Error = Error;
+
+    fn try_from(value: RawLightBlock) -> Result<Self, Self::Error> {
+        Ok(Self {
+            signed_header: value.signed_header.ok_or(Kind::MissingHeader)?.try_into()?,
+            validator_set: value
+                .validator_set
+                .ok_or(Kind::MissingValidatorSet)?
+                .try_into()?,
+        })
+    }
+}
+
+impl From<ConflictingBlock> for RawLightBlock {
+    fn from(value: ConflictingBlock) -> Self {
+        RawLightBlock {
+            signed_header: Some(value.signed_header.into()),
+            validator_set: Some(value.validator_set.into()),
+        }
+    }
+}
+
+/// Light client attack evidence: a light block which conflicts with the
+/// one on the main chain, together with the validators deemed responsible.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LightClientAttackEvidence {
+    /// The conflicting light block
+    pub conflicting_block: ConflictingBlock,
+    /// Height of the last block both chains agree on
+    pub common_height: block::Height,
+    /// Validators which signed the conflicting block, sorted by voting power
+    pub byzantine_validators: Vec<validator::Info>,
+    /// Total voting power of the validator set at the common height
+    pub total_voting_power: vote::Power,
+    /// Time of the block at the common height
+    pub timestamp: Time,
+}
+
+impl Protobuf<RawLightClientAttackEvidence> for LightClientAttackEvidence {}
+
+impl TryFrom<RawLightClientAttackEvidence> for LightClientAttackEvidence {
+    type Error = Error;
+
+    fn try_from(value: RawLightClientAttackEvidence) -> Result<Self, Self::Error> {
+        Ok(Self {
+            conflicting_block: value
+                .conflicting_block
+                .ok_or_else(|| Kind::InvalidEvidence.context("missing conflicting block"))?
+                .try_into()?,
+            common_height: value.common_height.try_into()?,
+            byzantine_validators: value
+                .byzantine_validators
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<Result<Vec<_>, _>>()?,
+            total_voting_power: value.total_voting_power.try_into()?,
+            timestamp: value.timestamp.ok_or(Kind::NoTimestamp)?.try_into()?,
+        })
+    }
+}
+
+impl From<LightClientAttackEvidence> for RawLightClientAttackEvidence {
+    fn from(value: LightClientAttackEvidence) -> Self {
+        RawLightClientAttackEvidence {
+            conflicting_block: Some(value.conflicting_block.into()),
+            common_height: value.common_height.into(),
+            byzantine_validators: value
+                .byzantine_validators
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+            total_voting_power: value.total_voting_power.into(),
+            timestamp: Some(value.timestamp.into()),
+        }
+    }
+}
+
+impl LightClientAttackEvidence {
+    /// Whether the conflicting header could not have been derived from the
+    /// state of the trusted header at the same height, i.e. whether this is
+    /// evidence of a lunatic attack.
+    ///
+    /// <https://github.com/tendermint/tendermint/blob/v0.34.0/types/evidence.go#L253-L259>
+    pub fn conflicting_header_is_invalid(&self, trusted_header: &block::Header) -> bool {
+        let conflicting_header = &self.conflicting_block.signed_header.header;
+
+        trusted_header.validators_hash != conflicting_header.validators_hash
+            || trusted_header.next_validators_hash != conflicting_header.next_validators_hash
+            || trusted_header.consensus_hash != conflicting_header.consensus_hash
+            || trusted_header.app_hash != conflicting_header.app_hash
+            || trusted_header.last_results_hash != conflicting_header.last_results_hash
+    }
+
+    /// Height of the conflicting block
+    pub fn height(&self) -> block::Height {
+        self.conflicting_block.signed_header.header.height
+    }
+}
+
+/// Evidence parameters, part of the consensus parameters
+///
+/// <https://github.com/tendermint/spec/blob/d46cd7f573a2c6a2399fcab2cde981330aa63f37/spec/core/data_structures.md#evidenceparams>
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Params {
+    /// Maximum age of evidence, in blocks
+    pub max_age_num_blocks: u64,
+
+    /// Maximum age of evidence, in time.
+    ///
+    /// Evidence is only considered expired once it is older than both
+    /// `max_age_num_blocks` and `max_age_duration`.
+    pub max_age_duration: Duration,
+
+    /// Maximum total size of the evidence of a block, in bytes
+    pub max_bytes: i64,
+}
+
+impl Protobuf<RawEvidenceParams> for Params {}
+
+impl TryFrom<RawEvidenceParams> for Params {
+    type Error = Error;
+
+    fn try_from(value: RawEvidenceParams) -> Result<Self, Self::Error> {
+        let max_age_duration = value
+            .max_age_duration
+            .ok_or_else(|| Kind::InvalidConsensusParams.context("missing max_age_duration"))?;
+
+        Ok(Self {
+            max_age_num_blocks: value
+                .max_age_num_blocks
+                .try_into()
+                .map_err(|_| Kind::NegativeHeight)?,
+            max_age_duration: Duration::new(
+                max_age_duration
+                    .seconds
+                    .try_into()
+                    .map_err(|_| Kind::OutOfRange)?,
+                max_age_duration
+                    .nanos
+                    .try_into()
+                    .map_err(|_| Kind::OutOfRange)?,
+            ),
+            max_bytes: value.max_bytes,
+        })
+    }
+}
+
+impl From<Params> for RawEvidenceParams {
+    fn from(value: Params) -> Self {
+        Self {
+            // Todo: make the struct more robust so this can become infallible.
+            max_age_num_blocks: value.max_age_num_blocks as i64,
+            max_age_duration: Some(RawDuration {
+                seconds: value.max_age_duration.as_secs() as i64,
+                nanos: value.max_age_duration.subsec_nanos() as i32,
+            }),
+            max_bytes: value.max_bytes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::account;
+    use crate::block::{CommitSig, Round};
+    use crate::public_key::{Ed25519, PublicKey};
+    use ed25519_dalek::SecretKey;
+    use std::str::FromStr;
+    use tendermint_proto::types::Header as RawHeader;
+
+    fn vote(block_id: Option<block::Id>) -> Vote {
+        Vote {
+            vote_type: vote::Type::Precommit,
+            height: block::Height::from(10_u32),
+            round: Round::from(1_u16),
+            block_id,
+            timestamp: Some(Time::unix_epoch()),
+            validator_address: account::Id::new([0xAB; account::LENGTH]),
+            validator_index: 0_u32.try_into().unwrap(),
+            signature: vec![1; 64].try_into().unwrap(),
+        }
+    }
+
+    fn block_id(hash: &str) -> Option<block::Id> {
+        Some(block::Id::from_str(hash).unwrap())
+    }
+
+    const HASH_A: &str = "26C0A41F3243C6BCD7AD2DFF8A8D83A71D29D307B5326C227F734A1A512FE47D";
+    const HASH_B: &str = "F2C5A5E6D4E1E0B8D4B3C4E0E4C6E2D0D4C4E8C6A2A0B2D0C4E6E8D0C2A4B6D8";
+
+    #[test]
+    fn duplicate_vote_evidence_orders_votes() {
+        let ev = DuplicateVoteEvidence::new(
+            vote(block_id(HASH_B)),
+            vote(block_id(HASH_A)),
+            10_u32.into(),
+            1_u32.into(),
+            Time::unix_epoch(),
+        )
+        .unwrap();
+        assert_eq!(ev.vote_a.block_id, block_id(HASH_A));
+        assert_eq!(ev.vote_b.block_id, block_id(HASH_B));
+    }
+
+    #[test]
+    fn duplicate_vote_evidence_rejects_identical_votes() {
+        let ev = DuplicateVoteEvidence::new(
+            vote(block_id(HASH_A)),
+            vote(block_id(HASH_A)),
+            10_u32.into(),
+            1_u32.into(),
+            Time::unix_epoch(),
+        );
+        assert!(ev.is_err());
+    }
+
+    #[test]
+    fn duplicate_vote_evidence_protobuf_roundtrip() {
+        let ev = Evidence::DuplicateVote(Box::new(
+            DuplicateVoteEvidence::new(
+                vote(block_id(HASH_A)),
+                vote(None),
+                10_u32.into(),
+                1_u32.into(),
+                Time::unix_epoch(),
+            )
+            .unwrap(),
+        ));
+        let encoded = ev.encode_vec().unwrap();
+        assert_eq!(Evidence::decode_vec(&encoded).unwrap(), ev);
+    }
+
+    fn validator(seed: u8, power: u32) -> validator::Info {
+        let public_key = Ed25519::from(&SecretKey::from_bytes(&[seed; 32]).unwrap());
+        validator::Info {
+            address: account::Id::from(public_key),
+            pub_key: PublicKey::Ed25519(public_key),
+            power: vote::Power::from(power),
+            proposer_priority: validator::ProposerPriority::from(0),
+        }
+    }
+
+    fn conflicting_block(validators: Vec<validator::Info>) -> ConflictingBlock {
+        let raw_header: RawHeader = serde_json::from_str(include_str!(
+            "../tests/support/serialization/block/header_with_known_hash.json"
+        ))
+        .unwrap();
+        let header = block::Header::try_from(raw_header).unwrap();
+        let commit = block::Commit {
+            height: header.height,
+            round: Round::from(0_u16),
+            block_id: block::Id::from_str(HASH_A).unwrap(),
+            signatures: validators
+                .iter()
+                .map(|validator| CommitSig::BlockIDFlagCommit {
+                    validator_address: validator.address,
+                    timestamp: header.time,
+                    signature: vec![2; 64].try_into().unwrap(),
+                })
+                .collect(),
+        };
+
+        ConflictingBlock {
+            signed_header: SignedHeader::new(header, commit).unwrap(),
+            validator_set: validator::Set::without_proposer(validators).unwrap(),
+        }
+    }
+
+    #[test]
+    fn light_client_attack_evidence_protobuf_roundtrip() {
+        let validators = vec![validator(1, 30), validator(2, 20), validator(3, 10)];
+        let conflicting_block = conflicting_block(validators.clone());
+        let ev = Evidence::LightClientAttack(Box::new(LightClientAttackEvidence {
+            common_height: block::Height::from(2_u32),
+            byzantine_validators: validators[..2].to_vec(),
+            total_voting_power: conflicting_block.validator_set.total_voting_power(),
+            timestamp: conflicting_block.signed_header.header.time,
+            conflicting_block,
+        }));
+
+        let encoded = ev.encode_vec().unwrap();
+        assert_eq!(Evidence::decode_vec(&encoded).unwrap(), ev);
+    }
+}
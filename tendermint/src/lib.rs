@@ -1,7 +1,27 @@
+pub mod abci;
+pub mod account;
 pub mod block;
+pub mod chain;
+pub mod consensus;
+pub mod error;
+pub mod evidence;
 pub mod hash;
+pub mod merkle;
+pub mod public_key;
+pub mod serializers;
+pub mod signature;
+pub mod time;
+pub mod validator;
+pub mod vote;
 
+/// The protobuf definitions the domain types of this crate convert to and from
+pub use tendermint_proto as proto;
 
 pub use crate::{
+    error::{Error, Kind},
     hash::Hash,
+    public_key::PublicKey,
+    signature::Signature,
+    time::Time,
+    vote::Vote,
 };
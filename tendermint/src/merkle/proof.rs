@@ -0,0 +1,273 @@
+//! Merkle proofs of inclusion of a leaf in a simple Merkle tree
+
+use super::{empty_hash, get_split_point, inner_hash, leaf_hash, Hash, HASH_SIZE};
+use crate::{Error, Kind};
+use std::convert::{TryFrom, TryInto};
+use tendermint_proto::crypto::Proof as RawProof;
+use tendermint_proto::Protobuf;
+
+/// Proof that a leaf is included in a simple Merkle tree with a given root.
+///
+/// The aunts are the hashes of the siblings of the nodes on the path from
+/// the leaf to the root, starting with the sibling of the leaf itself.
+///
+/// <https://github.com/tendermint/tendermint/blob/v0.34.0/crypto/merkle/proof.go>
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Proof {
+    /// Total number of leaves in the tree
+    pub total: u64,
+
+    /// Index of the leaf in the tree
+    pub index: u64,
+
+    /// Hash of the leaf
+    pub leaf_hash: Hash,
+
+    /// Hashes of the siblings on the path from the leaf to the root
+    pub aunts: Vec<Hash>,
+}
+
+impl Protobuf<RawProof> for Proof {}
+
+impl TryFrom<RawProof> for Proof {
+    type Error = Error;
+
+    fn try_from(value: RawProof) -> Result<Self, Self::Error> {
+        Ok(Proof {
+            total: value.total.try_into().map_err(|_| Kind::InvalidProof)?,
+            index: value.index.try_into().map_err(|_| Kind::InvalidProof)?,
+            leaf_hash: to_hash(value.leaf_hash)?,
+            aunts: value
+                .aunts
+                .into_iter()
+                .map(to_hash)
+                .collect::<Result<Vec<_>, _>>()?,
+        })
+    }
+}
+
+impl From<Proof> for RawProof {
+    fn from(value: Proof) -> Self {
+        RawProof {
+            total: value.total as i64,
+            index: value.index as i64,
+            leaf_hash: value.leaf_hash.to_vec(),
+            aunts: value.aunts.iter().map(|aunt| aunt.to_vec()).collect(),
+        }
+    }
+}
+
+fn to_hash(bytes: Vec<u8>) -> Result<Hash, Error> {
+    if bytes.len() != HASH_SIZE {
+        return Err(Kind::InvalidHashSize.into());
+    }
+
+    let mut hash = [0u8; HASH_SIZE];
+    hash.copy_from_slice(&bytes);
+    Ok(hash)
+}
+
+impl Proof {
+    /// Check that the index lies within the tree and that there is one aunt
+    /// per level between the leaf and the root.
+    ///
+    /// <https://github.com/tendermint/tendermint/blob/v0.34.0/crypto/merkle/proof.go#L115-L136>
+    pub fn validate_basic(&self) -> Result<(), Error> {
+        if self.index >= self.total {
+            return Err(Kind::InvalidProof
+                .context("leaf index is not within the tree")
+                .into());
+        }
+
+        let total = usize::try_from(self.total)
+            .map_err(|_| Kind::InvalidProof.context("too many leaves"))?;
+
+        if self.aunts.len() != leaf_depth(self.index as usize, total) {
+            return Err(Kind::InvalidProof
+                .context("number of aunts does not match the depth of the leaf")
+                .into());
+        }
+
+        Ok(())
+    }
+
+    /// Check that this proves the inclusion of the given leaf
+    /// in the tree with the given root hash.
+    pub fn verify(&self, root_hash: &Hash, leaf: &[u8]) -> Result<(), Error> {
+        self.validate_basic()?;
+
+        if self.leaf_hash != leaf_hash(leaf) {
+            return Err(Kind::InvalidProof.context("leaf hash mismatch").into());
+        }
+
+        match self.compute_root_hash() {
+            Some(computed) if &computed == root_hash => Ok(()),
+            Some(_) => Err(Kind::InvalidProof.context("root hash mismatch").into()),
+            None => Err(Kind::InvalidProof.context("malformed proof").into()),
+        }
+    }
+
+    /// Compute the root hash of the tree from the leaf hash and the aunts,
+    /// or `None` if the aunts do not match the shape of the tree.
+    pub fn compute_root_hash(&self) -> Option<Hash> {
+        self.validate_basic().ok()?;
+        compute_hash_from_aunts(self.index, self.total, self.leaf_hash, &self.aunts)
+    }
+}
+
+/// Compute the root hash of a simple Merkle tree from the given leaves,
+/// together with a proof of inclusion for each leaf.
+pub fn proofs_from_byte_vectors(items: &[Vec<u8>]) -> (Hash, Vec<Proof>) {
+    let total = items.len() as u64;
+    let (root_hash, trails) = trails_from_byte_slices(items);
+
+    let proofs = items
+        .iter()
+        .zip(trails)
+        .enumerate()
+        .map(|(index, (item, aunts))| Proof {
+            total,
+            index: index as u64,
+            leaf_hash: leaf_hash(item),
+            aunts,
+        })
+        .collect();
+
+    (root_hash, proofs)
+}
+
+// Returns the root hash of the subtree with the given leaves,
+// and the aunts of each leaf within that subtree.
+fn trails_from_byte_slices(items: &[Vec<u8>]) -> (Hash, Vec<Vec<Hash>>) {
+    match items.len() {
+        0 => (empty_hash(), vec![]),
+        1 => (leaf_hash(&items[0]), vec![vec![]]),
+        length => {
+            let k = get_split_point(length);
+            let (left_root, mut lefts) = trails_from_byte_slices(&items[..k]);
+            let (right_root, mut rights) = trails_from_byte_slices(&items[k..]);
+
+            lefts.iter_mut().for_each(|aunts| aunts.push(right_root));
+            rights.iter_mut().for_each(|aunts| aunts.push(left_root));
+            lefts.append(&mut rights);
+
+            (inner_hash(&left_root, &right_root), lefts)
+        }
+    }
+}
+
+// Returns the depth of the leaf at the given index in a tree with the given
+// number of leaves, which is the number of aunts in its proof.
+fn leaf_depth(mut index: usize, mut total: usize) -> usize {
+    let mut depth = 0;
+    while total > 1 {
+        let num_left = get_split_point(total);
+        if index < num_left {
+            total = num_left;
+        } else {
+            index -= num_left;
+            total -= num_left;
+        }
+        depth += 1;
+    }
+    depth
+}
+
+fn compute_hash_from_aunts(index: u64, total: u64, leaf: Hash, aunts: &[Hash]) -> Option<Hash> {
+    if index >= total {
+        return None;
+    }
+
+    match total {
+        0 => None,
+        1 if aunts.is_empty() => Some(leaf),
+        1 => None,
+        _ => {
+            let (last, rest) = aunts.split_last()?;
+            let num_left = get_split_point(total as usize) as u64;
+
+            if index < num_left {
+                let left = compute_hash_from_aunts(index, num_left, leaf, rest)?;
+                Some(inner_hash(&left, last))
+            } else {
+                let right =
+                    compute_hash_from_aunts(index - num_left, total - num_left, leaf, rest)?;
+                Some(inner_hash(last, &right))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::merkle::simple_hash_from_byte_vectors;
+
+    fn leaves(count: usize) -> Vec<Vec<u8>> {
+        (0..count).map(|i| format!("leaf {}", i).into_bytes()).collect()
+    }
+
+    #[test]
+    fn proofs_verify_against_root() {
+        for count in 1..=9 {
+            let items = leaves(count);
+            let (root, proofs) = proofs_from_byte_vectors(&items);
+
+            assert_eq!(root, simple_hash_from_byte_vectors(items.clone()));
+            assert_eq!(proofs.len(), count);
+
+            for (item, proof) in items.iter().zip(&proofs) {
+                assert!(proof.verify(&root, item).is_ok());
+                assert!(proof.verify(&root, b"another leaf").is_err());
+            }
+        }
+    }
+
+    #[test]
+    fn tampered_proofs_are_rejected() {
+        let items = leaves(5);
+        let (root, proofs) = proofs_from_byte_vectors(&items);
+
+        let mut proof = proofs[3].clone();
+        proof.index = 2;
+        assert!(proof.verify(&root, &items[3]).is_err());
+
+        let mut proof = proofs[3].clone();
+        proof.aunts.pop();
+        assert!(proof.verify(&root, &items[3]).is_err());
+
+        let mut proof = proofs[3].clone();
+        proof.total = 4;
+        assert!(proof.verify(&root, &items[3]).is_err());
+    }
+
+    #[test]
+    fn malformed_proofs_are_rejected_up_front() {
+        let items = leaves(5);
+        let (_, proofs) = proofs_from_byte_vectors(&items);
+
+        let mut proof = proofs[4].clone();
+        proof.index = 5;
+        assert!(proof.validate_basic().is_err());
+
+        let mut proof = proofs[4].clone();
+        proof.aunts.push(proof.aunts[0]);
+        assert!(proof.validate_basic().is_err());
+
+        for total in &[u64::MAX, 1 << 63, (1 << 63) + 1] {
+            let mut proof = proofs[4].clone();
+            proof.total = *total;
+            assert!(proof.validate_basic().is_err());
+            assert_eq!(proof.compute_root_hash(), None);
+        }
+
+        assert!(proofs.iter().all(|proof| proof.validate_basic().is_ok()));
+    }
+
+    #[test]
+    fn protobuf_round_trip() {
+        let (_, proofs) = proofs_from_byte_vectors(&leaves(3));
+        let raw = RawProof::from(proofs[1].clone());
+        assert_eq!(Proof::try_from(raw).unwrap(), proofs[1]);
+    }
+}
@@ -0,0 +1,305 @@
+//! Public keys used in Tendermint networks
+
+pub use ed25519_dalek::PublicKey as Ed25519;
+#[cfg(feature = "secp256k1")]
+pub use k256::ecdsa::VerifyingKey as Secp256k1;
+
+use crate::error::{Error, Kind};
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+use subtle_encoding::{base64, bech32, hex};
+use tendermint_proto::crypto::public_key::Sum;
+use tendermint_proto::crypto::PublicKey as RawPublicKey;
+use tendermint_proto::Protobuf;
+
+extern crate prusti_contracts;
+use prusti_contracts::*;
+
+/// Amino prefix of Ed25519 public keys, used in their Bech32 encoding
+const ED25519_AMINO_PREFIX: [u8; 5] = [0x16, 0x24, 0xDE, 0x64, 0x20];
+
+/// Amino prefix of secp256k1 public keys, used in their Bech32 encoding
+#[cfg(feature = "secp256k1")]
+const SECP256K1_AMINO_PREFIX: [u8; 5] = [0xEB, 0x5A, 0xE9, 0x87, 0x21];
+
+/// JSON type tag of Ed25519 public keys
+const ED25519_TYPE: &str = "tendermint/PubKeyEd25519";
+
+/// JSON type tag of secp256k1 public keys
+#[cfg(feature = "secp256k1")]
+const SECP256K1_TYPE: &str = "tendermint/PubKeySecp256k1";
+
+/// Public keys allowed in Tendermint protocols
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum PublicKey {
+    /// Ed25519 keys
+    Ed25519(Ed25519),
+
+    /// Secp256k1 keys
+    #[cfg(feature = "secp256k1")]
+    Secp256k1(Secp256k1),
+}
+
+impl Protobuf<RawPublicKey> for PublicKey {}
+
+impl TryFrom<RawPublicKey> for PublicKey {
+    type Error = Error;
+
+    fn try_from(value: RawPublicKey) -> Result<Self, Self::Error> {
+        let sum = value
+            .sum
+            .ok_or_else(|| Kind::InvalidKey.context("empty public key"))?;
+
+        match sum {
+            Sum::Ed25519(bytes) => Self::from_raw_ed25519(&bytes),
+            #[cfg(feature = "secp256k1")]
+            Sum::Secp256k1(bytes) => Self::from_raw_secp256k1(&bytes),
+            #[cfg(not(feature = "secp256k1"))]
+            Sum::Secp256k1(_) => None,
+        }
+        .ok_or_else(|| Kind::InvalidKey.into())
+    }
+}
+
+impl From<PublicKey> for RawPublicKey {
+    fn from(value: PublicKey) -> Self {
+        let sum = match value {
+            PublicKey::Ed25519(pk) => Sum::Ed25519(pk.as_bytes().to_vec()),
+            #[cfg(feature = "secp256k1")]
+            PublicKey::Secp256k1(pk) => Sum::Secp256k1(pk.to_bytes().to_vec()),
+        };
+
+        RawPublicKey { sum: Some(sum) }
+    }
+}
+
+impl PublicKey {
+    /// From raw Ed25519 public key bytes
+    pub fn from_raw_ed25519(bytes: &[u8]) -> Option<PublicKey> {
+        Ed25519::from_bytes(bytes).map(PublicKey::Ed25519).ok()
+    }
+
+    /// From raw secp256k1 public key bytes, in compressed SEC1 encoding
+    #[cfg(feature = "secp256k1")]
+    pub fn from_raw_secp256k1(bytes: &[u8]) -> Option<PublicKey> {
+        Secp256k1::from_sec1_bytes(bytes)
+            .map(PublicKey::Secp256k1)
+            .ok()
+    }
+
+    /// Get the Ed25519 public key, if this is one
+    pub fn ed25519(self) -> Option<Ed25519> {
+        #[allow(unreachable_patterns)]
+        match self {
+            PublicKey::Ed25519(pk) => Some(pk),
+            _ => None,
+        }
+    }
+
+    /// Get the secp256k1 public key, if this is one
+    #[cfg(feature = "secp256k1")]
+    pub fn secp256k1(self) -> Option<Secp256k1> {
+        match self {
+            PublicKey::Secp256k1(pk) => Some(pk),
+            _ => None,
+        }
+    }
+
+    /// Serialize this key as raw bytes
+    pub fn to_bytes(self) -> Vec<u8> {
+        match self {
+            PublicKey::Ed25519(pk) => pk.as_bytes().to_vec(),
+            #[cfg(feature = "secp256k1")]
+            PublicKey::Secp256k1(pk) => pk.to_bytes().to_vec(),
+        }
+    }
+
+    /// Serialize this key as Bech32 with the given human readable part,
+    /// prefixed with its Amino type prefix
+    pub fn to_bech32(self, hrp: &str) -> String {
+        let mut data = match self {
+            PublicKey::Ed25519(_) => ED25519_AMINO_PREFIX.to_vec(),
+            #[cfg(feature = "secp256k1")]
+            PublicKey::Secp256k1(_) => SECP256K1_AMINO_PREFIX.to_vec(),
+        };
+        data.extend_from_slice(&self.to_bytes());
+
+        bech32::encode(hrp, data)
+    }
+
+    /// Serialize this key as upper-case hexadecimal
+    pub fn to_hex(self) -> String {
+        String::from_utf8(hex::encode_upper(self.to_bytes())).unwrap()
+    }
+
+    /// JSON type tag of this key
+    fn type_tag(self) -> &'static str {
+        match self {
+            PublicKey::Ed25519(_) => ED25519_TYPE,
+            #[cfg(feature = "secp256k1")]
+            PublicKey::Secp256k1(_) => SECP256K1_TYPE,
+        }
+    }
+}
+
+impl From<Ed25519> for PublicKey {
+    fn from(pk: Ed25519) -> Self {
+        PublicKey::Ed25519(pk)
+    }
+}
+
+#[cfg(feature = "secp256k1")]
+impl From<Secp256k1> for PublicKey {
+    fn from(pk: Secp256k1) -> Self {
+        PublicKey::Secp256k1(pk)
+    }
+}
+
+/// Public key algorithms, as named in the validator consensus parameters
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub enum Algorithm {
+    /// Ed25519
+    Ed25519,
+
+    /// Secp256k1
+    Secp256k1,
+}
+
+impl Algorithm {
+    /// Name of this algorithm, as used by Tendermint
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Algorithm::Ed25519 => "ed25519",
+            Algorithm::Secp256k1 => "secp256k1",
+        }
+    }
+}
+
+impl fmt::Display for Algorithm {
+    #[trusted]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Algorithm {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        match s {
+            "ed25519" => Ok(Algorithm::Ed25519),
+            "secp256k1" => Ok(Algorithm::Secp256k1),
+            _ => Err(Kind::Parse
+                .context(format!("unknown public key algorithm: {}", s))
+                .into()),
+        }
+    }
+}
+
+impl fmt::Display for PublicKey {
+    #[trusted]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_hex())
+    }
+}
+
+/// JSON representation of public keys, as produced by Tendermint:
+/// `{"type": "tendermint/PubKeyEd25519", "value": "<base64>"}`
+#[derive(Serialize, Deserialize)]
+struct JsonPublicKey {
+    #[serde(rename = "type")]
+    type_tag: String,
+    value: String,
+}
+
+impl Serialize for PublicKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        JsonPublicKey {
+            type_tag: self.type_tag().to_owned(),
+            value: String::from_utf8(base64::encode(self.to_bytes())).unwrap(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for PublicKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let json = JsonPublicKey::deserialize(deserializer)?;
+        let bytes = base64::decode(&json.value)
+            .map_err(|e| D::Error::custom(format!("invalid base64 public key: {}", e)))?;
+
+        let public_key = match json.type_tag.as_str() {
+            ED25519_TYPE => PublicKey::from_raw_ed25519(&bytes),
+            #[cfg(feature = "secp256k1")]
+            SECP256K1_TYPE => PublicKey::from_raw_secp256k1(&bytes),
+            other => {
+                return Err(D::Error::custom(format!(
+                    "unsupported public key type: {}",
+                    other
+                )))
+            }
+        };
+
+        public_key.ok_or_else(|| D::Error::custom("invalid public key"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // RFC 8032 test vector 1
+    const ED25519_KEY_HEX: &str =
+        "D75A980182B10AB7D54BFED3C964073A0EE172F3DAA62325AF021A68F707511A";
+    const ED25519_KEY_BASE64: &str = "11qYAYKxCrfVS/7TyWQHOg7hcvPapiMlrwIaaPcHURo=";
+
+    fn ed25519_key() -> PublicKey {
+        PublicKey::from_raw_ed25519(&hex::decode_upper(ED25519_KEY_HEX).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn hex_and_protobuf_round_trip() {
+        let pk = ed25519_key();
+        assert_eq!(pk.to_hex(), ED25519_KEY_HEX);
+
+        let raw: RawPublicKey = pk.into();
+        assert_eq!(PublicKey::try_from(raw).unwrap(), pk);
+
+        assert!(PublicKey::try_from(RawPublicKey { sum: None }).is_err());
+        assert!(PublicKey::try_from(RawPublicKey {
+            sum: Some(Sum::Ed25519(vec![1, 2, 3]))
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn bech32_encoding() {
+        let pk = ed25519_key();
+        let encoded = pk.to_bech32("cosmosvalconspub");
+
+        let (hrp, data) = bech32::decode(&encoded).unwrap();
+        assert_eq!(hrp, "cosmosvalconspub");
+        assert_eq!(&data[..5], &ED25519_AMINO_PREFIX);
+        assert_eq!(&data[5..], pk.to_bytes().as_slice());
+    }
+
+    #[test]
+    fn json_encoding() {
+        let json = format!(
+            r#"{{"type":"tendermint/PubKeyEd25519","value":"{}"}}"#,
+            ED25519_KEY_BASE64
+        );
+
+        let pk: PublicKey = serde_json::from_str(&json).unwrap();
+        assert_eq!(pk, ed25519_key());
+        assert_eq!(serde_json::to_string(&pk).unwrap(), json);
+
+        assert!(serde_json::from_str::<PublicKey>(
+            r#"{"type":"tendermint/PubKeySr25519","value":""}"#
+        )
+        .is_err());
+    }
+}
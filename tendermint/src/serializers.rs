@@ -0,0 +1,238 @@
+//! Serde serializers
+//!
+//! Serializers and deserializers for a transparent developer experience.
+//!
+//! Tendermint's JSON encoding differs from serde's defaults in a few ways:
+//!
+//! - integers (including 64-bit ones) are encoded as strings: [`from_str`]
+//!   and [`optional_from_str`]
+//! - byte arrays are encoded as base64 or upper-case hex strings: [`bytes`]
+//! - absent timestamps are encoded as the Go zero time: [`optional_time`]
+//! - empty vectors are often encoded as `null`: [`nullable`]
+//!
+//! Use them in `#[serde(with = "...")]` attributes of domain types:
+//!
+//! ```ignore
+//! #[derive(Serialize, Deserialize)]
+//! struct Example {
+//!     #[serde(with = "serializers::from_str")]
+//!     height: u64,
+//!     #[serde(with = "serializers::bytes::base64string")]
+//!     signature: Vec<u8>,
+//! }
+//! ```
+
+pub mod bytes;
+pub mod from_str;
+pub mod nullable;
+pub mod optional_from_str;
+pub mod optional_time;
+
+#[cfg(test)]
+mod tests {
+    use crate::abci::transaction::{self, Transaction};
+    use crate::block::{self, Block};
+    use crate::{account, chain, serializers, vote, Hash, PublicKey, Time};
+    use serde::{Deserialize, Serialize};
+    use std::str::FromStr;
+
+    /// JSON-RPC response of a node
+    #[derive(Deserialize)]
+    struct Response<T> {
+        result: T,
+    }
+
+    fn result<'de, T: Deserialize<'de>>(json: &'de str) -> T {
+        serde_json::from_str::<Response<T>>(json).unwrap().result
+    }
+
+    /// Result of the `/block` endpoint
+    #[derive(Deserialize)]
+    struct BlockResult {
+        block_id: block::Id,
+        block: Block,
+    }
+
+    /// Subset of the result of the `/status` endpoint
+    #[derive(Deserialize)]
+    struct StatusResult {
+        node_info: NodeInfo,
+        sync_info: SyncInfo,
+        validator_info: ValidatorInfo,
+    }
+
+    #[derive(Deserialize)]
+    struct NodeInfo {
+        network: chain::Id,
+        protocol_version: block::header::Version,
+    }
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    struct SyncInfo {
+        latest_block_hash: Hash,
+        #[serde(with = "serializers::bytes::hexstring")]
+        latest_app_hash: Vec<u8>,
+        latest_block_height: block::Height,
+        latest_block_time: Time,
+        #[serde(with = "serializers::bytes::hexstring")]
+        earliest_app_hash: Vec<u8>,
+        earliest_block_height: block::Height,
+        catching_up: bool,
+    }
+
+    #[derive(Deserialize)]
+    struct ValidatorInfo {
+        address: account::Id,
+        pub_key: PublicKey,
+        voting_power: vote::Power,
+    }
+
+    /// Subset of the result of the `/tx_search` endpoint
+    #[derive(Deserialize)]
+    struct TxSearchResult {
+        txs: Vec<TxResult>,
+        #[serde(with = "serializers::from_str")]
+        total_count: u32,
+    }
+
+    #[derive(Deserialize)]
+    struct TxResult {
+        hash: transaction::Hash,
+        height: block::Height,
+        tx: Transaction,
+        tx_result: DeliverTx,
+    }
+
+    #[derive(Deserialize)]
+    struct DeliverTx {
+        #[serde(with = "serializers::bytes::base64string")]
+        data: Vec<u8>,
+        #[serde(with = "serializers::from_str")]
+        gas_used: i64,
+    }
+
+    const BLOCK_AT_HEIGHT_1: &str =
+        include_str!("../tests/support/serialization/rpc/block_at_height_1.json");
+    const BLOCK_AT_HEIGHT_10: &str =
+        include_str!("../tests/support/serialization/rpc/block_at_height_10.json");
+    const STATUS: &str = include_str!("../tests/support/serialization/rpc/status.json");
+    const TX_SEARCH: &str =
+        include_str!("../tests/support/serialization/rpc/tx_search_no_prove.json");
+
+    #[test]
+    fn parses_block_response() {
+        let BlockResult { block_id, block } = result(BLOCK_AT_HEIGHT_10);
+
+        assert_eq!(block.header.hash(), block_id.hash);
+        assert_eq!(block.header.height, block::Height::from(10_u32));
+        assert_eq!(block.header.version.block, 11);
+        assert_eq!(block.data.iter().count(), 0);
+        let last_commit = block.last_commit.as_ref().unwrap();
+        assert_eq!(last_commit.height, block::Height::from(9_u32));
+        assert_eq!(last_commit.signatures.len(), 1);
+
+        let json = serde_json::to_value(&block).unwrap();
+        assert_eq!(json["header"]["height"], "10");
+        assert_eq!(serde_json::from_value::<Block>(json).unwrap(), block);
+    }
+
+    #[test]
+    fn parses_first_block_response() {
+        let BlockResult { block_id, block } = result(BLOCK_AT_HEIGHT_1);
+
+        assert_eq!(block.header.hash(), block_id.hash);
+        assert_eq!(block.header.last_block_id, None);
+        assert!(block.header.app_hash.is_empty());
+        assert_eq!(block.last_commit, None);
+    }
+
+    #[test]
+    fn parses_status_response() {
+        let status: StatusResult = result(STATUS);
+
+        assert_eq!(status.node_info.network.as_str(), "dockerchain");
+        assert_eq!(status.node_info.protocol_version.app, 1);
+
+        let sync_info = &status.sync_info;
+        assert_eq!(
+            sync_info.latest_block_hash,
+            Hash::from_str("0841B9258E07365306288BAE1D3B0F9B0E8F74CAF5ED188CCA7C3F9BD1036861")
+                .unwrap()
+        );
+        assert_eq!(sync_info.latest_app_hash, vec![0; 8]);
+        assert_eq!(sync_info.latest_block_height, block::Height::from(16_u32));
+        assert!(sync_info.earliest_app_hash.is_empty());
+        assert_eq!(sync_info.earliest_block_height, block::Height::from(1_u32));
+
+        let json = serde_json::to_string(sync_info).unwrap();
+        assert!(json.contains(r#""latest_block_height":"16""#));
+        assert!(json.contains(r#""latest_app_hash":"0000000000000000""#));
+        assert_eq!(&serde_json::from_str::<SyncInfo>(&json).unwrap(), sync_info);
+
+        let validator = &status.validator_info;
+        assert_eq!(
+            validator.address,
+            account::Id::from_str("ABAF73F9A1D15E78CA39C1E066E07D3F3B3BAFAF").unwrap()
+        );
+        assert_eq!(account::Id::from(validator.pub_key), validator.address);
+        assert_eq!(validator.voting_power.value(), 10);
+    }
+
+    #[test]
+    fn parses_tx_search_response() {
+        let search: TxSearchResult = result(TX_SEARCH);
+
+        assert_eq!(search.total_count as usize, search.txs.len());
+        for tx in &search.txs {
+            assert_eq!(tx.tx.hash(), tx.hash);
+            assert!(tx.tx_result.data.is_empty());
+            assert_eq!(tx.tx_result.gas_used, 0);
+        }
+        assert_eq!(search.txs[0].height, block::Height::from(16_u32));
+        assert_eq!(search.txs[0].tx.as_bytes(), b"async-key=value");
+
+        let json = serde_json::to_string(&search.txs[0].tx).unwrap();
+        assert_eq!(json, r#""YXN5bmMta2V5PXZhbHVl""#);
+    }
+
+    /// No response of the kvstore fixtures has absent timestamps or heights,
+    /// which Tendermint encodes as the Go zero time and the empty string
+    #[test]
+    fn parses_absent_values() {
+        #[derive(Debug, Deserialize, Serialize, PartialEq)]
+        struct Absent {
+            #[serde(with = "serializers::optional_time")]
+            timestamp: Option<Time>,
+            #[serde(with = "serializers::optional_from_str")]
+            earliest_block_height: Option<u64>,
+        }
+
+        let absent: Absent = serde_json::from_str(
+            r#"{"timestamp": "0001-01-01T00:00:00Z", "earliest_block_height": ""}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            absent,
+            Absent {
+                timestamp: None,
+                earliest_block_height: None
+            }
+        );
+        assert_eq!(
+            serde_json::to_string(&absent).unwrap(),
+            r#"{"timestamp":"0001-01-01T00:00:00Z","earliest_block_height":null}"#
+        );
+
+        let data: transaction::Data = serde_json::from_str(r#"{"txs": null}"#).unwrap();
+        assert_eq!(data, transaction::Data::default());
+    }
+
+    #[test]
+    fn rejects_malformed_values() {
+        assert!(serde_json::from_str::<block::Height>("10").is_err());
+        assert!(serde_json::from_str::<block::Height>(r#""-1""#).is_err());
+        assert!(serde_json::from_str::<vote::Power>(r#""-1""#).is_err());
+        assert!(serde_json::from_str::<Transaction>(r#""%%""#).is_err());
+        assert!(serde_json::from_str::<transaction::Hash>(r#""ABCD""#).is_err());
+    }
+}
@@ -0,0 +1,96 @@
+//! Serialize and deserialize byte arrays as strings
+
+/// Serialize into and deserialize from a base64 string
+pub mod base64string {
+    use serde::{de::Error as _, Deserialize, Deserializer, Serializer};
+    use std::borrow::Cow;
+    use subtle_encoding::base64;
+
+    /// Deserialize a base64 string (or `null`) into bytes
+    pub fn deserialize<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: From<Vec<u8>>,
+    {
+        let s = <Option<Cow<'_, str>>>::deserialize(deserializer)?.unwrap_or_default();
+        base64::decode(s.as_bytes())
+            .map(Into::into)
+            .map_err(|e| D::Error::custom(format!("invalid base64: {}", e)))
+    }
+
+    /// Serialize bytes into a base64 string
+    pub fn serialize<S, T>(value: T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        T: AsRef<[u8]>,
+    {
+        let encoded = String::from_utf8(base64::encode(value.as_ref())).unwrap();
+        serializer.serialize_str(&encoded)
+    }
+}
+
+/// Serialize into and deserialize from a vector of base64 strings,
+/// where `null` is an empty vector
+pub mod vec_base64string {
+    use serde::{de::Error as _, Deserialize, Deserializer, Serializer};
+    use subtle_encoding::base64;
+
+    /// Deserialize an array of base64 strings (or `null`) into a vector of bytes
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<Vec<u8>>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Option::<Vec<String>>::deserialize(deserializer)?
+            .unwrap_or_default()
+            .iter()
+            .map(|s| {
+                base64::decode(s.as_bytes())
+                    .map_err(|e| D::Error::custom(format!("invalid base64: {}", e)))
+            })
+            .collect()
+    }
+
+    /// Serialize a vector of bytes into an array of base64 strings
+    pub fn serialize<S>(value: &[Vec<u8>], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_seq(
+            value
+                .iter()
+                .map(|bytes| String::from_utf8(base64::encode(bytes)).unwrap()),
+        )
+    }
+}
+
+/// Serialize into and deserialize from a hexadecimal string.
+///
+/// Upper and lower case are accepted; upper case is produced, like Tendermint.
+pub mod hexstring {
+    use serde::{de::Error as _, Deserialize, Deserializer, Serializer};
+    use std::borrow::Cow;
+    use subtle_encoding::hex;
+
+    /// Deserialize a hexadecimal string (or `null`) into bytes
+    pub fn deserialize<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: From<Vec<u8>>,
+    {
+        let s = <Option<Cow<'_, str>>>::deserialize(deserializer)?.unwrap_or_default();
+        hex::decode_upper(s.as_bytes())
+            .or_else(|_| hex::decode(s.as_bytes()))
+            .map(Into::into)
+            .map_err(|e| D::Error::custom(format!("invalid hex: {}", e)))
+    }
+
+    /// Serialize bytes into an upper-case hexadecimal string
+    pub fn serialize<S, T>(value: T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        T: AsRef<[u8]>,
+    {
+        let encoded = String::from_utf8(hex::encode_upper(value.as_ref())).unwrap();
+        serializer.serialize_str(&encoded)
+    }
+}
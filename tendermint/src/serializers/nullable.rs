@@ -0,0 +1,24 @@
+//! Deserialize `null` into the default value of `T`, eg. an empty vector.
+//!
+//! Go encodes nil slices as `null`, which serde would otherwise reject for
+//! `Vec<T>` fields. Values are serialized as usual.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Deserialize `null` or a `T` into a `T`
+pub fn deserialize<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de> + Default,
+{
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+/// Serialize a `T` as usual
+pub fn serialize<S, T>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: Serialize,
+{
+    value.serialize(serializer)
+}
@@ -0,0 +1,35 @@
+//! Serialize and deserialize an `Option<T>` from or into an optional string,
+//! for any `T` that implements [`std::str::FromStr`] and [`std::fmt::Display`].
+//!
+//! `null` and the empty string both deserialize to `None`.
+
+use serde::{de::Error as _, Deserialize, Deserializer, Serializer};
+use std::borrow::Cow;
+
+/// Deserialize an optional string into `Option<T>`
+pub fn deserialize<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: std::str::FromStr,
+    <T as std::str::FromStr>::Err: std::fmt::Display,
+{
+    match <Option<Cow<'_, str>>>::deserialize(deserializer)? {
+        Some(s) if !s.is_empty() => s
+            .parse::<T>()
+            .map(Some)
+            .map_err(|e| D::Error::custom(format!("{}", e))),
+        _ => Ok(None),
+    }
+}
+
+/// Serialize from `Option<T>` into an optional string
+pub fn serialize<S, T>(value: &Option<T>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: std::fmt::Display,
+{
+    match value {
+        Some(value) => serializer.serialize_some(&value.to_string()),
+        None => serializer.serialize_none(),
+    }
+}
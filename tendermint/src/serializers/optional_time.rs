@@ -0,0 +1,31 @@
+//! Serialize and deserialize an `Option<Time>`, encoding `None` as the Go zero
+//! time ("0001-01-01T00:00:00Z") like Tendermint does for absent timestamps,
+//! eg. those of absent commit signatures.
+//!
+//! `null` and the empty string are also accepted as `None`.
+
+use crate::Time;
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+use std::borrow::Cow;
+
+/// Deserialize an RFC 3339 string into `Option<Time>`
+pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<Time>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = match <Option<Cow<'_, str>>>::deserialize(deserializer)? {
+        Some(s) if !s.is_empty() => s,
+        _ => return Ok(None),
+    };
+
+    let time = Time::parse_from_rfc3339(&s).map_err(|e| D::Error::custom(format!("{}", e)))?;
+    Ok(if time.is_zero() { None } else { Some(time) })
+}
+
+/// Serialize `Option<Time>` into an RFC 3339 string
+pub fn serialize<S>(value: &Option<Time>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    value.unwrap_or_else(Time::zero).serialize(serializer)
+}
@@ -0,0 +1,124 @@
+//! Cryptographic signatures
+
+use crate::error::{Error, Kind};
+use crate::PublicKey;
+use std::convert::TryFrom;
+use tendermint_proto::Protobuf;
+
+/// Size of Ed25519 and (compact, `r || s`) secp256k1 signatures in bytes
+pub const SIGNATURE_LENGTH: usize = 64;
+
+/// Signatures
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Signature(Vec<u8>);
+
+impl Protobuf<Vec<u8>> for Signature {}
+
+impl TryFrom<Vec<u8>> for Signature {
+    type Error = Error;
+
+    fn try_from(value: Vec<u8>) -> Result<Self, Self::Error> {
+        if value.is_empty() {
+            return Err(Kind::InvalidSignature.context("empty signature").into());
+        }
+        if value.len() != SIGNATURE_LENGTH {
+            return Err(Kind::InvalidSignature
+                .context(format!(
+                    "expected {} bytes, got {}",
+                    SIGNATURE_LENGTH,
+                    value.len()
+                ))
+                .into());
+        }
+        Ok(Signature(value))
+    }
+}
+
+impl From<Signature> for Vec<u8> {
+    fn from(value: Signature) -> Self {
+        value.0
+    }
+}
+
+impl Signature {
+    /// Return the raw bytes of this signature
+    pub fn as_bytes(&self) -> &[u8] {
+        self.0.as_slice()
+    }
+}
+
+impl AsRef<[u8]> for Signature {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+/// Verifies signatures against public keys.
+///
+/// Abstracting over the verification allows consumers such as the light client
+/// to plug in a different cryptographic backend than the default one.
+pub trait Verifier: Send + Sync {
+    /// Check that the given signature of the given message was produced
+    /// by the secret key matching the given public key.
+    fn verify(&self, pubkey: &PublicKey, msg: &[u8], signature: &Signature) -> Result<(), Error>;
+}
+
+/// Verifies Ed25519 signatures with `ed25519-dalek`, and secp256k1 ECDSA
+/// signatures with `k256` when the `secp256k1` feature is enabled.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DefaultVerifier;
+
+impl Verifier for DefaultVerifier {
+    fn verify(&self, pubkey: &PublicKey, msg: &[u8], signature: &Signature) -> Result<(), Error> {
+        match pubkey {
+            PublicKey::Ed25519(pk) => {
+                use ed25519_dalek::Verifier as _;
+
+                let sig = ed25519_dalek::Signature::try_from(signature.as_bytes())
+                    .map_err(|e| Kind::InvalidSignature.context(e))?;
+                pk.verify(msg, &sig)
+                    .map_err(|e| Kind::SignatureVerificationFailed.context(e).into())
+            }
+            #[cfg(feature = "secp256k1")]
+            PublicKey::Secp256k1(pk) => {
+                use k256::ecdsa::signature::Verifier as _;
+
+                let sig = k256::ecdsa::Signature::try_from(signature.as_bytes())
+                    .map_err(|e| Kind::InvalidSignature.context(e))?;
+                pk.verify(msg, &sig)
+                    .map_err(|e| Kind::SignatureVerificationFailed.context(e).into())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use subtle_encoding::hex;
+
+    // RFC 8032 test vector 2
+    const PUBLIC_KEY: &str = "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c";
+    const MESSAGE: &str = "72";
+    const SIGNATURE: &str = "92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da\
+                             085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00";
+
+    #[test]
+    fn signature_length_is_checked() {
+        assert!(Signature::try_from(vec![]).is_err());
+        assert!(Signature::try_from(vec![0; SIGNATURE_LENGTH - 1]).is_err());
+        assert!(Signature::try_from(vec![0; SIGNATURE_LENGTH]).is_ok());
+    }
+
+    #[test]
+    fn verifies_ed25519_signatures() {
+        let pubkey = PublicKey::from_raw_ed25519(&hex::decode(PUBLIC_KEY).unwrap()).unwrap();
+        let msg = hex::decode(MESSAGE).unwrap();
+        let signature = Signature::try_from(hex::decode(SIGNATURE).unwrap()).unwrap();
+
+        assert!(DefaultVerifier.verify(&pubkey, &msg, &signature).is_ok());
+        assert!(DefaultVerifier
+            .verify(&pubkey, b"another message", &signature)
+            .is_err());
+    }
+}
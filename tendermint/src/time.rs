@@ -0,0 +1,326 @@
+//! Timestamps used by Tendermint blockchains
+
+use crate::error::{Error, Kind};
+use chrono::{DateTime, Datelike, LocalResult, TimeZone, Utc};
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+use std::convert::{TryFrom, TryInto};
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime};
+use tendermint_proto::google::protobuf::Timestamp;
+use tendermint_proto::Protobuf;
+
+extern crate prusti_contracts;
+use prusti_contracts::*;
+
+/// Seconds between the Go zero time ("0001-01-01T00:00:00Z") and the `UNIX_EPOCH`
+const ZERO_TIME_SECONDS: i64 = -62_135_596_800;
+
+/// Seconds between the `UNIX_EPOCH` and the last second of year 9999
+/// ("9999-12-31T23:59:59Z"), the latest time a protobuf `Timestamp` can hold
+const MAX_TIME_SECONDS: i64 = 253_402_300_799;
+
+/// Tendermint timestamps
+///
+/// Timestamps are encoded in JSON as RFC 3339 strings with nanosecond precision,
+/// as produced by Go's `time.RFC3339Nano` layout. Like protobuf timestamps,
+/// they range from year 1 to year 9999.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub struct Time(DateTime<Utc>);
+
+impl Protobuf<Timestamp> for Time {}
+
+impl TryFrom<Timestamp> for Time {
+    type Error = Error;
+
+    fn try_from(value: Timestamp) -> Result<Self, Error> {
+        let nanos: u32 = value.nanos.try_into().map_err(|_| Kind::InvalidTimestamp)?;
+        if nanos > 999_999_999 {
+            return Err(Kind::InvalidTimestamp
+                .context(format!("nanoseconds out of range: {}", nanos))
+                .into());
+        }
+
+        match Utc.timestamp_opt(value.seconds, nanos) {
+            LocalResult::Single(t) if Time::is_in_range(&t) => Ok(Time(t)),
+            _ => Err(Kind::InvalidTimestamp
+                .context(format!("seconds out of range: {}", value.seconds))
+                .into()),
+        }
+    }
+}
+
+impl From<Time> for Timestamp {
+    fn from(value: Time) -> Self {
+        Timestamp {
+            seconds: value.0.timestamp(),
+            nanos: value.0.timestamp_subsec_nanos() as i32,
+        }
+    }
+}
+
+impl Time {
+    /// Get a `Time` value representing the current wall clock time
+    pub fn now() -> Self {
+        Time(Utc::now())
+    }
+
+    /// Get the `UNIX_EPOCH` time ("1970-01-01 00:00:00 UTC") as a `Time`
+    pub fn unix_epoch() -> Self {
+        Time(Utc.timestamp_opt(0, 0).unwrap())
+    }
+
+    /// Get the Go zero time ("0001-01-01 00:00:00 UTC") as a `Time`,
+    /// which Tendermint uses as the timestamp of absent votes
+    pub fn zero() -> Self {
+        Time(Utc.timestamp_opt(ZERO_TIME_SECONDS, 0).unwrap())
+    }
+
+    /// Whether this is the Go zero time
+    pub fn is_zero(&self) -> bool {
+        *self == Self::zero()
+    }
+
+    /// Calculate the amount of time which has passed since another `Time`
+    /// as a `std::time::Duration`
+    pub fn duration_since(&self, other: Time) -> Result<Duration, Error> {
+        self.0
+            .signed_duration_since(other.0)
+            .to_std()
+            .map_err(|_| Kind::OutOfRange.into())
+    }
+
+    /// Parse a timestamp from an RFC 3339 date, converting it to UTC
+    pub fn parse_from_rfc3339(s: &str) -> Result<Self, Error> {
+        // Like Go's `time.RFC3339`, only accept `T` between the date and the time,
+        // while chrono also accepts a space
+        if s.as_bytes().get(10) != Some(&b'T') {
+            return Err(Kind::Parse
+                .context(format!("invalid RFC 3339 timestamp: {}", s))
+                .into());
+        }
+
+        let date = DateTime::parse_from_rfc3339(s)
+            .map_err(|e| Kind::Parse.context(format!("invalid RFC 3339 timestamp: {}", e)))?
+            .with_timezone(&Utc);
+        if !Self::is_in_range(&date) {
+            return Err(Kind::OutOfRange
+                .context(format!("timestamp out of range: {}", s))
+                .into());
+        }
+
+        Ok(Time(date))
+    }
+
+    /// Format this timestamp as an RFC 3339 date in UTC, with as many
+    /// fractional digits as needed to represent its nanoseconds (like
+    /// Go's `time.RFC3339Nano` layout)
+    pub fn to_rfc3339(&self) -> String {
+        // chrono's `%Y` would prefix years after 9999 with a sign, which only
+        // dates converted from `DateTime` or `SystemTime` can reach
+        let mut s = format!("{:04}-{}", self.0.year(), self.0.format("%m-%dT%H:%M:%S"));
+
+        let nanos = self.0.timestamp_subsec_nanos();
+        if nanos != 0 {
+            let fraction = format!("{:09}", nanos);
+            s.push('.');
+            s.push_str(fraction.trim_end_matches('0'));
+        }
+
+        s.push('Z');
+        s
+    }
+
+    /// Add the given duration to this timestamp, returning `None` if the
+    /// result is after year 9999
+    pub fn checked_add(&self, duration: Duration) -> Option<Self> {
+        let duration = chrono::Duration::from_std(duration).ok()?;
+        self.0
+            .checked_add_signed(duration)
+            .filter(Self::is_in_range)
+            .map(Time)
+    }
+
+    /// Subtract the given duration from this timestamp, returning `None` if
+    /// the result is before year 1
+    pub fn checked_sub(&self, duration: Duration) -> Option<Self> {
+        let duration = chrono::Duration::from_std(duration).ok()?;
+        self.0
+            .checked_sub_signed(duration)
+            .filter(Self::is_in_range)
+            .map(Time)
+    }
+
+    /// Whether the given date lies between years 1 and 9999, so that its year
+    /// has the four digits RFC 3339 expects
+    fn is_in_range(date: &DateTime<Utc>) -> bool {
+        (ZERO_TIME_SECONDS..=MAX_TIME_SECONDS).contains(&date.timestamp())
+    }
+}
+
+impl fmt::Display for Time {
+    #[trusted]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_rfc3339())
+    }
+}
+
+impl FromStr for Time {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        Self::parse_from_rfc3339(s)
+    }
+}
+
+impl<'de> Deserialize<'de> for Time {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Self::from_str(&s).map_err(|e| D::Error::custom(format!("{}", e)))
+    }
+}
+
+impl Serialize for Time {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_rfc3339().serialize(serializer)
+    }
+}
+
+impl From<DateTime<Utc>> for Time {
+    fn from(t: DateTime<Utc>) -> Time {
+        Time(t)
+    }
+}
+
+impl From<Time> for DateTime<Utc> {
+    fn from(t: Time) -> DateTime<Utc> {
+        t.0
+    }
+}
+
+impl From<SystemTime> for Time {
+    fn from(t: SystemTime) -> Time {
+        Time(t.into())
+    }
+}
+
+impl From<Time> for SystemTime {
+    fn from(t: Time) -> SystemTime {
+        t.0.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rfc3339_round_trip_preserves_nanoseconds() {
+        for s in &[
+            "2020-09-14T16:33:54.21191421Z",
+            "2019-08-01T11:39:11.000000001Z",
+            "2019-08-01T11:39:11.5Z",
+            "2019-08-01T11:39:11Z",
+            "0001-01-01T00:00:00Z",
+        ] {
+            let time = Time::parse_from_rfc3339(s).unwrap();
+            assert_eq!(&time.to_rfc3339(), s);
+        }
+    }
+
+    #[test]
+    fn rfc3339_offsets_are_converted_to_utc() {
+        let time = Time::parse_from_rfc3339("2019-08-01T13:39:11.25+02:00").unwrap();
+        assert_eq!(time.to_string(), "2019-08-01T11:39:11.25Z");
+
+        assert!(Time::parse_from_rfc3339("2019-08-01 11:39:11Z").is_err());
+    }
+
+    #[test]
+    fn zero_time_protobuf_round_trip() {
+        let zero = Time::try_from(Timestamp {
+            seconds: -62_135_596_800,
+            nanos: 0,
+        })
+        .unwrap();
+
+        assert!(zero.is_zero());
+        assert_eq!(zero.to_rfc3339(), "0001-01-01T00:00:00Z");
+
+        let timestamp: Timestamp = zero.into();
+        assert_eq!(timestamp.seconds, -62_135_596_800);
+        assert_eq!(timestamp.nanos, 0);
+    }
+
+    #[test]
+    fn invalid_protobuf_timestamps_are_rejected() {
+        assert!(Time::try_from(Timestamp {
+            seconds: 0,
+            nanos: -1,
+        })
+        .is_err());
+        assert!(Time::try_from(Timestamp {
+            seconds: 0,
+            nanos: 1_000_000_000,
+        })
+        .is_err());
+        assert!(Time::try_from(Timestamp {
+            seconds: i64::MAX,
+            nanos: 0,
+        })
+        .is_err());
+        assert!(Time::try_from(Timestamp {
+            seconds: MAX_TIME_SECONDS + 1,
+            nanos: 0,
+        })
+        .is_err());
+        assert!(Time::try_from(Timestamp {
+            seconds: ZERO_TIME_SECONDS - 1,
+            nanos: 0,
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn checked_arithmetic() {
+        let epoch = Time::unix_epoch();
+        let later = epoch.checked_add(Duration::new(1, 5)).unwrap();
+
+        assert_eq!(later.to_rfc3339(), "1970-01-01T00:00:01.000000005Z");
+        assert_eq!(later.checked_sub(Duration::new(1, 5)), Some(epoch));
+        assert_eq!(later.duration_since(epoch).unwrap(), Duration::new(1, 5));
+        assert!(epoch.checked_add(Duration::from_secs(u64::MAX)).is_none());
+    }
+
+    #[test]
+    fn arithmetic_stays_within_years_1_to_9999() {
+        let last = Time::try_from(Timestamp {
+            seconds: MAX_TIME_SECONDS,
+            nanos: 999_999_999,
+        })
+        .unwrap();
+        assert_eq!(last.to_rfc3339(), "9999-12-31T23:59:59.999999999Z");
+        assert_eq!(last.checked_add(Duration::from_nanos(1)), None);
+
+        let far = Time::from(Utc.with_ymd_and_hms(12345, 6, 7, 8, 9, 10).unwrap());
+        assert_eq!(far.to_rfc3339(), "12345-06-07T08:09:10Z");
+
+        assert_eq!(Time::zero().checked_sub(Duration::from_nanos(1)), None);
+        assert_eq!(
+            Time::zero()
+                .checked_add(Duration::from_secs(1))
+                .unwrap()
+                .checked_sub(Duration::from_secs(1)),
+            Some(Time::zero())
+        );
+    }
+
+    #[test]
+    fn serde_uses_rfc3339_strings() {
+        let time: Time = serde_json::from_str("\"2020-09-14T16:33:54.21191421Z\"").unwrap();
+        assert_eq!(
+            serde_json::to_string(&time).unwrap(),
+            "\"2020-09-14T16:33:54.21191421Z\""
+        );
+    }
+}
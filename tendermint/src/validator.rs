@@ -0,0 +1,294 @@
+//! Tendermint validators
+
+use crate::merkle::simple_hash_from_byte_vectors;
+use crate::signature::{Signature, Verifier};
+use crate::{account, vote, Error, Hash, Kind, PublicKey};
+use prost::Message;
+use std::convert::{TryFrom, TryInto};
+use tendermint_proto::types::{
+    SimpleValidator as RawSimpleValidator, Validator as RawValidator,
+    ValidatorSet as RawValidatorSet,
+};
+use tendermint_proto::Protobuf;
+
+/// Validator set contains a vector of validators
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Set {
+    validators: Vec<Info>,
+    proposer: Option<Info>,
+    total_voting_power: vote::Power,
+}
+
+impl Protobuf<RawValidatorSet> for Set {}
+
+impl TryFrom<RawValidatorSet> for Set {
+    type Error = Error;
+
+    fn try_from(value: RawValidatorSet) -> Result<Self, Self::Error> {
+        let validators = value
+            .validators
+            .into_iter()
+            .map(TryInto::try_into)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let proposer = value.proposer.map(TryInto::try_into).transpose()?;
+        let validator_set = Self::new(validators, proposer)?;
+
+        // Ensure that the raw voting power matches the computed one
+        let raw_voting_power: vote::Power = value.total_voting_power.try_into()?;
+        if raw_voting_power != validator_set.total_voting_power() {
+            return Err(Kind::InvalidBlock
+                .context("total voting power in raw validator set does not match the sum of the validators' power")
+                .into());
+        }
+
+        Ok(validator_set)
+    }
+}
+
+impl From<Set> for RawValidatorSet {
+    fn from(value: Set) -> Self {
+        RawValidatorSet {
+            validators: value.validators.into_iter().map(Into::into).collect(),
+            proposer: value.proposer.map(Into::into),
+            total_voting_power: value.total_voting_power.into(),
+        }
+    }
+}
+
+/// Maximum total voting power of a validator set, chosen so that proposer
+/// priorities cannot overflow
+///
+/// <https://github.com/tendermint/tendermint/blob/v0.34.0/types/validator_set.go#L24-L31>
+pub const MAX_TOTAL_VOTING_POWER: u64 = i64::MAX as u64 / 8;
+
+impl Set {
+    /// Constructor
+    ///
+    /// Fails if the total voting power of the validators exceeds
+    /// [`MAX_TOTAL_VOTING_POWER`].
+    pub fn new(mut validators: Vec<Info>, proposer: Option<Info>) -> Result<Set, Error> {
+        Self::sort_validators(&mut validators);
+
+        // Compute the total voting power
+        let mut total = 0u64;
+        for validator in &validators {
+            total = total
+                .checked_add(validator.power.value())
+                .filter(|total| *total <= MAX_TOTAL_VOTING_POWER)
+                .ok_or_else(|| {
+                    Kind::IntegerOverflow
+                        .context("total voting power of the validator set is too large")
+                })?;
+        }
+
+        Ok(Set {
+            validators,
+            proposer,
+            total_voting_power: total.try_into()?,
+        })
+    }
+
+    /// Convenience constructor for cases where there is no proposer
+    pub fn without_proposer(validators: Vec<Info>) -> Result<Set, Error> {
+        Self::new(validators, None)
+    }
+
+    /// Get Info of the underlying validators.
+    pub fn validators(&self) -> &Vec<Info> {
+        &self.validators
+    }
+
+    /// Get proposer
+    pub fn proposer(&self) -> &Option<Info> {
+        &self.proposer
+    }
+
+    /// Get total voting power
+    pub fn total_voting_power(&self) -> vote::Power {
+        self.total_voting_power
+    }
+
+    /// Compute the hash of this validator set
+    ///
+    /// The hash is the Merkle root of the protobuf encodings of the validators,
+    /// each reduced to its public key and voting power.
+    ///
+    /// <https://github.com/tendermint/tendermint/blob/v0.34.0/types/validator_set.go#L341-L350>
+    pub fn hash(&self) -> Hash {
+        let validator_bytes = self.validators.iter().map(Info::hash_bytes).collect();
+        Hash::Sha256(simple_hash_from_byte_vectors(validator_bytes))
+    }
+
+    /// Sort the validators according to the current Tendermint requirements
+    /// (v. 0.34 -> first by validator power, descending, then by address, ascending)
+    fn sort_validators(vals: &mut [Info]) {
+        vals.sort_by_key(|v| (std::cmp::Reverse(v.power), v.address));
+    }
+
+    /// Returns the validator with the given Id if its in the Set.
+    pub fn validator(&self, val_id: account::Id) -> Option<Info> {
+        self.validators
+            .iter()
+            .find(|val| val.address == val_id)
+            .cloned()
+    }
+}
+
+/// Validator information
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Info {
+    /// Validator account address
+    pub address: account::Id,
+
+    /// Validator public key
+    pub pub_key: PublicKey,
+
+    /// Validator voting power
+    pub power: vote::Power,
+
+    /// Validator proposer priority
+    pub proposer_priority: ProposerPriority,
+}
+
+impl Info {
+    /// Return the voting power of the validator.
+    pub fn power(&self) -> u64 {
+        self.power.value()
+    }
+
+    /// Verify the given signature of the given message against
+    /// the public key of this validator.
+    pub fn verify_signature(
+        &self,
+        verifier: &dyn Verifier,
+        msg: &[u8],
+        signature: &Signature,
+    ) -> Result<(), Error> {
+        verifier.verify(&self.pub_key, msg, signature)
+    }
+
+    /// Protobuf encoding of this validator, as a leaf of the validator set hash
+    fn hash_bytes(&self) -> Vec<u8> {
+        let simple_validator = RawSimpleValidator {
+            pub_key: Some(self.pub_key.into()),
+            voting_power: self.power.into(),
+        };
+
+        let mut buf = Vec::with_capacity(simple_validator.encoded_len());
+        simple_validator
+            .encode(&mut buf)
+            .expect("buffer has enough capacity");
+        buf
+    }
+}
+
+impl TryFrom<RawValidator> for Info {
+    type Error = Error;
+
+    fn try_from(value: RawValidator) -> Result<Self, Self::Error> {
+        Ok(Info {
+            address: value.address.try_into()?,
+            pub_key: value
+                .pub_key
+                .ok_or(Kind::MissingPublicKey)?
+                .try_into()?,
+            power: value.voting_power.try_into()?,
+            proposer_priority: value.proposer_priority.into(),
+        })
+    }
+}
+
+impl From<Info> for RawValidator {
+    fn from(value: Info) -> Self {
+        RawValidator {
+            address: value.address.into(),
+            pub_key: Some(value.pub_key.into()),
+            voting_power: value.power.into(),
+            proposer_priority: value.proposer_priority.into(),
+        }
+    }
+}
+
+/// Proposer priority
+#[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub struct ProposerPriority(i64);
+
+impl From<i64> for ProposerPriority {
+    fn from(value: i64) -> Self {
+        ProposerPriority(value)
+    }
+}
+
+impl From<ProposerPriority> for i64 {
+    fn from(value: ProposerPriority) -> i64 {
+        value.value()
+    }
+}
+
+impl ProposerPriority {
+    /// Get the current proposer priority
+    pub fn value(self) -> i64 {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::public_key::Ed25519;
+    use ed25519_dalek::SecretKey;
+
+    fn validator(seed: u8, power: u64) -> Info {
+        let public_key = Ed25519::from(&SecretKey::from_bytes(&[seed; 32]).unwrap());
+        Info {
+            address: account::Id::from(public_key),
+            pub_key: PublicKey::Ed25519(public_key),
+            power: vote::Power::try_from(power).unwrap(),
+            proposer_priority: ProposerPriority::from(0),
+        }
+    }
+
+    #[test]
+    fn total_voting_power_is_capped() {
+        let at_max = Set::without_proposer(vec![
+            validator(1, MAX_TOTAL_VOTING_POWER - 1),
+            validator(2, 1),
+        ])
+        .unwrap();
+        assert_eq!(at_max.total_voting_power().value(), MAX_TOTAL_VOTING_POWER);
+
+        let above_max =
+            Set::without_proposer(vec![validator(1, MAX_TOTAL_VOTING_POWER), validator(2, 1)]);
+        assert_eq!(
+            Kind::of(&above_max.unwrap_err()),
+            Some(&Kind::IntegerOverflow)
+        );
+
+        let overflowing = Set::without_proposer(vec![
+            validator(1, i64::MAX as u64),
+            validator(2, i64::MAX as u64),
+            validator(3, i64::MAX as u64),
+        ]);
+        assert_eq!(
+            Kind::of(&overflowing.unwrap_err()),
+            Some(&Kind::IntegerOverflow)
+        );
+    }
+
+    #[test]
+    fn raw_set_with_too_much_power_is_rejected() {
+        let raw = RawValidatorSet {
+            validators: vec![
+                validator(1, i64::MAX as u64).into(),
+                validator(2, i64::MAX as u64).into(),
+            ],
+            proposer: None,
+            total_voting_power: 0,
+        };
+        assert_eq!(
+            Kind::of(&Set::try_from(raw).unwrap_err()),
+            Some(&Kind::IntegerOverflow)
+        );
+    }
+}
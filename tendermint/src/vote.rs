@@ -0,0 +1,192 @@
+//! Votes from validators
+
+mod power;
+mod set;
+mod validator_index;
+
+pub use self::power::Power;
+pub use self::set::{AddVoteOutcome, VoteSet};
+pub use self::validator_index::ValidatorIndex;
+use crate::{account, block, chain, Signature, Time};
+use crate::{Error, Kind::*};
+use prost::Message;
+use std::convert::{TryFrom, TryInto};
+use tendermint_proto::types::{CanonicalVote as RawCanonicalVote, Vote as RawVote};
+use tendermint_proto::Protobuf;
+
+/// Votes are signed messages from validators for a particular block which
+/// include information about the validator signing it.
+///
+/// <https://github.com/tendermint/spec/blob/d46cd7f573a2c6a2399fcab2cde981330aa63f37/spec/core/data_structures.md#vote>
+#[derive(Clone, Debug, PartialEq)]
+pub struct Vote {
+    /// Type of vote (prevote or precommit)
+    pub vote_type: Type,
+
+    /// Block height
+    pub height: block::Height,
+
+    /// Round
+    pub round: block::Round,
+
+    /// Block ID
+    pub block_id: Option<block::Id>,
+
+    /// Timestamp
+    pub timestamp: Option<Time>,
+
+    /// Validator address
+    pub validator_address: account::Id,
+
+    /// Validator index
+    pub validator_index: ValidatorIndex,
+
+    /// Signature
+    pub signature: Signature,
+}
+
+impl Protobuf<RawVote> for Vote {}
+
+impl TryFrom<RawVote> for Vote {
+    type Error = Error;
+
+    fn try_from(value: RawVote) -> Result<Self, Self::Error> {
+        if value.timestamp.is_none() {
+            return Err(NoTimestamp.into());
+        }
+        Ok(Vote {
+            vote_type: value.r#type.try_into()?,
+            height: value.height.try_into()?,
+            round: value.round.try_into()?,
+            // block_id can be nil in the Go implementation
+            block_id: value
+                .block_id
+                .map(TryInto::try_into)
+                .transpose()?
+                .filter(|i| i != &block::Id::default()),
+            timestamp: value.timestamp.map(TryInto::try_into).transpose()?,
+            validator_address: value.validator_address.try_into()?,
+            validator_index: value.validator_index.try_into()?,
+            signature: value.signature.try_into()?,
+        })
+    }
+}
+
+impl From<Vote> for RawVote {
+    fn from(value: Vote) -> Self {
+        RawVote {
+            r#type: value.vote_type.into(),
+            height: value.height.into(),
+            round: value.round.into(),
+            block_id: value.block_id.map(Into::into),
+            timestamp: value.timestamp.map(Into::into),
+            validator_address: value.validator_address.into(),
+            validator_index: value.validator_index.into(),
+            signature: value.signature.into(),
+        }
+    }
+}
+
+impl Vote {
+    /// Is this vote a prevote?
+    pub fn is_prevote(&self) -> bool {
+        match self.vote_type {
+            Type::Prevote => true,
+            Type::Precommit => false,
+        }
+    }
+
+    /// Is this vote a precommit?
+    pub fn is_precommit(&self) -> bool {
+        match self.vote_type {
+            Type::Precommit => true,
+            Type::Prevote => false,
+        }
+    }
+
+    /// The bytes signed by the validator casting this vote on the given chain:
+    /// the length-prefixed protobuf encoding of the canonical vote.
+    ///
+    /// <https://github.com/tendermint/tendermint/blob/v0.34.0/types/vote.go#L87-L102>
+    pub fn to_signable_bytes(&self, chain_id: &chain::Id) -> Vec<u8> {
+        let canonical_vote = RawCanonicalVote {
+            r#type: self.vote_type.into(),
+            height: self.height.into(),
+            round: i32::from(self.round).into(),
+            block_id: self.block_id.map(Into::into),
+            timestamp: self.timestamp.map(Into::into),
+            chain_id: chain_id.to_string(),
+        };
+
+        let mut buf = Vec::with_capacity(canonical_vote.encoded_len() + 10);
+        canonical_vote
+            .encode_length_delimited(&mut buf)
+            .expect("buffer has enough capacity");
+        buf
+    }
+}
+
+/// Types of votes
+#[repr(u8)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+pub enum Type {
+    /// Votes for blocks which validators observe are valid for a given round
+    Prevote = 1,
+
+    /// Votes to commit to a particular block for a given round
+    Precommit = 2,
+}
+
+impl Protobuf<i32> for Type {}
+
+impl TryFrom<i32> for Type {
+    type Error = Error;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(Type::Prevote),
+            2 => Ok(Type::Precommit),
+            _ => Err(InvalidMessageType.into()),
+        }
+    }
+}
+
+impl From<Type> for i32 {
+    fn from(value: Type) -> Self {
+        value as i32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vote_sign_bytes() {
+        // Same as the `TestVoteSignBytesTestVectors` vectors of the Go implementation
+        let vote = Vote {
+            vote_type: Type::Precommit,
+            height: block::Height::from(1_u32),
+            round: block::Round::from(1_u8),
+            block_id: None,
+            timestamp: Some(Time::zero()),
+            validator_address: account::Id::new([0; account::LENGTH]),
+            validator_index: ValidatorIndex::try_from(0_u32).unwrap(),
+            signature: Signature::try_from(vec![1; 64]).unwrap(),
+        };
+        let chain_id = "test_chain_id".parse().unwrap();
+
+        let mut expected = vec![
+            0x30, // length prefix
+            0x08, 0x02, // type
+            0x11, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // height
+            0x19, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // round
+            // timestamp (the Go zero time)
+            0x2a, 0x0b, 0x08, 0x80, 0x92, 0xb8, 0xc3, 0x98, 0xfe, 0xff, 0xff, 0xff, 0x01,
+            0x32, 0x0d, // chain ID
+        ];
+        expected.extend_from_slice(b"test_chain_id");
+
+        assert_eq!(vote.to_signable_bytes(&chain_id), expected);
+    }
+}
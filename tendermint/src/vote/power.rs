@@ -0,0 +1,90 @@
+//! Voting power
+
+use crate::error::{Error, Kind};
+use crate::serializers;
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
+use std::convert::{TryFrom, TryInto};
+use std::fmt;
+
+extern crate prusti_contracts;
+use prusti_contracts::*;
+
+/// Voting power
+#[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub struct Power(u64);
+
+impl fmt::Display for Power {
+    #[trusted]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.value())
+    }
+}
+
+/// Voting powers are encoded as strings, like other 64-bit integers of
+/// Tendermint's JSON
+impl<'de> Deserialize<'de> for Power {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value: i64 = serializers::from_str::deserialize(deserializer)?;
+        Power::try_from(value).map_err(|e| D::Error::custom(format!("{}", e)))
+    }
+}
+
+impl Serialize for Power {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializers::from_str::serialize(self, serializer)
+    }
+}
+
+impl TryFrom<i64> for Power {
+    type Error = Error;
+
+    fn try_from(value: i64) -> Result<Self, Self::Error> {
+        Ok(Power(value.try_into().map_err(|_| Kind::NegativePower)?))
+    }
+}
+
+impl From<Power> for i64 {
+    fn from(value: Power) -> Self {
+        value.value() as i64
+    }
+}
+
+impl TryFrom<u64> for Power {
+    type Error = Error;
+
+    fn try_from(value: u64) -> Result<Self, Self::Error> {
+        let _val: i64 = value.try_into().map_err(|_| Kind::IntegerOverflow)?;
+        Ok(Power(value))
+    }
+}
+
+impl From<Power> for u64 {
+    fn from(value: Power) -> Self {
+        value.value()
+    }
+}
+
+impl From<u32> for Power {
+    fn from(value: u32) -> Self {
+        Power(value as u64)
+    }
+}
+
+impl Power {
+    /// Get the current voting power
+    pub fn value(self) -> u64 {
+        self.0
+    }
+
+    /// Is the current voting power zero?
+    pub fn is_zero(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Add two voting powers, failing on overflow
+    pub fn checked_add(self, rhs: Power) -> Option<Power> {
+        self.0
+            .checked_add(rhs.0)
+            .and_then(|sum| Power::try_from(sum).ok())
+    }
+}
@@ -0,0 +1,411 @@
+//! Sets of votes cast by the validators of a validator set
+//! at a given height and round
+
+use crate::block::{self, Commit, CommitSig};
+use crate::evidence::DuplicateVoteEvidence;
+use crate::signature::Verifier;
+use crate::{chain, validator, vote, Error, Kind, Vote};
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+
+/// Outcome of adding a vote to a `VoteSet`
+#[derive(Clone, Debug, PartialEq)]
+pub enum AddVoteOutcome {
+    /// The vote was added to the set
+    Added,
+
+    /// The set already contains this vote from this validator
+    Duplicate,
+
+    /// The set already contains a vote from this validator for another block:
+    /// the new vote is not added, and the two votes are evidence of misbehavior
+    Conflicting(Box<DuplicateVoteEvidence>),
+}
+
+/// Votes of a given type cast by the validators of a validator set at a given
+/// height and round, which can be turned into a `Commit` once +2/3 of the
+/// voting power precommitted to the same block.
+///
+/// Votes are indexed by the position of their validator in the validator set,
+/// and their signatures are checked before they are added.
+///
+/// <https://github.com/tendermint/tendermint/blob/v0.34.0/types/vote_set.go>
+#[derive(Clone, Debug)]
+pub struct VoteSet {
+    chain_id: chain::Id,
+    height: block::Height,
+    round: block::Round,
+    vote_type: vote::Type,
+    validator_set: validator::Set,
+    votes: Vec<Option<Vote>>,
+    sum: u64,
+    power_by_block: BTreeMap<Option<block::Id>, u64>,
+    two_thirds_majority: Option<Option<block::Id>>,
+}
+
+impl VoteSet {
+    /// Create an empty vote set for the given chain, height, round and vote
+    /// type, with the given validators
+    pub fn new(
+        chain_id: chain::Id,
+        height: block::Height,
+        round: block::Round,
+        vote_type: vote::Type,
+        validator_set: validator::Set,
+    ) -> Self {
+        let votes = vec![None; validator_set.validators().len()];
+
+        Self {
+            chain_id,
+            height,
+            round,
+            vote_type,
+            validator_set,
+            votes,
+            sum: 0,
+            power_by_block: BTreeMap::new(),
+            two_thirds_majority: None,
+        }
+    }
+
+    /// Check the given vote and add it to the set.
+    ///
+    /// Fails if the vote is for another height, round or type, if it was not
+    /// cast by the validator at its index in the validator set, or if its
+    /// signature does not verify.
+    pub fn add_vote(
+        &mut self,
+        vote: Vote,
+        signature_verifier: &dyn Verifier,
+    ) -> Result<AddVoteOutcome, Error> {
+        if vote.height != self.height || vote.round != self.round {
+            return Err(Kind::InvalidVote
+                .context(format!(
+                    "expected height/round {}/{}, got {}/{}",
+                    self.height, self.round, vote.height, vote.round
+                ))
+                .into());
+        }
+        if vote.vote_type != self.vote_type {
+            return Err(Kind::InvalidVote
+                .context(format!(
+                    "expected {:?} vote, got {:?}",
+                    self.vote_type, vote.vote_type
+                ))
+                .into());
+        }
+        if vote.timestamp.is_none() {
+            return Err(Kind::NoTimestamp.into());
+        }
+
+        let index = usize::from(vote.validator_index);
+        let validator = self.validator_set.validators().get(index).ok_or_else(|| {
+            Kind::InvalidVote.context(format!(
+                "validator index {} out of range",
+                vote.validator_index
+            ))
+        })?;
+        if validator.address != vote.validator_address {
+            return Err(Kind::InvalidValidatorAddress
+                .context(format!(
+                    "validator at index {} is {}, not {}",
+                    index, validator.address, vote.validator_address
+                ))
+                .into());
+        }
+
+        let sign_bytes = vote.to_signable_bytes(&self.chain_id);
+        validator.verify_signature(signature_verifier, &sign_bytes, &vote.signature)?;
+
+        if let Some(existing) = &self.votes[index] {
+            if existing.block_id == vote.block_id {
+                return Ok(AddVoteOutcome::Duplicate);
+            }
+
+            let timestamp = existing.timestamp.expect("votes in the set have a timestamp");
+            let evidence = DuplicateVoteEvidence::new(
+                existing.clone(),
+                vote,
+                self.validator_set.total_voting_power(),
+                validator.power,
+                timestamp,
+            )?;
+            return Ok(AddVoteOutcome::Conflicting(Box::new(evidence)));
+        }
+
+        let power = validator.power();
+        let block_power = {
+            let block_power = self.power_by_block.entry(vote.block_id).or_insert(0);
+            *block_power += power;
+            *block_power
+        };
+        self.sum += power;
+
+        if self.two_thirds_majority.is_none() && self.is_two_thirds(block_power) {
+            self.two_thirds_majority = Some(vote.block_id);
+        }
+
+        self.votes[index] = Some(vote);
+        Ok(AddVoteOutcome::Added)
+    }
+
+    /// Vote cast by the validator at the given index, if any
+    pub fn vote(&self, index: usize) -> Option<&Vote> {
+        self.votes.get(index)?.as_ref()
+    }
+
+    /// Total voting power of the votes in the set
+    pub fn sum(&self) -> u64 {
+        self.sum
+    }
+
+    /// Voting power of the votes for the given block ID (`None` for nil)
+    pub fn power_for(&self, block_id: Option<block::Id>) -> u64 {
+        self.power_by_block.get(&block_id).copied().unwrap_or(0)
+    }
+
+    /// The block ID (`None` for nil) which received +2/3 of the voting power,
+    /// if any
+    pub fn two_thirds_majority(&self) -> Option<Option<block::Id>> {
+        self.two_thirds_majority
+    }
+
+    /// Whether +2/3 of the voting power voted, for any block or nil
+    pub fn has_two_thirds_any(&self) -> bool {
+        self.is_two_thirds(self.sum)
+    }
+
+    /// Whether all validators voted
+    pub fn has_all(&self) -> bool {
+        self.votes.iter().all(Option::is_some)
+    }
+
+    /// Build the commit justifying the block which received +2/3 of the
+    /// precommits.
+    ///
+    /// Signatures are aligned with the validator set: validators which did not
+    /// vote, or voted for another block, are marked as absent.
+    pub fn make_commit(&self) -> Result<Commit, Error> {
+        if self.vote_type != vote::Type::Precommit {
+            return Err(Kind::InvalidVote
+                .context("commits can only be made from precommits")
+                .into());
+        }
+
+        let block_id = match self.two_thirds_majority {
+            Some(Some(block_id)) => block_id,
+            Some(None) => {
+                return Err(Kind::InvalidVote
+                    .context("+2/3 of the precommits are for nil")
+                    .into())
+            }
+            None => {
+                return Err(Kind::InvalidVote
+                    .context("no +2/3 majority of precommits")
+                    .into())
+            }
+        };
+
+        let signatures = self
+            .votes
+            .iter()
+            .map(|vote| match vote {
+                Some(vote) if vote.block_id == Some(block_id) => CommitSig::BlockIDFlagCommit {
+                    validator_address: vote.validator_address,
+                    timestamp: vote.timestamp.expect("votes in the set have a timestamp"),
+                    signature: vote.signature.clone(),
+                },
+                Some(vote) if vote.block_id.is_none() => CommitSig::BlockIDFlagNil {
+                    validator_address: vote.validator_address,
+                    timestamp: vote.timestamp.expect("votes in the set have a timestamp"),
+                    signature: vote.signature.clone(),
+                },
+                _ => CommitSig::BlockIDFlagAbsent,
+            })
+            .collect();
+
+        Ok(Commit {
+            height: self.height,
+            round: self.round,
+            block_id,
+            signatures,
+        })
+    }
+
+    fn is_two_thirds(&self, power: u64) -> bool {
+        let total = u128::from(self.validator_set.total_voting_power().value());
+        u128::from(power) * 3 > total * 2
+    }
+}
+
+impl TryFrom<&VoteSet> for Commit {
+    type Error = Error;
+
+    fn try_from(vote_set: &VoteSet) -> Result<Self, Self::Error> {
+        vote_set.make_commit()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::public_key::{Ed25519, PublicKey};
+    use crate::signature::DefaultVerifier;
+    use crate::{account, Signature, Time};
+    use ed25519_dalek::{Keypair, SecretKey, Signer};
+
+    const BLOCK_ID: &str = "26C0A41F3243C6BCD7AD2DFF8A8D83A71D29D307B5326C227F734A1A512FE47D";
+    const OTHER_BLOCK_ID: &str =
+        "C37A55FB95E9E5B5C4E5F9B5D8A1F8E9C7A0B3D2E1F4A5B6C7D8E9F0A1B2C3D4";
+
+    fn keypair(seed: u8) -> Keypair {
+        let secret = SecretKey::from_bytes(&[seed; 32]).unwrap();
+        let public = Ed25519::from(&secret);
+        Keypair { secret, public }
+    }
+
+    fn validator(keypair: &Keypair, power: u32) -> validator::Info {
+        validator::Info {
+            address: account::Id::from(keypair.public),
+            pub_key: PublicKey::Ed25519(keypair.public),
+            power: vote::Power::from(power),
+            proposer_priority: validator::ProposerPriority::from(0),
+        }
+    }
+
+    struct Fixture {
+        chain_id: chain::Id,
+        keypairs: Vec<Keypair>,
+        validator_set: validator::Set,
+    }
+
+    impl Fixture {
+        /// Four validators with powers 40, 30, 20 and 10
+        fn new() -> Self {
+            let keypairs = (1..=4).map(keypair).collect::<Vec<_>>();
+            let validators = keypairs
+                .iter()
+                .zip(&[40, 30, 20, 10])
+                .map(|(keypair, power)| validator(keypair, *power))
+                .collect();
+
+            Self {
+                chain_id: "test-chain".parse().unwrap(),
+                keypairs,
+                validator_set: validator::Set::without_proposer(validators).unwrap(),
+            }
+        }
+
+        fn vote_set(&self) -> VoteSet {
+            VoteSet::new(
+                self.chain_id.clone(),
+                block::Height::from(10_u32),
+                block::Round::default(),
+                vote::Type::Precommit,
+                self.validator_set.clone(),
+            )
+        }
+
+        /// Signed precommit of the validator at the given index
+        fn vote(&self, index: usize, block_id: Option<&str>) -> Vote {
+            let validator = &self.validator_set.validators()[index];
+            let keypair = self
+                .keypairs
+                .iter()
+                .find(|k| account::Id::from(k.public) == validator.address)
+                .unwrap();
+
+            let mut vote = Vote {
+                vote_type: vote::Type::Precommit,
+                height: block::Height::from(10_u32),
+                round: block::Round::default(),
+                block_id: block_id.map(|id| id.parse().unwrap()),
+                timestamp: Some(Time::unix_epoch()),
+                validator_address: validator.address,
+                validator_index: vote::ValidatorIndex::try_from(index).unwrap(),
+                signature: Signature::try_from(vec![0; 64]).unwrap(),
+            };
+            let signature = keypair.sign(&vote.to_signable_bytes(&self.chain_id));
+            vote.signature = Signature::try_from(signature.to_bytes().to_vec()).unwrap();
+            vote
+        }
+    }
+
+    #[test]
+    fn builds_commit_once_two_thirds_precommitted() {
+        let fixture = Fixture::new();
+        let mut vote_set = fixture.vote_set();
+
+        for (index, block_id) in &[(0, Some(BLOCK_ID)), (2, None), (3, Some(OTHER_BLOCK_ID))] {
+            let outcome = vote_set.add_vote(fixture.vote(*index, *block_id), &DefaultVerifier);
+            assert_eq!(outcome.unwrap(), AddVoteOutcome::Added);
+        }
+        assert_eq!(vote_set.two_thirds_majority(), None);
+        assert!(vote_set.make_commit().is_err());
+
+        vote_set
+            .add_vote(fixture.vote(1, Some(BLOCK_ID)), &DefaultVerifier)
+            .unwrap();
+        assert_eq!(vote_set.power_for(Some(BLOCK_ID.parse().unwrap())), 70);
+        assert_eq!(
+            vote_set.two_thirds_majority(),
+            Some(Some(BLOCK_ID.parse().unwrap()))
+        );
+
+        let commit = vote_set.make_commit().unwrap();
+        assert_eq!(commit.block_id, BLOCK_ID.parse().unwrap());
+        assert_eq!(commit.signatures.len(), 4);
+        assert!(commit.signatures[0].is_commit());
+        assert!(commit.signatures[1].is_commit());
+        assert!(commit.signatures[2].is_nil());
+        assert!(commit.signatures[3].is_absent());
+
+        // The commit reproduces the original votes
+        assert_eq!(commit.vote(1).as_ref(), vote_set.vote(1));
+    }
+
+    #[test]
+    fn reports_conflicting_votes_as_evidence() {
+        let fixture = Fixture::new();
+        let mut vote_set = fixture.vote_set();
+
+        let vote = fixture.vote(0, Some(BLOCK_ID));
+        vote_set.add_vote(vote.clone(), &DefaultVerifier).unwrap();
+        assert_eq!(
+            vote_set.add_vote(vote, &DefaultVerifier).unwrap(),
+            AddVoteOutcome::Duplicate
+        );
+
+        match vote_set
+            .add_vote(fixture.vote(0, Some(OTHER_BLOCK_ID)), &DefaultVerifier)
+            .unwrap()
+        {
+            AddVoteOutcome::Conflicting(evidence) => {
+                assert_eq!(evidence.validator_power, vote::Power::from(40_u32));
+                assert_eq!(evidence.total_voting_power, vote::Power::from(100_u32));
+            }
+            outcome => panic!("unexpected outcome: {:?}", outcome),
+        }
+        assert_eq!(vote_set.sum(), 40);
+    }
+
+    #[test]
+    fn rejects_invalid_votes() {
+        let fixture = Fixture::new();
+        let mut vote_set = fixture.vote_set();
+
+        let mut vote = fixture.vote(0, Some(BLOCK_ID));
+        vote.block_id = Some(OTHER_BLOCK_ID.parse().unwrap());
+        assert!(vote_set.add_vote(vote, &DefaultVerifier).is_err());
+
+        let mut vote = fixture.vote(0, Some(BLOCK_ID));
+        vote.validator_index = vote::ValidatorIndex::try_from(1_u32).unwrap();
+        assert!(vote_set.add_vote(vote, &DefaultVerifier).is_err());
+
+        let mut vote = fixture.vote(0, Some(BLOCK_ID));
+        vote.vote_type = vote::Type::Prevote;
+        assert!(vote_set.add_vote(vote, &DefaultVerifier).is_err());
+
+        assert_eq!(vote_set.sum(), 0);
+    }
+}
@@ -0,0 +1,80 @@
+use crate::error::{Error, Kind};
+use std::convert::TryInto;
+use std::{
+    convert::TryFrom,
+    fmt::{self, Debug, Display},
+};
+
+extern crate prusti_contracts;
+use prusti_contracts::*;
+
+/// ValidatorIndex for a particular Vote
+#[derive(Copy, Clone, Eq, Hash, PartialEq, PartialOrd, Ord)]
+pub struct ValidatorIndex(u32);
+
+impl TryFrom<i32> for ValidatorIndex {
+    type Error = Error;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        Ok(ValidatorIndex(
+            value.try_into().map_err(|_| Kind::NegativeValidatorIndex)?,
+        ))
+    }
+}
+
+impl From<ValidatorIndex> for i32 {
+    fn from(value: ValidatorIndex) -> Self {
+        value.value() as i32 // does not overflow. The value is <= i32::MAX
+    }
+}
+
+impl TryFrom<u32> for ValidatorIndex {
+    type Error = Error;
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        let _val: i32 = value.try_into().map_err(|_| Kind::IntegerOverflow)?;
+        Ok(ValidatorIndex(value))
+    }
+}
+
+impl From<ValidatorIndex> for u32 {
+    fn from(value: ValidatorIndex) -> Self {
+        value.value()
+    }
+}
+
+impl TryFrom<usize> for ValidatorIndex {
+    type Error = Error;
+
+    fn try_from(value: usize) -> Result<Self, Self::Error> {
+        let val: u32 = value.try_into().map_err(|_| Kind::IntegerOverflow)?;
+        ValidatorIndex::try_from(val)
+    }
+}
+
+impl From<ValidatorIndex> for usize {
+    fn from(value: ValidatorIndex) -> Self {
+        value.value() as usize
+    }
+}
+
+impl ValidatorIndex {
+    /// Get inner integer value. Alternative to `.0` or `.into()`
+    pub fn value(&self) -> u32 {
+        self.0
+    }
+}
+
+impl Debug for ValidatorIndex {
+    #[trusted]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "vote::ValidatorIndex({})", self.0)
+    }
+}
+
+impl Display for ValidatorIndex {
+    #[trusted]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
@@ -0,0 +1,25 @@
+//! Errors raised while generating test data
+
+use thiserror::Error;
+
+/// Errors raised while generating test data
+#[derive(Clone, Debug, Error, PartialEq)]
+pub enum Error {
+    /// The description could not be parsed
+    #[error("parse error: {0}")]
+    Parse(String),
+
+    /// The description is inconsistent, e.g. a signer is not a validator
+    #[error("invalid description: {0}")]
+    Invalid(String),
+
+    /// Building a tendermint type from the generated data failed
+    #[error("tendermint error: {0}")]
+    Tendermint(String),
+}
+
+impl From<tendermint::Error> for Error {
+    fn from(e: tendermint::Error) -> Self {
+        Error::Tendermint(e.to_string())
+    }
+}
@@ -0,0 +1,41 @@
+//! Test data generator for the light client.
+//!
+//! Produces fully signed light blocks from compact descriptions, given either
+//! as JSON or with builders, so that verifier tests can run without a chain:
+//!
+//! - [`Validator`]: a validator with a deterministic key derived from its ID
+//! - [`LightBlock`]: a light block at a given height and time, signed by some
+//!   of its validators, possibly with [`Fault`]s injected
+//! - [`LightChain`]: a sequence of hash-linked light blocks, with validator
+//!   set changes at given heights
+
+mod error;
+pub mod light_block;
+pub mod light_chain;
+pub mod validator;
+
+pub use error::Error;
+pub use light_block::{Fault, LightBlock, TMLightBlock};
+pub use light_chain::LightChain;
+pub use validator::Validator;
+
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Chain ID of the generated blocks, unless specified otherwise
+pub const DEFAULT_CHAIN_ID: &str = "test-chain";
+
+/// Description of some test data, which can be generated into its `Output`.
+pub trait Generator<Output>: Serialize + DeserializeOwned + Sized {
+    /// Generate the test data from this description
+    fn generate(&self) -> Result<Output, Error>;
+
+    /// Parse a description from JSON
+    fn from_json(json: &str) -> Result<Self, Error> {
+        serde_json::from_str(json).map_err(|e| Error::Parse(e.to_string()))
+    }
+
+    /// Encode this description as JSON
+    fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("descriptions can always be encoded")
+    }
+}
@@ -0,0 +1,430 @@
+//! Signed light blocks, possibly with faults injected
+
+use std::collections::HashSet;
+use std::convert::TryFrom;
+use std::time::Duration;
+
+use ed25519_dalek::Signer;
+use serde::{Deserialize, Serialize};
+use tendermint::block::{self, signed_header::SignedHeader, Commit, CommitSig, Header};
+use tendermint::{account, chain, validator, vote, Hash, Signature, Time, Vote};
+
+use crate::validator::generate_validators;
+use crate::{Error, Generator, Validator, DEFAULT_CHAIN_ID};
+
+/// Faults which can be injected into a generated light block
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Fault {
+    /// The signature of the given validator does not verify
+    WrongSignature {
+        /// ID of the validator
+        validator: String,
+    },
+
+    /// Validators sign until just before they reach more than 2/3 of the
+    /// voting power, so that the commit does not have enough power
+    InsufficientPower,
+
+    /// The `validators_hash` of the header does not match its validator set
+    BadHash,
+}
+
+/// A generated light block: a signed header, with the validator set of its
+/// height.
+///
+/// This mirrors the `LightBlock` of the light client, which cannot be built
+/// here without making the light client depend on this crate for its own tests.
+/// Tests of the light client convert it with `LightBlock::new`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TMLightBlock {
+    /// Header and commit of this block
+    pub signed_header: SignedHeader,
+    /// Validator set at the block height
+    pub validators: validator::Set,
+}
+
+/// Description of a light block, signed by (some of) its validators
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LightBlock {
+    /// Validators of the block
+    pub validators: Vec<Validator>,
+
+    /// Validators of the next block, the same as `validators` by default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_validators: Option<Vec<Validator>>,
+
+    /// Chain ID, `DEFAULT_CHAIN_ID` by default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain_id: Option<String>,
+
+    /// Height, 1 by default
+    #[serde(default = "default_height")]
+    pub height: u64,
+
+    /// Time, in seconds since the UNIX epoch; the height by default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time: Option<u64>,
+
+    /// Hash of the previous block, in upper-case hex, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_block_hash: Option<String>,
+
+    /// IDs of the validators which sign the commit; all validators by default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signers: Option<Vec<String>>,
+
+    /// Faults to inject
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub faults: Vec<Fault>,
+}
+
+fn default_height() -> u64 {
+    1
+}
+
+impl LightBlock {
+    /// A light block at height 1 with the given validators, signed by all of them
+    pub fn new(validators: Vec<Validator>) -> Self {
+        Self {
+            validators,
+            next_validators: None,
+            chain_id: None,
+            height: default_height(),
+            time: None,
+            last_block_hash: None,
+            signers: None,
+            faults: vec![],
+        }
+    }
+
+    /// Set the validators of the next block
+    pub fn next_validators(mut self, next_validators: Vec<Validator>) -> Self {
+        self.next_validators = Some(next_validators);
+        self
+    }
+
+    /// Set the chain ID
+    pub fn chain_id(mut self, chain_id: &str) -> Self {
+        self.chain_id = Some(chain_id.to_owned());
+        self
+    }
+
+    /// Set the height
+    pub fn height(mut self, height: u64) -> Self {
+        self.height = height;
+        self
+    }
+
+    /// Set the time, in seconds since the UNIX epoch
+    pub fn time(mut self, time: u64) -> Self {
+        self.time = Some(time);
+        self
+    }
+
+    /// Set the validators which sign the commit
+    pub fn signers(mut self, signers: &[&str]) -> Self {
+        self.signers = Some(signers.iter().map(|id| (*id).to_owned()).collect());
+        self
+    }
+
+    /// Inject the given fault
+    pub fn fault(mut self, fault: Fault) -> Self {
+        self.faults.push(fault);
+        self
+    }
+
+    /// Description of the next block: one height and one second later, with
+    /// the next validators of this block, signed by all of them, and linked to
+    /// this block by its hash
+    pub fn next(&self) -> Result<Self, Error> {
+        let header_hash = self.generate()?.signed_header.header.hash();
+
+        Ok(Self {
+            validators: self.next_validators_or_default().to_vec(),
+            next_validators: None,
+            chain_id: self.chain_id.clone(),
+            height: self.height + 1,
+            time: Some(self.time_secs() + 1),
+            last_block_hash: Some(header_hash.to_string()),
+            signers: None,
+            faults: vec![],
+        })
+    }
+
+    fn next_validators_or_default(&self) -> &[Validator] {
+        self.next_validators.as_ref().unwrap_or(&self.validators)
+    }
+
+    fn time_secs(&self) -> u64 {
+        self.time.unwrap_or(self.height)
+    }
+
+    /// IDs of the validators signing the commit, before faults are injected
+    fn signer_ids(&self) -> Result<HashSet<String>, Error> {
+        let ids = match &self.signers {
+            Some(signers) => signers.iter().cloned().collect::<HashSet<_>>(),
+            None => self.validators.iter().map(|v| v.id.clone()).collect(),
+        };
+
+        for id in &ids {
+            if !self.validators.iter().any(|v| &v.id == id) {
+                return Err(Error::Invalid(format!("signer {} is not a validator", id)));
+            }
+        }
+
+        if !self.faults.contains(&Fault::InsufficientPower) {
+            return Ok(ids);
+        }
+
+        // Keep signers, heaviest first, as long as they stay below +2/3 of the power
+        let total: u64 = self.validators.iter().map(|v| v.voting_power).sum();
+        let mut signers = self
+            .validators
+            .iter()
+            .filter(|v| ids.contains(&v.id))
+            .collect::<Vec<_>>();
+        signers.sort_by_key(|v| std::cmp::Reverse(v.voting_power));
+
+        let mut power = 0;
+        Ok(signers
+            .into_iter()
+            .take_while(|v| {
+                power += v.voting_power;
+                power * 3 <= total * 2
+            })
+            .map(|v| v.id.clone())
+            .collect())
+    }
+}
+
+impl Generator<TMLightBlock> for LightBlock {
+    fn generate(&self) -> Result<TMLightBlock, Error> {
+        let chain_id: chain::Id = self
+            .chain_id
+            .as_deref()
+            .unwrap_or(DEFAULT_CHAIN_ID)
+            .parse()?;
+        let height = block::Height::try_from(self.height)?;
+        let time = Time::unix_epoch()
+            .checked_add(Duration::from_secs(self.time_secs()))
+            .ok_or_else(|| Error::Invalid(format!("time {} is out of range", self.time_secs())))?;
+        let validator_set = generate_validators(&self.validators)?;
+
+        let validators_hash = if self.faults.contains(&Fault::BadHash) {
+            generate_validators(&[Validator::new("bad hash")])?.hash()
+        } else {
+            validator_set.hash()
+        };
+
+        let last_block_id = match &self.last_block_hash {
+            Some(hash) => Some(block::Id {
+                hash: hash.parse::<Hash>()?,
+                part_set_header: Default::default(),
+            }),
+            None => None,
+        };
+
+        let header = Header {
+            version: Default::default(),
+            chain_id: chain_id.clone(),
+            height,
+            time,
+            last_block_id,
+            last_commit_hash: Hash::None,
+            data_hash: Hash::None,
+            validators_hash,
+            next_validators_hash: generate_validators(self.next_validators_or_default())?.hash(),
+            consensus_hash: Hash::None,
+            app_hash: vec![],
+            last_results_hash: Hash::None,
+            evidence_hash: Hash::None,
+            proposer_address: validator_set
+                .validators()
+                .first()
+                .map_or(account::Id::new([0; 20]), |info| info.address),
+        };
+        let block_id = block::Id {
+            hash: header.hash(),
+            part_set_header: Default::default(),
+        };
+
+        let signer_ids = self.signer_ids()?;
+        let wrong_signers = self
+            .faults
+            .iter()
+            .filter_map(|fault| match fault {
+                Fault::WrongSignature { validator } => Some(validator.as_str()),
+                _ => None,
+            })
+            .collect::<HashSet<_>>();
+
+        let mut signatures = vec![];
+        for (index, info) in validator_set.validators().iter().enumerate() {
+            let validator = match self.validators.iter().find(|v| v.address() == info.address) {
+                Some(validator) if signer_ids.contains(&validator.id) => validator,
+                _ => {
+                    signatures.push(CommitSig::BlockIDFlagAbsent);
+                    continue;
+                }
+            };
+
+            let vote = Vote {
+                vote_type: vote::Type::Precommit,
+                height,
+                round: block::Round::default(),
+                block_id: Some(block_id),
+                timestamp: Some(time),
+                validator_address: info.address,
+                validator_index: vote::ValidatorIndex::try_from(index)?,
+                signature: Signature::try_from(vec![0; 64])?,
+            };
+            let mut signature = validator
+                .keypair()
+                .sign(&vote.to_signable_bytes(&chain_id))
+                .to_bytes()
+                .to_vec();
+            if wrong_signers.contains(validator.id.as_str()) {
+                signature[0] ^= 0xFF;
+            }
+
+            signatures.push(CommitSig::BlockIDFlagCommit {
+                validator_address: info.address,
+                timestamp: time,
+                signature: Signature::try_from(signature)?,
+            });
+        }
+
+        let commit = Commit {
+            height,
+            round: block::Round::default(),
+            block_id,
+            signatures,
+        };
+
+        Ok(TMLightBlock {
+            signed_header: SignedHeader::new(header, commit)?,
+            validators: validator_set,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tendermint::signature::DefaultVerifier;
+    use tendermint_light_client::components::verifier::{
+        voting_power_in, ProdVerifier, Verdict, VerificationError, Verifier,
+    };
+    use tendermint_light_client::light_client::Options;
+    use tendermint_light_client::types::{self, TrustThresholdFraction};
+
+    fn validators() -> Vec<Validator> {
+        ["a", "b", "c", "d"]
+            .iter()
+            .map(|id| Validator::new(id))
+            .collect()
+    }
+
+    fn options() -> Options {
+        Options {
+            chain_id: DEFAULT_CHAIN_ID.parse().unwrap(),
+            trust_threshold: TrustThresholdFraction::default(),
+            trusting_period: Duration::from_secs(3600),
+            clock_drift: Options::DEFAULT_CLOCK_DRIFT,
+        }
+    }
+
+    fn light_block(description: &LightBlock) -> types::LightBlock {
+        let generated = description.generate().unwrap();
+        types::LightBlock::new(generated.signed_header, generated.validators)
+    }
+
+    fn verify(untrusted: &LightBlock, trusted: &LightBlock) -> Verdict {
+        ProdVerifier::default().verify(
+            &light_block(untrusted),
+            &light_block(trusted),
+            &options(),
+            Time::unix_epoch()
+                .checked_add(Duration::from_secs(100))
+                .unwrap(),
+        )
+    }
+
+    #[test]
+    fn generated_blocks_verify() {
+        let trusted = LightBlock::new(validators());
+        let untrusted = trusted.next().unwrap();
+
+        let generated = untrusted.generate().unwrap();
+        assert_eq!(
+            generated.signed_header.header.last_block_id.unwrap().hash,
+            trusted.generate().unwrap().signed_header.header.hash()
+        );
+        assert_eq!(verify(&untrusted, &trusted), Verdict::Success);
+    }
+
+    #[test]
+    fn faults_are_injected() {
+        let trusted = LightBlock::new(validators());
+
+        let untrusted = trusted.next().unwrap().fault(Fault::WrongSignature {
+            validator: "b".to_owned(),
+        });
+        assert!(matches!(verify(&untrusted, &trusted), Verdict::Invalid(_)));
+
+        let untrusted = trusted.next().unwrap().fault(Fault::InsufficientPower);
+        let generated = untrusted.generate().unwrap();
+        let tally = voting_power_in(
+            &generated.signed_header,
+            &generated.validators,
+            &DefaultVerifier,
+        )
+        .unwrap();
+        assert_eq!((tally.tallied, tally.total), (2, 4));
+        assert!(matches!(verify(&untrusted, &trusted), Verdict::Invalid(_)));
+
+        let untrusted = trusted.next().unwrap().fault(Fault::BadHash);
+        let generated = untrusted.generate().unwrap();
+        assert_ne!(
+            generated.signed_header.header.validators_hash,
+            generated.validators.hash()
+        );
+        assert_eq!(
+            verify(&untrusted, &trusted),
+            Verdict::Invalid(VerificationError::InvalidValidatorSet {
+                header_validators_hash: generated.signed_header.header.validators_hash,
+                validators_hash: generated.validators.hash(),
+            })
+        );
+    }
+
+    #[test]
+    fn json_descriptions() {
+        let json = r#"{
+            "validators": [{"id": "a", "voting_power": 2}, {"id": "b"}],
+            "height": 5,
+            "signers": ["a"],
+            "faults": [{"type": "wrong_signature", "validator": "a"}]
+        }"#;
+
+        let description = LightBlock::from_json(json).unwrap();
+        assert_eq!(description.height, 5);
+        assert_eq!(
+            description.faults,
+            vec![Fault::WrongSignature {
+                validator: "a".to_owned()
+            }]
+        );
+        assert_eq!(
+            LightBlock::from_json(&description.to_json()).unwrap(),
+            description
+        );
+
+        let generated = description.generate().unwrap();
+        assert!(generated.signed_header.commit.signatures[1].is_absent());
+
+        let unknown_signer = LightBlock::new(validators()).signers(&["z"]);
+        assert!(unknown_signer.generate().is_err());
+    }
+}
@@ -0,0 +1,151 @@
+//! Sequences of hash-linked light blocks
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, Fault, Generator, LightBlock, TMLightBlock, Validator};
+
+/// Description of a chain of light blocks at heights `1..=length`, each one
+/// second after the previous one and linked to it by its hash
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LightChain {
+    /// Validators of the first block
+    pub validators: Vec<Validator>,
+
+    /// Chain ID, `DEFAULT_CHAIN_ID` by default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain_id: Option<String>,
+
+    /// Number of blocks
+    pub length: u64,
+
+    /// New validator sets, by the height from which they are in effect
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub validator_changes: BTreeMap<u64, Vec<Validator>>,
+
+    /// Faults to inject, by height
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub faults: BTreeMap<u64, Vec<Fault>>,
+}
+
+impl LightChain {
+    /// A chain of the given length, with the given validators throughout
+    pub fn new(validators: Vec<Validator>, length: u64) -> Self {
+        Self {
+            validators,
+            chain_id: None,
+            length,
+            validator_changes: BTreeMap::new(),
+            faults: BTreeMap::new(),
+        }
+    }
+
+    /// Set the chain ID
+    pub fn chain_id(mut self, chain_id: &str) -> Self {
+        self.chain_id = Some(chain_id.to_owned());
+        self
+    }
+
+    /// Replace the validators from the given height onwards
+    pub fn change_validators(mut self, height: u64, validators: Vec<Validator>) -> Self {
+        self.validator_changes.insert(height, validators);
+        self
+    }
+
+    /// Inject the given fault in the block at the given height
+    pub fn fault(mut self, height: u64, fault: Fault) -> Self {
+        self.faults.entry(height).or_default().push(fault);
+        self
+    }
+
+    /// Validators in effect at the given height
+    fn validators_at(&self, height: u64) -> &[Validator] {
+        self.validator_changes
+            .range(..=height)
+            .next_back()
+            .map(|(_, validators)| validators.as_slice())
+            .unwrap_or(&self.validators)
+    }
+
+    /// Descriptions of the blocks of this chain
+    pub fn light_blocks(&self) -> Result<Vec<LightBlock>, Error> {
+        let mut blocks: Vec<LightBlock> = vec![];
+
+        for height in 1..=self.length {
+            let mut block = match blocks.last() {
+                Some(previous) => previous.next()?,
+                None => {
+                    let mut first = LightBlock::new(self.validators_at(1).to_vec());
+                    first.chain_id = self.chain_id.clone();
+                    first
+                }
+            };
+
+            block.next_validators = Some(self.validators_at(height + 1).to_vec());
+            block.faults = self.faults.get(&height).cloned().unwrap_or_default();
+            blocks.push(block);
+        }
+
+        Ok(blocks)
+    }
+}
+
+impl Generator<Vec<TMLightBlock>> for LightChain {
+    fn generate(&self) -> Result<Vec<TMLightBlock>, Error> {
+        self.light_blocks()?
+            .iter()
+            .map(Generator::generate)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validator_changes_take_effect_at_their_height() {
+        let chain = LightChain::new(vec![Validator::new("a")], 4)
+            .change_validators(3, vec![Validator::new("b"), Validator::new("c")]);
+
+        let blocks = chain.generate().unwrap();
+        assert_eq!(blocks.len(), 4);
+
+        let validator_counts = blocks
+            .iter()
+            .map(|block| block.validators.validators().len())
+            .collect::<Vec<_>>();
+        assert_eq!(validator_counts, vec![1, 1, 2, 2]);
+
+        // The block before the change announces it
+        assert_eq!(
+            blocks[1].signed_header.header.next_validators_hash,
+            blocks[2].validators.hash()
+        );
+
+        for pair in blocks.windows(2) {
+            assert_eq!(
+                pair[1].signed_header.header.last_block_id.unwrap().hash,
+                pair[0].signed_header.header.hash()
+            );
+        }
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let chain = LightChain::new(vec![Validator::new("a")], 2)
+            .chain_id("other-chain")
+            .fault(2, Fault::BadHash);
+
+        let json = chain.to_json();
+        assert!(json.contains("bad_hash"));
+        assert_eq!(LightChain::from_json(&json).unwrap(), chain);
+
+        let blocks = chain.generate().unwrap();
+        assert_eq!(
+            blocks[1].signed_header.header.chain_id.as_str(),
+            "other-chain"
+        );
+    }
+}
@@ -0,0 +1,93 @@
+//! Validators with deterministic keys
+
+use std::convert::TryFrom;
+
+use ed25519_dalek::{Keypair, SecretKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tendermint::public_key::Ed25519;
+use tendermint::{account, validator, vote, PublicKey};
+
+use crate::{Error, Generator};
+
+/// A validator, identified by an arbitrary ID from which its key is derived
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Validator {
+    /// Identifier of the validator, e.g. `"a"`
+    pub id: String,
+
+    /// Voting power of the validator, 1 by default
+    #[serde(default = "default_voting_power")]
+    pub voting_power: u64,
+}
+
+fn default_voting_power() -> u64 {
+    1
+}
+
+impl Validator {
+    /// A validator with the given ID and a voting power of 1
+    pub fn new(id: &str) -> Self {
+        Self {
+            id: id.to_owned(),
+            voting_power: default_voting_power(),
+        }
+    }
+
+    /// Set the voting power of this validator
+    pub fn voting_power(mut self, voting_power: u64) -> Self {
+        self.voting_power = voting_power;
+        self
+    }
+
+    /// The key pair of this validator: its secret key is the SHA-256 hash of its ID
+    pub fn keypair(&self) -> Keypair {
+        let seed = Sha256::digest(self.id.as_bytes());
+        let secret = SecretKey::from_bytes(&seed).expect("any 32 bytes are a secret key");
+        let public = Ed25519::from(&secret);
+        Keypair { secret, public }
+    }
+
+    /// The address of this validator
+    pub fn address(&self) -> account::Id {
+        account::Id::from(self.keypair().public)
+    }
+}
+
+impl Generator<validator::Info> for Validator {
+    fn generate(&self) -> Result<validator::Info, Error> {
+        let public = self.keypair().public;
+        Ok(validator::Info {
+            address: account::Id::from(public),
+            pub_key: PublicKey::Ed25519(public),
+            power: vote::Power::try_from(self.voting_power)?,
+            proposer_priority: validator::ProposerPriority::from(0),
+        })
+    }
+}
+
+/// Generate the validator set of the given validators
+pub fn generate_validators(validators: &[Validator]) -> Result<validator::Set, Error> {
+    let validators = validators
+        .iter()
+        .map(Generator::generate)
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(validator::Set::without_proposer(validators)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_are_deterministic() {
+        let a = Validator::new("a");
+        assert_eq!(a.address(), Validator::new("a").voting_power(10).address());
+        assert_ne!(a.address(), Validator::new("b").address());
+
+        let json = r#"{"id": "a"}"#;
+        let parsed = Validator::from_json(json).unwrap();
+        assert_eq!(parsed, a);
+        assert_eq!(parsed.generate().unwrap().power(), 1);
+    }
+}